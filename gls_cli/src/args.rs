@@ -0,0 +1,84 @@
+use std::{collections::HashMap, str::FromStr};
+
+// minimal `--name value` / `--flag` parser, anything else is positional
+pub struct Args {
+	positional: Vec<String>,
+	values: HashMap<String, String>,
+	flags: Vec<String>,
+}
+
+impl Args {
+	pub fn parse(raw: &[String], options: &[&str], flags: &[&str]) -> Result<Self, String> {
+		let mut positional = Vec::new();
+		let mut values = HashMap::new();
+		let mut set_flags = Vec::new();
+
+		let mut iter = raw.iter();
+		while let Some(arg) = iter.next() {
+			if let Some(name) = arg.strip_prefix("--") {
+				if flags.contains(&name) {
+					set_flags.push(name.to_owned());
+				}
+				else if options.contains(&name) {
+					let value = iter.next().ok_or(format!("Missing value for --{name}"))?;
+					values.insert(name.to_owned(), value.clone());
+				}
+				else {
+					return Err(format!("Unknown option --{name}"));
+				}
+			}
+			else {
+				positional.push(arg.clone());
+			}
+		}
+
+		Ok(Self {
+			positional,
+			values,
+			flags: set_flags,
+		})
+	}
+
+	pub fn positional(&self, i: usize, name: &str) -> Result<&str, String> {
+		self.positional.get(i).map(|s| s.as_str()).ok_or(format!("Missing <{name}>"))
+	}
+
+	pub fn expect_positional(&self, count: usize) -> Result<(), String> {
+		if self.positional.len() > count {
+			return Err(format!("Unexpected argument {}", self.positional[count]));
+		}
+		Ok(())
+	}
+
+	pub fn value<T: FromStr>(&self, name: &str) -> Result<Option<T>, String> {
+		match self.values.get(name) {
+			Some(value) => value.parse().map(Some).map_err(|_| format!("Invalid value for --{name}: {value}")),
+			None => Ok(None),
+		}
+	}
+
+	pub fn value_or<T: FromStr>(&self, name: &str, default: T) -> Result<T, String> {
+		Ok(self.value(name)?.unwrap_or(default))
+	}
+
+	pub fn string(&self, name: &str) -> Option<&str> {
+		self.values.get(name).map(|s| s.as_str())
+	}
+
+	pub fn required(&self, name: &str) -> Result<&str, String> {
+		self.string(name).ok_or(format!("Missing --{name}"))
+	}
+
+	pub fn flag(&self, name: &str) -> bool {
+		self.flags.iter().any(|f| f == name)
+	}
+
+	// "x,y" pairs such as the goal origin
+	pub fn pair(&self, name: &str) -> Result<Option<[u32; 2]>, String> {
+		let Some(value) = self.string(name) else { return Ok(None); };
+		let invalid = || format!("Invalid value for --{name}: {value}, expected x,y");
+
+		let (x, y) = value.split_once(',').ok_or_else(invalid)?;
+		Ok(Some([x.trim().parse().map_err(|_| invalid())?, y.trim().parse().map_err(|_| invalid())?]))
+	}
+}
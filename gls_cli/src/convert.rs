@@ -0,0 +1,13 @@
+use crate::{args::Args, files::{load_file, save_file}};
+
+pub const USAGE: &str = "gls convert <input> <output>
+	Translates between the binary (.gls) and text (.json) formats, chosen by extension.
+	Headerless files from older versions are upgraded to the current format.";
+
+pub fn run(raw: &[String]) -> Result<(), String> {
+	let args = Args::parse(raw, &[], &[])?;
+	args.expect_positional(2)?;
+
+	let file = load_file(args.positional(0, "input")?)?;
+	save_file(&file, args.positional(1, "output")?)
+}
@@ -0,0 +1,272 @@
+use std::{fs, time::{Duration, Instant, SystemTime, UNIX_EPOCH}};
+
+use soft_evolution::{genetic_algorithm::{GeneticAlgorithm, selection::Selection, stopping::StopCondition, islands::{Islands, Topology}, statistics::save_csv}, l_system::{cell::Cell, ruleset::{Ruleset, Symmetry}}, ls_evolve::{LS, Growth, EvolveParams}, gls_file::{GlsFile, GrowthSettings, EvolutionSettings}};
+
+use crate::{args::Args, files::{extension, load_file, load_template, save_file, unix_time}};
+
+pub const USAGE: &str = "gls evolve --goal <template.txt|file> --out <best.gls|best.json> [options]
+gls evolve --resume <run.glsrun> --out <best.gls|best.json> [options]
+	Evolves a ruleset growing into the goal and writes the best one found.
+	--origin X,Y          origin of a .txt goal, defaults to its centre
+	--from FILE           starts from mutants of the rules and axiom in a .gls or .json file instead of random rulesets
+	--generations N       generation budget, counted from the start of a resumed run
+	--time SECONDS        wall-clock budget, when neither is given 100 generations are run
+	--target F            stops once the best fitness reaches F
+	--stagnation N        stops after N generations without improvement of the best fitness
+	--min-diversity F     stops once the mean genotype distance between agents falls to F
+	--seed N              random seed, printed when not given
+	--population N        agents per generation of every island (1000)
+	--survivors N         agents kept between generations (500)
+	--selection NAME      tournament, tournament-replace, roulette, rank, truncation or boltzmann (tournament)
+	--tournament N        tournament size (2)
+	--temperature F       boltzmann selection temperature (1.0)
+	--elites N            fittest agents always kept (1)
+	--mutation F          mutation factor (0.5)
+	--crossover F         share of offspring recombined from two parents (0.0)
+	--max-steps N         growth steps per evaluation (25)
+	--same-weight F       fitness per matching cell (1.0)
+	--different-weight F  fitness per mismatched cell (-10.0)
+	--size-weight F       fitness per rule size (-0.5)
+	--size-pow F          exponent of rule size (1.5)
+	--symmetry NAME       none, mirror or rotational, makes the rules symmetric before they grow (none)
+	--name TEXT           name saved in the file metadata
+	--stats FILE.csv      per-generation statistics of the best island
+	--multi-objective     rank by non-dominated sorting of matching cells, mismatched cells and rule size, ignoring the weights
+	--novelty F           weight of novelty against goal fitness, 1 searches for novelty alone (0.0)
+	--neighbours N        nearest neighbours novelty is measured against (15)
+	--islands N           separately evolving populations (1)
+	--migration-interval N  generations between migrations of the best agents to other islands (10)
+	--migrants N          agents every island sends (5)
+	--topology NAME       ring or random (ring)
+	--adaptive-mutations  let the mutation operator weights follow their recent success rates
+	--serial              evaluate fitnesses and islands on a single thread
+	--checkpoint FILE     periodically saves the whole run to be resumed later
+	--checkpoint-interval N  generations between checkpoints (50), one is always saved at the end
+	--resume FILE         continues a checkpointed run with its saved goal and settings, only the budget, output and checkpoint options apply";
+
+pub fn run(raw: &[String]) -> Result<(), String> {
+	let args = Args::parse(raw, &[
+		"goal", "origin", "out", "generations", "time", "seed", "population", "survivors", "selection", "tournament", "temperature", "elites", "mutation", "crossover",
+		"novelty", "neighbours", "islands", "migration-interval", "migrants", "topology", "max-steps", "same-weight", "different-weight", "size-weight", "size-pow", "symmetry", "name", "stats",
+		"resume", "checkpoint", "checkpoint-interval", "target", "stagnation", "min-diversity", "from",
+	], &["serial", "multi-objective", "adaptive-mutations"])?;
+	args.expect_positional(0)?;
+
+	let out = args.required("out")?;
+
+	let time: Option<f64> = args.value("time")?;
+	let generations: Option<u32> = args.value("generations")?;
+	let generations = if time.is_none() { Some(generations.unwrap_or(100)) } else { generations };
+	let time = time.map(Duration::from_secs_f64);
+
+	let checkpoint = args.string("checkpoint");
+	let checkpoint_interval: u32 = args.value_or("checkpoint-interval", 50)?;
+
+	let mut islands = match args.string("resume") {
+		Some(path) => {
+			let data = fs::read(path).map_err(|e| format!("{path}: {e}"))?;
+			let islands = Islands::deserialize(&data).map_err(|e| format!("{path}: {e}"))?;
+			eprintln!("resuming at generation {}", islands.generation_number());
+			islands
+		},
+		None => new_islands(&args)?,
+	};
+
+	let start = Instant::now();
+	let first = &islands.islands()[0];
+	let params = first.params().clone();
+	let multi_objective = first.multi_objective;
+	let (population, survivors, mutation) = (first.generation_count, first.survivors_count, first.mutation_factor);
+	let tournament = match first.selection {
+		Selection::Tournament { size, .. } => size,
+		_ => 2,
+	};
+
+	let mut conditions = Vec::new();
+	conditions.extend(generations.map(StopCondition::GenerationCap));
+	conditions.extend(time.map(StopCondition::TimeLimit));
+	conditions.extend(args.value("target")?.map(StopCondition::TargetFitness));
+	conditions.extend(args.value("stagnation")?.map(StopCondition::Stagnation));
+	conditions.extend(args.value("min-diversity")?.map(StopCondition::DiversityCollapse));
+
+	let reason = loop {
+		if let Some(reason) = islands.stop_reason(&conditions, start.elapsed()) { break reason; }
+
+		islands.perform_generation();
+		let gen_alg = &islands.islands()[islands.best_island()];
+		if multi_objective {
+			let front: usize = islands.islands().iter().map(|i| i.pareto_front().len()).sum();
+			eprintln!("generation {:>6}  front {:>6}", islands.generation_number(), front);
+		}
+		else {
+			eprintln!("generation {:>6}  best {:>10.2}  median {:>10.2}", islands.generation_number(), gen_alg.best().1, gen_alg.median().1);
+		}
+
+		if let Some(path) = checkpoint {
+			if checkpoint_interval > 0 && islands.generation_number().is_multiple_of(checkpoint_interval) {
+				save_checkpoint(&islands, path)?;
+			}
+		}
+	};
+	eprintln!("stopped: {reason}");
+
+	if let Some(path) = checkpoint {
+		save_checkpoint(&islands, path)?;
+	}
+
+	let (best, fitness) = if multi_objective {
+		// of the fronts, the agent the weights prefer is saved
+		let front: usize = islands.islands().iter().map(|i| i.pareto_front().len()).sum();
+		eprintln!("{front} agents on the pareto fronts");
+		islands.islands().iter().map(|i| best_on_front(i, &params)).max_by(|a, b| a.1.total_cmp(&b.1)).unwrap()
+	}
+	else {
+		let (best, fitness) = islands.best();
+		(best, *fitness)
+	};
+	eprintln!("best fitness {fitness:.2} after {} generations, {:.1}s", islands.generation_number(), start.elapsed().as_secs_f64());
+	let hit_rate = islands.islands().iter().map(|i| i.fitness_cache.hit_rate()).sum::<f32>() / islands.islands().len() as f32;
+	eprintln!("fitness cache hits {:.0}%", hit_rate * 100.0);
+	let mutations = &islands.islands()[0].mutations;
+	for (i, name) in mutations.names.iter().enumerate() {
+		let (uses, improvements) = islands.islands().iter()
+			.map(|island| island.mutations.stats()[i])
+			.fold((0, 0), |(uses, improvements), stats| (uses + stats.uses, improvements + stats.improvements));
+		eprintln!("{name:<16} {uses:>8} uses  {:>5.1}% improved", improvements as f32 / uses.max(1) as f32 * 100.0);
+	}
+
+	if let Some(path) = args.string("stats") {
+		save_csv(islands.islands()[islands.best_island()].history(), path)?;
+	}
+
+	let mut file = GlsFile::new(best.0.rules().into());
+	file.axiom = best.1.axiom.clone();
+	file.goal = Some(params.goal.clone());
+	file.growth = Some(GrowthSettings { max_steps: best.1.steps(params.max_steps) as u32 });
+	file.evolution = Some(EvolutionSettings {
+		generation_count: population as u32,
+		survivors_count: survivors as u32,
+		tournament_size: tournament as u32,
+		mutation_factor: mutation,
+		same_weight: params.same_weight,
+		different_weight: params.different_weight,
+		size_weight: params.size_weight,
+		size_pow: params.size_pow,
+	});
+	file.metadata.name = args.string("name").unwrap_or("").to_owned();
+	let seed = islands.seed().map_or("unknown".to_owned(), |seed| seed.to_string());
+	file.metadata.notes = format!("evolved with seed {seed} for {} generations, fitness {fitness:.2}", islands.generation_number());
+	file.metadata.created = unix_time();
+
+	save_file(&file, out)
+}
+
+fn new_islands(args: &Args) -> Result<Islands<LS, EvolveParams>, String> {
+	let goal_path = args.required("goal")?;
+	let goal = if extension(goal_path) == "txt" {
+		load_template(goal_path, args.pair("origin")?)?
+	}
+	else {
+		load_file(goal_path)?.goal.ok_or(format!("{goal_path}: file has no goal"))?
+	};
+
+	let mut params = EvolveParams::new(goal);
+	params.max_steps = args.value_or("max-steps", params.max_steps)?;
+	params.same_weight = args.value_or("same-weight", params.same_weight)?;
+	params.different_weight = args.value_or("different-weight", params.different_weight)?;
+	params.size_weight = args.value_or("size-weight", params.size_weight)?;
+	params.size_pow = args.value_or("size-pow", params.size_pow)?;
+	if let Some(name) = args.string("symmetry") {
+		params.symmetry = Symmetry::from_name(name).ok_or(format!("Unknown symmetry {name}"))?;
+	}
+
+	let population: usize = args.value_or("population", 1000)?;
+	let survivors: usize = args.value_or("survivors", population / 2)?;
+	let tournament: usize = args.value_or("tournament", 2)?;
+	let temperature: f32 = args.value_or("temperature", 1.0)?;
+	let selection = match args.string("selection").unwrap_or("tournament") {
+		"tournament" => Selection::Tournament { size: tournament, replacement: false },
+		"tournament-replace" => Selection::Tournament { size: tournament, replacement: true },
+		"roulette" => Selection::Roulette,
+		"rank" => Selection::Rank,
+		"truncation" => Selection::Truncation,
+		"boltzmann" => Selection::Boltzmann { temperature },
+		other => return Err(format!("Unknown selection strategy {other}")),
+	};
+	let mutation: f32 = args.value_or("mutation", 0.5)?;
+	if population < 2 || survivors < 1 || survivors >= population {
+		return Err("Survivors must be at least 1 and fewer than the population".into());
+	}
+	if tournament < 2 || tournament >= population {
+		return Err("Tournament size must be at least 2 and smaller than the population".into());
+	}
+
+	let seed = match args.value("seed")? {
+		Some(seed) => seed,
+		None => {
+			let seed = rand_seed();
+			eprintln!("seed: {seed}");
+			seed
+		},
+	};
+
+	let novelty_weight: f32 = args.value_or("novelty", 0.0)?;
+	if !(0.0..=1.0).contains(&novelty_weight) {
+		return Err("Novelty weight must be between 0 and 1".into());
+	}
+	let crossover_rate: f32 = args.value_or("crossover", 0.0)?;
+	if !(0.0..=1.0).contains(&crossover_rate) {
+		return Err("Crossover rate must be between 0 and 1".into());
+	}
+
+	let mut islands = Islands::<LS, EvolveParams>::new_seeded(args.value_or("islands", 1)?, population, survivors, mutation, params, seed);
+	islands.migration_interval = args.value_or("migration-interval", 10)?;
+	islands.migrants = args.value_or("migrants", 5)?;
+	islands.topology = match args.string("topology").unwrap_or("ring") {
+		"ring" => Topology::Ring,
+		"random" => Topology::Random,
+		other => return Err(format!("Unknown topology {other}")),
+	};
+	islands.parallel = !args.flag("serial");
+
+	for gen_alg in islands.islands_mut() {
+		gen_alg.selection = selection;
+		gen_alg.elite_count = args.value_or("elites", 1)?;
+		gen_alg.parallel = !args.flag("serial");
+		gen_alg.multi_objective = args.flag("multi-objective");
+		gen_alg.novelty_weight = novelty_weight;
+		gen_alg.novelty_neighbours = args.value_or("neighbours", 15)?;
+		gen_alg.crossover_rate = crossover_rate;
+		gen_alg.mutations.adaptive = args.flag("adaptive-mutations");
+	}
+
+	if let Some(path) = args.string("from") {
+		let file = load_file(path)?;
+		let (mut rules, mut growth) = (file.rules, Growth::new(file.axiom));
+		if !growth.axiom.contents().iter().any(|cell| matches!(cell, Cell::Stem(_, _))) {
+			return Err(format!("{path}: the axiom has no stems to grow from"));
+		}
+		rules.canonicalize_with(&mut growth.axiom);
+		islands.reset_from_seed(&LS::with_growth(rules, growth));
+	}
+
+	Ok(islands)
+}
+
+// written next to the target first so an interrupted save never leaves a truncated checkpoint
+fn save_checkpoint(islands: &Islands<LS, EvolveParams>, path: &str) -> Result<(), String> {
+	let temp = format!("{path}.tmp");
+	fs::write(&temp, islands.serialize()).map_err(|e| format!("{temp}: {e}"))?;
+	fs::rename(&temp, path).map_err(|e| format!("{path}: {e}"))
+}
+
+fn best_on_front<'a>(gen_alg: &'a GeneticAlgorithm<LS, EvolveParams>, params: &EvolveParams) -> (&'a LS, f32) {
+	let weighted = |o: &Vec<f32>| o[0] * params.same_weight - o[1] * params.different_weight - o[2] * params.size_weight;
+	let best = gen_alg.pareto_front().into_iter().max_by(|&a, &b| weighted(&gen_alg.objectives()[a]).total_cmp(&weighted(&gen_alg.objectives()[b]))).unwrap_or(0);
+	(&gen_alg.agents()[best].0, weighted(&gen_alg.objectives()[best]))
+}
+
+fn rand_seed() -> u64 {
+	let time = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+	time.as_nanos() as u64 ^ std::process::id() as u64
+}
@@ -0,0 +1,53 @@
+use std::{fs, path::Path, time::{SystemTime, UNIX_EPOCH}};
+
+use soft_evolution::{gls_file::GlsFile, l_system::grid::Grid};
+
+pub fn extension(path: &str) -> String {
+	Path::new(path).extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase()
+}
+
+// .json files hold the text form of a .gls file, anything else is read as the binary format.
+// Either way files without rules or with stems growing missing ones are refused, so what's loaded can be grown
+pub fn load_file(path: &str) -> Result<GlsFile, String> {
+	if extension(path) == "json" {
+		let text = fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+		serde_json::from_str(&text).map_err(|e| format!("{path}: {e}"))
+	}
+	else {
+		let data = fs::read(path).map_err(|e| format!("{path}: {e}"))?;
+		GlsFile::deserialize(&data).map_err(|e| format!("{path}: {e}"))
+	}
+}
+
+pub fn save_file(file: &GlsFile, path: &str) -> Result<(), String> {
+	let data = if extension(path) == "json" {
+		serde_json::to_vec_pretty(file).map_err(|e| e.to_string())?
+	}
+	else {
+		file.serialize()
+	};
+
+	fs::write(path, data).map_err(|e| format!("{path}: {e}"))
+}
+
+// ascii templates as used for goals, origin defaults to the centre
+pub fn load_template(path: &str, origin: Option<[u32; 2]>) -> Result<Grid, String> {
+	let text = fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+
+	let width = text.lines().next().map(|l| l.chars().count()).unwrap_or(0) as u32;
+	let height = text.lines().count() as u32;
+	if width == 0 {
+		return Err(format!("{path}: empty template"));
+	}
+
+	let origin = origin.unwrap_or([width / 2, height / 2]);
+	if origin[0] >= width || origin[1] >= height {
+		return Err(format!("{path}: origin is outside of the template"));
+	}
+
+	Grid::from_string(&text, origin).ok_or(format!("{path}: lines are of different lengths"))
+}
+
+pub fn unix_time() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
@@ -0,0 +1,36 @@
+use std::fs;
+
+use soft_evolution::l_system::LSystem;
+
+use crate::{args::Args, files::load_file};
+
+pub const USAGE: &str = "gls grow <file> [--steps N] [--out rows.txt]
+	Grows the system from its axiom and prints the result, or writes it as an ascii template.
+	Steps default to the growth settings saved in the file, or 25.";
+
+pub fn run(raw: &[String]) -> Result<(), String> {
+	let args = Args::parse(raw, &["steps", "out"], &[])?;
+	args.expect_positional(1)?;
+
+	let file = load_file(args.positional(0, "file")?)?;
+	let steps = args.value_or("steps", file.growth.map(|g| g.max_steps).unwrap_or(25))?;
+
+	let mut system = LSystem::new(file.axiom, file.rules);
+	let mut performed = 0;
+	while performed < steps && system.try_step() {
+		performed += 1;
+	}
+
+	match args.string("out") {
+		Some(path) => {
+			let mut text = system.state().to_rows().join("\n");
+			text.push('\n');
+			fs::write(path, text).map_err(|e| format!("{path}: {e}"))?;
+		},
+		None => println!("{}", system.state()),
+	}
+
+	eprintln!("{performed} steps, {} stems left", system.queue().len());
+
+	Ok(())
+}
@@ -0,0 +1,37 @@
+mod args;
+mod files;
+mod grow;
+mod evolve;
+mod render;
+mod convert;
+
+use std::{env, process::ExitCode};
+
+fn usage() -> String {
+	format!("Headless Grid L-System tools\n\n{}\n\n{}\n\n{}\n\n{}", grow::USAGE, evolve::USAGE, render::USAGE, convert::USAGE)
+}
+
+fn main() -> ExitCode {
+	let raw: Vec<String> = env::args().skip(1).collect();
+
+	let result = match raw.first().map(|s| s.as_str()) {
+		Some("grow") => grow::run(&raw[1..]),
+		Some("evolve") => evolve::run(&raw[1..]),
+		Some("render") => render::run(&raw[1..]),
+		Some("convert") => convert::run(&raw[1..]),
+		Some("help") | Some("--help") | Some("-h") => {
+			println!("{}", usage());
+			Ok(())
+		},
+		Some(command) => Err(format!("Unknown command {command}\n\n{}", usage())),
+		None => Err(usage()),
+	};
+
+	match result {
+		Ok(()) => ExitCode::SUCCESS,
+		Err(e) => {
+			eprintln!("{e}");
+			ExitCode::FAILURE
+		},
+	}
+}
@@ -0,0 +1,41 @@
+use soft_evolution::{l_system::LSystem, render::{RenderSettings, rasterize, save_svg}};
+
+use crate::{args::Args, files::{extension, load_file, load_template}};
+
+pub const USAGE: &str = "gls render <file> --out <image.png|image.svg> [--steps N] [--cell-size PX] [--grid] [--axes] [--no-arrows]
+	Renders the grown system, or an ascii template (.txt) as it is.";
+
+pub fn run(raw: &[String]) -> Result<(), String> {
+	let args = Args::parse(raw, &["out", "steps", "cell-size"], &["grid", "axes", "no-arrows"])?;
+	args.expect_positional(1)?;
+
+	let input = args.positional(0, "file")?;
+	let grid = if extension(input) == "txt" {
+		load_template(input, None)?
+	}
+	else {
+		let file = load_file(input)?;
+		let steps = args.value_or("steps", file.growth.map(|g| g.max_steps).unwrap_or(25))?;
+
+		let mut system = LSystem::new(file.axiom, file.rules);
+		for _ in 0..steps {
+			if !system.try_step() { break; }
+		}
+		system.state().clone()
+	};
+
+	let settings = RenderSettings {
+		cell_size: args.value_or("cell-size", 16)?,
+		grid_lines: args.flag("grid"),
+		axes: args.flag("axes"),
+		stem_arrows: !args.flag("no-arrows"),
+		..Default::default()
+	};
+
+	let out = args.required("out")?;
+	match extension(out).as_str() {
+		"png" => rasterize(&grid, &settings)?.save_png(out),
+		"svg" => save_svg(&grid, &settings, out),
+		_ => Err(format!("{out}: expected a .png or .svg file")),
+	}
+}
@@ -0,0 +1,107 @@
+use std::{fs, path::PathBuf, process::{Command, Output}};
+
+fn gls(args: &[&str]) -> Output {
+	Command::new(env!("CARGO_BIN_EXE_gls")).args(args).output().unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+	String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+// a fresh directory per test, so tests running at once don't share files
+fn scratch(name: &str) -> PathBuf {
+	let dir = std::env::temp_dir().join(format!("gls_cli_{}_{name}", std::process::id()));
+	fs::create_dir_all(&dir).unwrap();
+	fs::write(dir.join("goal.txt"), "..WW..\n.WWWW.\nWWWWWW\n.WWWW.\n").unwrap();
+	dir
+}
+
+fn fails_with(args: &[&str], message: &str) {
+	let output = gls(args);
+	assert!(!output.status.success(), "{args:?} succeeded");
+	assert!(stderr(&output).contains(message), "{args:?}: {}", stderr(&output));
+}
+
+#[test]
+fn bad_arguments_are_reported() {
+	let dir = scratch("arguments");
+	let goal = dir.join("goal.txt");
+	let goal = goal.to_str().unwrap();
+
+	fails_with(&[], "Headless Grid L-System tools");
+	fails_with(&["bogus"], "Unknown command bogus");
+	fails_with(&["evolve", "--bogus"], "Unknown option --bogus");
+	fails_with(&["evolve", "--goal", "goal.txt"], "Missing --out");
+	fails_with(&["evolve", "--goal", goal, "--out", "x.gls", "--seed"], "Missing value for --seed");
+	fails_with(&["evolve", "--goal", goal, "--out", "x.gls", "--seed", "abc"], "Invalid value for --seed: abc");
+	fails_with(&["evolve", "--out", "x.gls", "--goal", "goal.txt", "extra"], "Unexpected argument extra");
+	fails_with(&["grow"], "Missing <file>");
+
+	assert!(gls(&["help"]).status.success());
+	fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn missing_and_broken_files_are_errors() {
+	let dir = scratch("files");
+	let (goal, out) = (dir.join("goal.txt"), dir.join("best.gls"));
+	let (goal, out) = (goal.to_str().unwrap(), out.to_str().unwrap());
+
+	fails_with(&["evolve", "--goal", "/nonexistent/goal.txt", "--out", out], "/nonexistent/goal.txt");
+	fails_with(&["evolve", "--goal", goal, "--origin", "9,9", "--out", out], "origin is outside of the template");
+	fails_with(&["evolve", "--goal", goal, "--origin", "1", "--out", out], "expected x,y");
+
+	let broken = dir.join("broken.gls");
+	fs::write(&broken, b"GLS\x1A\x00\x01RULE").unwrap();
+	fails_with(&["grow", broken.to_str().unwrap()], "broken.gls");
+	assert!(!PathBuf::from(out).exists());
+
+	// files that parse but can't be grown
+	let json = |rules: &str, axiom: &str| format!(r#"{{"version":1,"rules":[{rules}],"axiom":{{"shift":[0,0],"rows":["{axiom}"]}},"goal":null,"growth":null,"evolution":null,"metadata":{{"name":"","notes":"","created":0}}}}"#);
+	let rule = r#"{"shift":[0,0],"rows":["W0^"]}"#;
+	for (name, text) in [("empty.json", json("", "0^")), ("rule.json", json(r#"{"shift":[0,0],"rows":["W3^"]}"#, "0^")), ("axiom.json", json(rule, "0^2>"))] {
+		let path = dir.join(name);
+		fs::write(&path, text).unwrap();
+		let (path, image) = (path.to_str().unwrap(), dir.join("out.png"));
+		fails_with(&["grow", path], name);
+		fails_with(&["render", path, "--out", image.to_str().unwrap()], name);
+		assert!(!image.exists());
+	}
+	let stemless = dir.join("stemless.json");
+	fs::write(&stemless, json(rule, "WW")).unwrap();
+	fails_with(&["evolve", "--goal", goal, "--out", out, "--from", stemless.to_str().unwrap()], "the axiom has no stems");
+	assert!(!PathBuf::from(out).exists());
+
+	let headerless = dir.join("headerless.gls");
+	// a single rule, one cell across, holding a stem of rule 3
+	fs::write(&headerless, [0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 2, 3, 0]).unwrap();
+	fails_with(&["grow", headerless.to_str().unwrap()], "Stems refer to missing rules");
+
+	fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn same_seed_writes_the_same_rules() {
+	let dir = scratch("seed");
+	let goal = dir.join("goal.txt");
+	let run = |seed: &str, name: &str| {
+		let out = dir.join(name);
+		let output = gls(&["evolve", "--goal", goal.to_str().unwrap(), "--out", out.to_str().unwrap(), "--seed", seed,
+			"--generations", "10", "--population", "60", "--survivors", "30"]);
+		assert!(output.status.success(), "{}", stderr(&output));
+
+		let file: serde_json::Value = serde_json::from_slice(&fs::read(out).unwrap()).unwrap();
+		(file["rules"].clone(), file["axiom"].clone())
+	};
+
+	let first = run("3", "a.json");
+	assert_eq!(first, run("3", "b.json"));
+	assert_ne!(first, run("4", "c.json"));
+
+	// and the result grows
+	let output = gls(&["grow", dir.join("a.json").to_str().unwrap()]);
+	assert!(output.status.success(), "{}", stderr(&output));
+	assert!(stderr(&output).contains("steps"));
+
+	fs::remove_dir_all(dir).unwrap();
+}
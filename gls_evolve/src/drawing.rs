@@ -1,8 +1,8 @@
-use std::f32::consts::PI;
-
 use egui_macroquad::{macroquad::prelude::*, egui::{Color32, emath::lerp}};
 use soft_evolution::l_system::{grid::Grid, cell::{Cell, Direction}};
 
+pub use soft_evolution::render::{cell_col, cell_border_col, stem_cell_col, animation::{smoothstep, ease_out_back}};
+
 
 const GRID_COL: Color = color_u8!(58, 58, 58, 255);
 
@@ -15,46 +15,6 @@ const STEM_TEXT_PARAMS: TextParams = TextParams {
 	color: BLACK,
 };
 
-pub fn col_from_hsv(hue: f32, saturation: f32, value: f32) -> [f32; 4] {
-
-	let hue = hue + hue.floor();
-	let hue = hue % 1.0;
-	let saturation = saturation.clamp(0.0, 1.0);
-	let value = value.clamp(0.0, 1.0);
-
-    let h = (hue * 6.0) as i32;
-    let f = hue * 6.0 - h as f32;
-    let p = value * (1.0 - saturation);
-    let q = value * (1.0 - f * saturation);
-    let t = value * (1.0 - (1.0 - f) * saturation);
-
-    match h {
-      0 => [value, t, p, 1.0],
-      1 => [q, value, p, 1.0],
-      2 => [p, value, t, 1.0],
-      3 => [p, q, value, 1.0],
-      4 => [t, p, value, 1.0],
-      5 => [value, p, q, 1.0],
-	  _ => unreachable!()
-    }
-}
-
-pub fn stem_cell_col(stem: u8) -> [f32; 4] {
-	col_from_hsv(0.678 / PI * stem as f32, 0.6, 1.0)
-}
-
-pub fn stem_cell_border_col(stem: u8) -> [f32; 4] {
-	col_from_hsv(0.678 / PI * stem as f32, 0.6, 0.5)
-}
-
-pub fn cell_col(cell: &Cell) -> [f32; 4] {
-	match cell {
-		Cell::Stem(n, _) => stem_cell_col(*n),
-		Cell::Passive => [0.5, 0.5, 0.5, 1.0],
-		Cell::Empty => [0.0, 0.0, 0.0, 1.0],
-	}
-}
-
 pub fn arr_to_col(col: [f32; 4]) -> Color32 {
 	Color32::from_rgba_premultiplied(
 		(255.0 * col[0]) as u8, 
@@ -91,11 +51,7 @@ pub fn draw_grid_axes(grid: &Grid, pixel: f32) {
 }
 
 pub fn draw_cell_rect(cell: Cell, x: f32, y: f32, radius: f32) {
-	let (col, border) = match cell {
-		Cell::Stem(n, _) => (Color::from(stem_cell_col(n)), Color::from(stem_cell_border_col(n))),
-		Cell::Passive => (Color::new(0.5, 0.5, 0.5, 1.0), Color::new(0.25, 0.25, 0.25, 1.0)),
-		Cell::Empty => (BLACK, BLACK),
-	};
+	let (col, border) = (Color::from(cell_col(&cell)), Color::from(cell_border_col(&cell)));
 
 	let margin = (1.0 - radius) * 0.5;
 
@@ -181,12 +137,4 @@ pub fn draw_grid_animated(grid: &Grid, prev_grid: &Grid, rule: &Grid, from: [i32
 			draw_cell(cell, xf, yf, ease_out_back(t));
 		}
 	}
-}
-
-pub fn smoothstep(x: f32) -> f32 {
-	3.0*x*x - 2.0*x*x*x
-}
-
-pub fn ease_out_back(x: f32) -> f32 {
-	2.70158*x*x*x - 6.40316*x*x + 4.70158*x
 }
\ No newline at end of file
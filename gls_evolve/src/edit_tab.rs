@@ -1,7 +1,7 @@
 use egui_macroquad::{macroquad::prelude::*, egui::{Context, DragValue, SidePanel, panel::Side, Vec2, vec2, Button, ScrollArea, Color32, Layout, Align, Window}};
-use soft_evolution::l_system::{grid::Grid, cell::{Cell, Direction}, is_valid};
+use soft_evolution::{l_system::{grid::Grid, cell::{Cell, Direction}, is_valid, ruleset::Ruleset, simplify::{simplify, Simplification}}, gls_file::{GlsFile, GrowthSettings}};
 
-use crate::{controls::Controls, drawing::{draw_grid_lines, draw_grid, pixel_width, draw_grid_axes}, state::Tab, ui::{centered_button, rule_button, RuleButtonResponse}, files::{is_alphanumeric, save_rules, load_rules, get_filenames}};
+use crate::{controls::Controls, drawing::{draw_grid_lines, draw_grid, pixel_width, draw_grid_axes}, state::Tab, ui::{centered_button, drag_label, rule_button, RuleButtonResponse}, files::{is_alphanumeric, save_file, load_file, get_filenames, unix_time}};
 
 #[derive(PartialEq)]
 enum EditTool {
@@ -23,16 +23,23 @@ pub struct EditTab {
 	draw_stem_type: u8,
 	draw_stem_dir: Direction,
 	send: Option<usize>,
+	send_seed: bool,
 	send_error: bool,
 
 	saving_window: bool,
 	save_filename: String,
+	save_notes: String,
 	save_disclaimer: Option<String>,
 
 	loading_window: bool,
 	load_filenames: Vec<String>,
 	load_disclaimer: Option<String>,
 	load_selected: usize,
+	// the last loaded file, so that its goal and settings survive being edited and saved again
+	loaded_file: Option<GlsFile>,
+
+	optimize_steps: u32, // growth steps the system is simplified for
+	optimized: Option<Simplification>, // what the last optimization removed
 }
 
 impl EditTab {
@@ -180,21 +187,78 @@ impl EditTab {
 					self.load_filenames = get_filenames();
 					self.load_selected = 0;
 				}
+				if centered_button(ui, vec2(150.0, 25.0), "Normalize").clicked() {
+					self.send_error = !self.normalize();
+				}
+				drag_label(ui, &mut self.optimize_steps, 1..=10000, 0.2, "Growth Steps");
+				if centered_button(ui, vec2(150.0, 25.0), "Optimize System").clicked() {
+					self.send_error = !self.optimize();
+				}
+				if let Some(result) = self.optimized {
+					ui.label(format!("Removed {} cells and {} rules.", result.removed_cells, result.removed_rules));
+					if result.stopped {
+						ui.label("It grows the same, and stops growing.");
+					}
+					else {
+						ui.label(format!("It grows the same for {} steps, later steps may differ.", result.steps));
+					}
+				}
 				if centered_button(ui, vec2(150.0, 25.0), "Send to Grow").clicked() {
-					if is_valid(&self.l_rules) {
+					if self.stems_valid() {
 						self.send = Some(2);
 					}
 					else {
 						self.send_error = true;
 					}
 				}
+				if centered_button(ui, vec2(150.0, 25.0), "Send rules to Evolve as seed").clicked() {
+					if self.stems_valid() {
+						self.send_seed = true;
+					}
+					else {
+						self.send_error = true;
+					}
+				}
 
 				if self.send_error {
-					ui.colored_label(Color32::RED, "Cannot send, invalid stem cells.");
+					ui.colored_label(Color32::RED, "Cannot send, invalid stem cells or an axiom without stems.");
 				}
 			});
     }
 
+	// whether the stems of the rules and of the loaded file's axiom all refer to existing rules, and the axiom has a stem to grow from
+	fn stems_valid(&self) -> bool {
+		let rules_len = self.l_rules.len();
+		let axiom_valid = self.loaded_file.iter().all(|file| {
+			let mut stems = file.axiom.contents().iter().filter_map(|cell| match cell { Cell::Stem(n, _) => Some(*n as usize), _ => None }).peekable();
+			stems.peek().is_some() && stems.all(|n| n < rules_len)
+		});
+		is_valid(&self.l_rules) && axiom_valid
+	}
+
+	// renumbers the rules from the axiom of the loaded file, or the usual one, and drops the unreachable ones
+	fn normalize(&mut self) -> bool {
+		if !self.stems_valid() { return false; }
+
+		match &mut self.loaded_file {
+			Some(file) => self.l_rules.canonicalize_with(&mut file.axiom),
+			None => self.l_rules.canonicalize(),
+		}
+		self.current_rule = self.current_rule.min(self.l_rules.len() - 1);
+		true
+	}
+
+	// removes every cell that doesn't change what grows within the growth steps, the whole system's `contract_empty`
+	fn optimize(&mut self) -> bool {
+		if !self.stems_valid() { return false; }
+
+		let mut axiom = Grid::single(Cell::Stem(0, Direction::UP));
+		let axiom = self.loaded_file.as_mut().map_or(&mut axiom, |file| &mut file.axiom);
+		self.optimized = Some(simplify(&mut self.l_rules, axiom, self.optimize_steps as usize));
+		self.current_rule = self.current_rule.min(self.l_rules.len() - 1);
+		true
+	}
+
 	fn draw_saving_window(&mut self, ctx: &Context) {
 		Window::new("Save")
 			.collapsible(false)
@@ -202,6 +266,8 @@ impl EditTab {
 			.show(ctx, |ui| {
 
 				ui.text_edit_singleline(&mut self.save_filename);
+				ui.label("Notes:");
+				ui.text_edit_multiline(&mut self.save_notes);
 
 				ui.colored_label(Color32::RED, self.save_disclaimer.as_ref().unwrap_or(&"".into()));
 
@@ -213,7 +279,16 @@ impl EditTab {
 						self.save_disclaimer = Some("Non-alphanumeric characters found".into());
 					}
 					else {
-						if let Err(e) = save_rules(&self.l_rules, &self.save_filename) {
+						let mut file = self.loaded_file.clone().unwrap_or_else(|| GlsFile::new(Vec::new()));
+						file.rules = self.l_rules.clone();
+						file.growth = Some(GrowthSettings { max_steps: self.optimize_steps });
+						file.metadata.name = self.save_filename.clone();
+						file.metadata.notes = self.save_notes.clone();
+						if file.metadata.created == 0 {
+							file.metadata.created = unix_time();
+						}
+
+						if let Err(e) = save_file(&file, &self.save_filename) {
 							self.save_disclaimer = Some(e.to_string());
 						}
 						else {
@@ -251,9 +326,15 @@ impl EditTab {
 				ui.colored_label(Color32::RED, self.load_disclaimer.as_ref().unwrap_or(&"".into()));
 
 				if ui.button("load").clicked() {
-					match load_rules(&self.load_filenames[self.load_selected]) {
-						Ok(rules) => {
-							self.receive(rules);
+					match load_file(&self.load_filenames[self.load_selected]) {
+						Ok(file) => {
+							self.receive(file.rules.clone());
+							self.save_filename = self.load_filenames[self.load_selected].clone();
+							self.save_notes = file.metadata.notes.clone();
+							if let Some(growth) = file.growth {
+								self.optimize_steps = growth.max_steps;
+							}
+							self.loaded_file = Some(file);
 							self.loading_window = false;
 							self.load_disclaimer = None;
 						},
@@ -281,14 +362,19 @@ impl Tab for EditTab {
 			draw_cell: CellType::Passive,
 			current_rule: 0,
 			send: None,
+			send_seed: false,
 			send_error: false,
 			saving_window: false,
 			save_filename: String::new(),
+			save_notes: String::new(),
 			save_disclaimer: None,
 			loading_window: false,
 			load_selected: 0,
 			load_filenames: Vec::new(),
 			load_disclaimer: None,
+			loaded_file: None,
+			optimize_steps: 100,
+			optimized: None,
 		}
     }
 
@@ -350,8 +436,32 @@ impl Tab for EditTab {
 		None
 	}
 
+	fn send_seed(&mut self) -> Option<(Vec<Grid>, Grid)> {
+		if !self.send_seed { return None; }
+		self.send_seed = false;
+		let axiom = self.loaded_file.as_ref().map_or_else(|| Grid::single(Cell::Stem(0, Direction::UP)), |file| file.axiom.clone());
+		Some((self.l_rules.clone(), axiom))
+	}
+
 	fn receive(&mut self, system: Vec<Grid>) {
 		self.l_rules = system;
 		self.current_rule = 0;
 	}
+
+	fn send_settings(&mut self) -> Option<GlsFile> {
+		if self.send != Some(2) { return None; }
+		self.loaded_file.clone()
+	}
+
+	// kept in the loaded file, so they're saved with the rules
+	fn receive_settings(&mut self, file: GlsFile) {
+		let loaded = self.loaded_file.get_or_insert_with(|| GlsFile::new(Vec::new()));
+		loaded.axiom = file.axiom;
+		loaded.goal = file.goal.or(loaded.goal.take());
+		loaded.evolution = file.evolution.or(loaded.evolution);
+		if let Some(growth) = file.growth {
+			loaded.growth = Some(growth);
+			self.optimize_steps = growth.max_steps;
+		}
+	}
 }
\ No newline at end of file
@@ -1,9 +1,9 @@
-use std::time::Instant;
+use std::{collections::HashMap, time::{Duration, Instant}};
 
-use egui_macroquad::{macroquad::prelude::*, egui::{Context, SidePanel, panel::Side, vec2, Sense, CentralPanel, Rect, Stroke, Color32}};
-use soft_evolution::{genetic_algorithm::GeneticAlgorithm, l_system::grid::Grid};
+use egui_macroquad::{macroquad::prelude::*, egui::{Context, Ui, SidePanel, ScrollArea, panel::Side, vec2, Sense, CentralPanel, TopBottomPanel, Rect, Stroke, Color32, Window, ComboBox, Button, DragValue}};
+use soft_evolution::{genetic_algorithm::{evolve::Evolve, statistics::{GenerationStats, save_csv}, selection::Selection, map_elites::MapElites, islands::{Islands, Topology}, stopping::StopCondition, mutation::MutationTable}, ls_evolve::{LS, Growth, EvolveParams, unique_genotypes, OBJECTIVES, DESCRIPTORS, descriptor_axis}, render::col_from_hsv, l_system::{grid::Grid, cell::{Cell, Direction}, ruleset::{Ruleset, Symmetry}}, gls_file::{GlsFile, GrowthSettings, EvolutionSettings}, render::Image, import::{ImportSettings, ColourMapping, load_image, image_to_grid}};
 
-use crate::{state::Tab, ls_evolve::LS, ui::{draw_grid_ui, centered_button, drag_label}};
+use crate::{state::Tab, ui::{draw_grid_ui, centered_button, drag_label, plot_lines}, drawing::arr_to_col, files::{is_alphanumeric, export_path, save_run, load_run, get_run_filenames}};
 
 fn number_suffix(n: usize) -> &'static str {
 	match (n) % 10 {
@@ -14,19 +14,10 @@ fn number_suffix(n: usize) -> &'static str {
 	}
 }
 
-pub struct EvolveParams {
-	pub goal: Grid,
-	pub max_steps: u16,
-
-	pub same_weight: f32,
-	pub different_weight: f32,
-	pub size_weight: f32,
-	pub size_pow: f32,
-}
-
 pub struct EvolveTab {
 	running: bool,
-	gen_alg: GeneticAlgorithm<LS, EvolveParams>,
+	islands: Islands<LS, EvolveParams>,
+	island: usize, // the island shown and edited
 	visible_count: usize,
 	visible_up_to: usize,
 	evolve_budget: u16,
@@ -34,24 +25,586 @@ pub struct EvolveTab {
 
 	send_selected: Option<usize>,
 	send_target: bool,
+
+	import_window: bool,
+	import_path: String,
+	import_image: Option<Image>,
+	import_settings: ImportSettings,
+	import_preview: Option<Grid>,
+	import_disclaimer: Option<String>,
+
+	pareto_window: bool,
+	pareto_axes: [usize; 2],
+
+	stats_window: bool,
+	stats_filename: String,
+	stats_disclaimer: Option<String>,
+
+	run_saving_window: bool,
+	run_loading_window: bool,
+	run_filename: String,
+	run_filenames: Vec<String>,
+	run_selected: usize,
+	run_disclaimer: Option<String>,
+	autosave_interval: u32, // generations between autosaves to `run_filename`, 0 is off
+	autosaved_generation: u32,
+	autosave_disclaimer: Option<String>,
+
+	lineage_strip: bool,
+	// the genealogy keeps genomes only, the bodies of the shown lineage are grown once. Keyed by the island, id, generation and fitness
+	lineage_bodies: HashMap<(usize, u64, u32, u32), Grid>,
+	// counted once per generation, keyed by the island, the generation and the best fitness
+	unique_genotypes: Option<((usize, u32, u32), usize)>,
+
+	seed_window: bool,
+	seed_rules: Vec<Grid>, // received from the Edit tab
+	seed_growth: Growth,
+	seed_copies: usize,
+
+	stop_conditions: Vec<(bool, StopCondition)>, // running pauses when an enabled one is reached
+	stop_reason: Option<StopCondition>,
+	run_time: Duration, // spent running since the last reset
+
+	// quality-diversity mode, evolving an archive of varied bodies instead of one population
+	qd_mode: bool,
+	map_elites: MapElites<LS, EvolveParams>,
+	qd_selected: Option<[usize; 2]>,
+}
+
+impl EvolveTab {
+	fn draw_import_window(&mut self, ctx: &Context) {
+		Window::new("Import goal from image")
+			.collapsible(false)
+			.constraint_to(ctx.screen_rect())
+			.show(ctx, |ui| {
+				ui.label("PNG or BMP file:");
+				ui.horizontal(|ui| {
+					ui.text_edit_singleline(&mut self.import_path);
+					if ui.button("open").clicked() {
+						match load_image(self.import_path.trim()) {
+							Ok(image) => {
+								self.import_preview = Some(image_to_grid(&image, &self.import_settings));
+								self.import_image = Some(image);
+								self.import_disclaimer = None;
+							},
+							Err(e) => self.import_disclaimer = Some(e),
+						}
+					}
+				});
+
+				let old_settings = self.import_settings.clone();
+
+				drag_label(ui, &mut self.import_settings.target_size, 0..=200, 0.1, "Size (0 = 1 cell per pixel)");
+				drag_label(ui, &mut self.import_settings.threshold, 0.0..=1.0, 0.002, "Threshold");
+				ui.checkbox(&mut self.import_settings.invert, "Invert");
+				ui.checkbox(&mut self.import_settings.alpha_mask, "Alpha as mask");
+
+				ui.label("Colour mapping:");
+				let mut to_remove = None;
+				for (i, mapping) in self.import_settings.mapping.iter_mut().enumerate() {
+					ui.horizontal(|ui| {
+						ui.color_edit_button_srgb(&mut mapping.colour);
+						ComboBox::from_id_source(("import mapping", i))
+							.selected_text(match mapping.cell {
+								Cell::Stem(_, _) => "Stem",
+								Cell::Passive => "Passive",
+								Cell::Empty => "Empty",
+							})
+							.show_ui(ui, |ui| {
+								ui.selectable_value(&mut mapping.cell, Cell::Passive, "Passive");
+								ui.selectable_value(&mut mapping.cell, Cell::Stem(0, Direction::UP), "Stem");
+								ui.selectable_value(&mut mapping.cell, Cell::Empty, "Empty");
+							});
+						if ui.button("\u{1F5D1}").clicked() {
+							to_remove = Some(i);
+						}
+					});
+				}
+				if let Some(i) = to_remove {
+					self.import_settings.mapping.remove(i);
+				}
+				if ui.button("\u{2795}").clicked() {
+					self.import_settings.mapping.push(ColourMapping { colour: [0, 0, 0], cell: Cell::Passive });
+				}
+
+				if let Some(image) = &self.import_image {
+					if self.import_settings != old_settings {
+						self.import_preview = Some(image_to_grid(image, &self.import_settings));
+					}
+				}
+
+				let (preview_rect, _) = ui.allocate_exact_size(vec2(200.0, 150.0), Sense::hover());
+				ui.painter().rect_filled(preview_rect, 0.0, Color32::BLACK);
+				if let Some(preview) = &self.import_preview {
+					draw_grid_ui(ui, preview, preview_rect);
+				}
+
+				ui.colored_label(Color32::RED, self.import_disclaimer.as_ref().unwrap_or(&"".into()));
+
+				ui.horizontal(|ui| {
+					if ui.add_enabled(self.import_preview.is_some(), Button::new("apply")).clicked() {
+						let goal = self.import_preview.take().unwrap();
+						self.set_goal(goal);
+						self.import_window = false;
+					}
+					if ui.button("cancel").clicked() {
+						self.import_window = false;
+					}
+				});
+			});
+	}
+
+	fn step(&mut self) {
+		if self.qd_mode {
+			self.map_elites.set_params(self.islands.islands()[self.island].params().clone());
+			self.map_elites.perform_iteration();
+		}
+		else {
+			self.islands.perform_generation();
+		}
+	}
+
+	// the selected agent and its first parents back to the first generation, newest first
+	fn draw_lineage(&mut self, ui: &mut Ui) {
+		let gen_alg = &self.islands.islands()[self.island];
+		let Some(&id) = gen_alg.ids().get(self.selected) else { return; };
+		let lineage = gen_alg.genealogy().lineage(id);
+
+		let mut bodies = HashMap::with_capacity(lineage.len());
+		for ancestor in &lineage {
+			let key = (self.island, ancestor.id, ancestor.generation, ancestor.fitness.to_bits());
+			let body = self.lineage_bodies.remove(&key).unwrap_or_else(|| {
+				let mut agent = ancestor.agent.clone();
+				agent.fitness(gen_alg.params());
+				agent.0.state().clone()
+			});
+			bodies.insert(key, body);
+		}
+		self.lineage_bodies = bodies;
+
+		ui.label(format!("Lineage: {} ancestors", lineage.len().saturating_sub(1)));
+		ScrollArea::horizontal().show(ui, |ui| {
+			ui.horizontal(|ui| {
+				for (i, ancestor) in lineage.iter().enumerate() {
+					ui.vertical(|ui| {
+						ui.set_width(90.0);
+						let (rect, _) = ui.allocate_exact_size(vec2(90.0, 60.0), Sense::hover());
+						draw_grid_ui(ui, &self.lineage_bodies[&(self.island, ancestor.id, ancestor.generation, ancestor.fitness.to_bits())], rect);
+						ui.label(format!("gen {}", ancestor.generation));
+
+						// green when the mutation improved on the parent, red when it made it worse
+						let colour = match lineage.get(i + 1).map(|parent| ancestor.fitness.total_cmp(&parent.fitness)) {
+							Some(std::cmp::Ordering::Greater) => Color32::LIGHT_GREEN,
+							Some(std::cmp::Ordering::Less) => Color32::LIGHT_RED,
+							_ => Color32::GRAY,
+						};
+						ui.colored_label(colour, format!("{:.2}", ancestor.fitness));
+						if ancestor.parents.len() > 1 {
+							ui.label(format!("crossover, {}", ancestor.origin));
+						}
+						else {
+							ui.label(ancestor.origin);
+						}
+					});
+				}
+			});
+		});
+	}
+
+	fn draw_seed_window(&mut self, ctx: &Context) {
+		Window::new("Seed population")
+			.collapsible(false)
+			.constraint_to(ctx.screen_rect())
+			.show(ctx, |ui| {
+				ui.label(format!("Received a system of {} rules.", self.seed_rules.len()));
+				let (preview_rect, _) = ui.allocate_exact_size(vec2(140.0, 100.0), Sense::hover());
+				draw_grid_ui(ui, &self.seed_rules[0], preview_rect);
+
+				let seed = LS::with_growth(self.seed_rules.clone(), self.seed_growth.clone());
+				if ui.button("start over from mutants of the seed").clicked() {
+					self.islands.reset_from_seed(&seed);
+					self.selected = 0;
+					self.autosaved_generation = 0;
+					self.run_time = Duration::ZERO;
+					self.stop_reason = None;
+					self.seed_window = false;
+				}
+
+				ui.separator();
+				let generation_count = self.islands.islands()[self.island].generation_count;
+				drag_label(ui, &mut self.seed_copies, 1..=generation_count, 0.1, "Copies");
+				if ui.button("replace the worst agents").clicked() {
+					self.islands.inject(&vec![seed; self.seed_copies]);
+					self.seed_window = false;
+				}
+
+				if ui.button("cancel").clicked() {
+					self.seed_window = false;
+				}
+			});
+	}
+
+	fn draw_stop_settings(&mut self, ui: &mut Ui) {
+		ui.label("Pause when:");
+		for (enabled, condition) in &mut self.stop_conditions {
+			ui.horizontal(|ui| {
+				ui.checkbox(enabled, "");
+				match condition {
+					StopCondition::TargetFitness(target) => drag_label(ui, target, -100000.0..=100000.0, 0.1, "Fitness reached"),
+					StopCondition::Stagnation(generations) => drag_label(ui, generations, 1..=100000, 0.5, "Gens without gain"),
+					StopCondition::TimeLimit(limit) => {
+						let mut minutes = limit.as_secs_f32() / 60.0;
+						drag_label(ui, &mut minutes, 0.1..=10000.0, 0.05, "Minutes running");
+						*limit = Duration::from_secs_f32(minutes * 60.0);
+					},
+					StopCondition::GenerationCap(cap) => drag_label(ui, cap, 1..=1000000, 1.0, "Generation reached"),
+					StopCondition::DiversityCollapse(min) => drag_label(ui, min, 0.0..=100.0, 0.01, "Diversity below"),
+				}
+			});
+		}
+	}
+
+	fn draw_mutation_settings(ui: &mut Ui, mutations: &mut MutationTable) {
+		ui.collapsing("Mutations", |ui| {
+			ui.checkbox(&mut mutations.adaptive, "Adaptive weights");
+			let stats = mutations.stats().to_vec();
+			for ((name, weight), stats) in mutations.names.iter().zip(&mut mutations.weights).zip(stats) {
+				ui.horizontal(|ui| {
+					ui.add_enabled(!mutations.adaptive, DragValue::new(weight).clamp_range(0.0..=100.0).speed(0.01));
+					ui.label(*name);
+				});
+				ui.label(format!("    {} uses, {:.1}% success", stats.uses, stats.success_rate() * 100.0));
+			}
+			if centered_button(ui, vec2(150.0, 25.0), "Reset statistics").clicked() {
+				mutations.reset_stats();
+			}
+		});
+	}
+
+	fn set_goal(&mut self, goal: Grid) {
+		for island in self.islands.islands_mut() {
+			island.params_mut().goal = goal.clone();
+		}
+	}
+
+	fn draw_island_settings(&mut self, ui: &mut Ui) {
+		ui.label("Islands");
+
+		let mut count = self.islands.islands().len();
+		drag_label(ui, &mut count, 1..=64, 0.05, "Island Count");
+		if count != self.islands.islands().len() {
+			self.islands.set_island_count(count);
+			if self.island >= count {
+				self.island = count - 1;
+				self.selected = 0;
+			}
+		}
+
+		if count > 1 {
+			ComboBox::from_label("Topology")
+				.selected_text(format!("{:?}", self.islands.topology))
+				.show_ui(ui, |ui| {
+					ui.selectable_value(&mut self.islands.topology, Topology::Ring, "Ring");
+					ui.selectable_value(&mut self.islands.topology, Topology::Random, "Random");
+				});
+			drag_label(ui, &mut self.islands.migration_interval, 0..=1000, 0.1, "Migration Interval");
+			drag_label(ui, &mut self.islands.migrants, 0..=100, 0.05, "Migrants");
+			ui.checkbox(&mut self.islands.parallel, "Parallel Islands");
+
+			let best = self.islands.best_island();
+			for (i, island) in self.islands.islands().iter().enumerate() {
+				let text = format!("{}island {}: {:.2}", if i == best { "* " } else { "" }, i + 1, island.best().1);
+				if ui.selectable_label(self.island == i, text).clicked() {
+					self.island = i;
+					self.selected = 0;
+				}
+			}
+		}
+		ui.add_space(5.0);
+	}
+
+	fn selected_elite(&self) -> Option<&LS> {
+		self.qd_selected.and_then(|[x, y]| self.map_elites.cell(x, y)).map(|e| &e.agent)
+	}
+
+	fn draw_map_elites_settings(&mut self, ui: &mut Ui) {
+		ui.label("MAP-Elites settings");
+
+		drag_label(ui, &mut self.map_elites.batch_size, 1..=10000, 1.0, "Batch Size");
+		drag_label(ui, &mut self.map_elites.mutation_factor, 0.0..=1.0, 0.002, "Mutation Factor");
+		drag_label(ui, &mut self.map_elites.crossover_rate, 0.0..=1.0, 0.002, "Crossover Rate");
+		ui.checkbox(&mut self.map_elites.parallel, "Parallel Evaluation");
+
+		let mut axes = self.map_elites.axes();
+		for (i, axis) in axes.iter_mut().enumerate() {
+			ui.add_space(5.0);
+			ComboBox::from_label(["x axis", "y axis"][i])
+				.selected_text(DESCRIPTORS[axis.descriptor])
+				.show_ui(ui, |ui| {
+					for (d, name) in DESCRIPTORS.iter().enumerate() {
+						if ui.selectable_label(axis.descriptor == d, *name).clicked() {
+							*axis = descriptor_axis(d, axis.bins);
+						}
+					}
+				});
+			drag_label(ui, &mut axis.bins, 1..=64, 0.05, "Bins");
+			drag_label(ui, &mut axis.min, -1000.0..=axis.max, 0.05, "Min");
+			drag_label(ui, &mut axis.max, axis.min..=1000.0, 0.05, "Max");
+		}
+		if axes != self.map_elites.axes() {
+			self.map_elites.set_axes(axes);
+			self.qd_selected = None;
+		}
+	}
+
+	// niches coloured from blue to red by fitness, empty ones black
+	fn draw_archive(&mut self, ui: &mut Ui) {
+		let [columns, rows] = self.map_elites.bins();
+		let available = ui.available_rect_before_wrap();
+		let size = (available.width() / columns as f32).min(available.height() / rows as f32);
+
+		let min = self.map_elites.elites().map(|e| e.fitness).fold(f32::MAX, f32::min);
+		let max = self.map_elites.elites().map(|e| e.fitness).fold(-f32::MAX, f32::max);
+
+		for y in 0..rows {
+			for x in 0..columns {
+				// the y axis grows upwards
+				let pos = available.min + vec2(x as f32, (rows - 1 - y) as f32) * size;
+				let rect = Rect::from_min_size(pos, vec2(size, size)).expand(-1.0);
+				let resp = ui.allocate_rect(rect, Sense::click());
+
+				let elite = self.map_elites.cell(x, y);
+				let fill = match elite {
+					Some(elite) => {
+						let t = if max > min { (elite.fitness - min) / (max - min) } else { 1.0 };
+						arr_to_col(col_from_hsv(0.66 - 0.66 * t, 0.8, 0.9))
+					},
+					None => Color32::BLACK,
+				};
+				let stroke = if self.qd_selected == Some([x, y]) { Stroke::new(2.0, Color32::WHITE) } else { Stroke::new(1.0, Color32::DARK_GRAY) };
+				ui.painter().rect(rect, 0.0, fill, stroke);
+
+				if let Some(elite) = elite {
+					if resp.clicked() {
+						self.qd_selected = Some([x, y]);
+					}
+					resp.on_hover_ui_at_pointer(|ui| {
+						ui.label(format!("fitness: {:.2}", elite.fitness));
+					});
+				}
+			}
+		}
+	}
+
+	fn check_run_filename(&self) -> Result<(), String> {
+		if self.run_filename.trim().is_empty() {
+			Err("Empty filename".into())
+		}
+		else if !is_alphanumeric(&self.run_filename) {
+			Err("Non-alphanumeric characters found".into())
+		}
+		else {
+			Ok(())
+		}
+	}
+
+	fn draw_run_saving_window(&mut self, ctx: &Context) {
+		Window::new("Save run")
+			.collapsible(false)
+			.constraint_to(ctx.screen_rect())
+			.show(ctx, |ui| {
+				ui.label("Saves every island with its agents, settings and random state.");
+				ui.text_edit_singleline(&mut self.run_filename);
+
+				ui.colored_label(Color32::RED, self.run_disclaimer.as_ref().unwrap_or(&"".into()));
+
+				if ui.button("save").clicked() {
+					let result = self.check_run_filename()
+						.and_then(|_| save_run(&self.islands.serialize(), &self.run_filename).map_err(|e| e.to_string()));
+
+					match result {
+						Ok(()) => {
+							self.run_saving_window = false;
+							self.run_disclaimer = None;
+						},
+						Err(e) => self.run_disclaimer = Some(e),
+					}
+				}
+				if ui.button("cancel").clicked() {
+					self.run_saving_window = false;
+					self.run_disclaimer = None;
+				}
+			});
+	}
+
+	fn draw_run_loading_window(&mut self, ctx: &Context) {
+		Window::new("Load run")
+			.collapsible(false)
+			.constraint_to(ctx.screen_rect())
+			.show(ctx, |ui| {
+				ui.label("Choose the run to continue:");
+
+				ScrollArea::vertical()
+					.max_height(200.0)
+					.auto_shrink([false, true])
+					.show(ui, |ui| {
+						for (i, name) in self.run_filenames.iter().enumerate() {
+							if ui.selectable_label(self.run_selected == i, name).clicked() {
+								self.run_selected = i;
+							}
+						}
+					});
+
+				ui.colored_label(Color32::RED, self.run_disclaimer.as_ref().unwrap_or(&"".into()));
+
+				if ui.add_enabled(!self.run_filenames.is_empty(), Button::new("load")).clicked() {
+					let name = &self.run_filenames[self.run_selected];
+					match load_run(name).and_then(|data| Islands::deserialize(&data)) {
+						Ok(islands) => {
+							self.islands = islands;
+							self.island = 0;
+							self.selected = 0;
+							self.run_filename = name.clone();
+							self.autosaved_generation = self.islands.generation_number();
+							self.run_time = Duration::ZERO;
+							self.stop_reason = None;
+							self.run_loading_window = false;
+							self.run_disclaimer = None;
+						},
+						Err(message) => self.run_disclaimer = Some(message),
+					}
+				}
+				if ui.button("cancel").clicked() {
+					self.run_loading_window = false;
+					self.run_disclaimer = None;
+				}
+			});
+	}
+
+	fn draw_stats_window(&mut self, ctx: &Context) {
+		Window::new("Statistics")
+			.collapsible(false)
+			.constraint_to(ctx.screen_rect())
+			.open(&mut self.stats_window)
+			.show(ctx, |ui| {
+				let history = self.islands.islands()[self.island].history();
+				let series = |f: fn(&GenerationStats) -> f32| history.iter().map(f).collect::<Vec<f32>>();
+
+				ui.label("fitness:");
+				plot_lines(ui, vec2(400.0, 180.0), &[
+					("best", Color32::LIGHT_GREEN, series(|s| s.best)),
+					("mean", Color32::LIGHT_BLUE, series(|s| s.mean)),
+					("median", Color32::YELLOW, series(|s| s.median)),
+					("worst", Color32::LIGHT_RED, series(|s| s.worst)),
+				]);
+				ui.label("diversity:");
+				plot_lines(ui, vec2(400.0, 80.0), &[("genotype distance", Color32::WHITE, series(|s| s.diversity))]);
+				ui.label("rules:");
+				plot_lines(ui, vec2(400.0, 80.0), &[("mean rule count", Color32::GOLD, series(|s| s.mean_rule_count()))]);
+
+				if let Some(last) = history.last() {
+					ui.label(format!("standard deviation: {:.2}", last.std_dev));
+					let counts: Vec<String> = last.rule_counts.iter().enumerate().filter(|&(_, &n)| n > 0).map(|(c, n)| format!("{c}: {n}")).collect();
+					ui.label(format!("agents by rule count: {}", counts.join(", ")));
+					ui.label(format!("last generation {:.0} ms, {:.1} s in total", last.duration * 1000.0, last.elapsed));
+				}
+
+				ui.separator();
+				ui.horizontal(|ui| {
+					ui.label("file name:");
+					ui.text_edit_singleline(&mut self.stats_filename);
+				});
+				ui.colored_label(Color32::RED, self.stats_disclaimer.as_ref().unwrap_or(&"".into()));
+
+				if ui.button("export csv").clicked() {
+					if self.stats_filename.trim().is_empty() {
+						self.stats_disclaimer = Some("Empty filename".into());
+					}
+					else if !is_alphanumeric(&self.stats_filename) {
+						self.stats_disclaimer = Some("Non-alphanumeric characters found".into());
+					}
+					else {
+						let result = export_path(&self.stats_filename, "csv")
+							.map_err(|e| e.to_string())
+							.and_then(|path| save_csv(history, path));
+
+						self.stats_disclaimer = result.err();
+					}
+				}
+			});
+	}
+
+	fn draw_pareto_window(&mut self, ctx: &Context) {
+		Window::new("Pareto front")
+			.collapsible(false)
+			.constraint_to(ctx.screen_rect())
+			.open(&mut self.pareto_window)
+			.show(ctx, |ui| {
+				for (axis, label) in self.pareto_axes.iter_mut().zip(["x axis", "y axis"]) {
+					ComboBox::from_label(label)
+						.selected_text(OBJECTIVES[*axis])
+						.show_ui(ui, |ui| {
+							for (i, name) in OBJECTIVES.iter().enumerate() {
+								ui.selectable_value(axis, i, *name);
+							}
+						});
+				}
+
+				let objectives = self.islands.islands()[self.island].objectives();
+				let (rect, resp) = ui.allocate_exact_size(vec2(300.0, 300.0), Sense::click());
+				ui.painter().rect(rect, 0.0, Color32::BLACK, Stroke::new(1.0, Color32::DARK_GRAY));
+
+				if objectives.is_empty() {
+					ui.label("Evolve a generation to see the front");
+					return;
+				}
+
+				let [x, y] = self.pareto_axes;
+				let min = [x, y].map(|m| objectives.iter().map(|o| o[m]).fold(f32::MAX, f32::min));
+				let max = [x, y].map(|m| objectives.iter().map(|o| o[m]).fold(-f32::MAX, f32::max));
+				let to_screen = |o: &[f32]| {
+					let t = [0, 1].map(|i| if max[i] > min[i] { (o[[x, y][i]] - min[i]) / (max[i] - min[i]) } else { 0.5 });
+					rect.expand(-8.0).lerp_inside(vec2(t[0], 1.0 - t[1]))
+				};
+
+				let front = self.islands.islands()[self.island].pareto_front();
+				for o in objectives {
+					ui.painter().circle_filled(to_screen(o), 1.5, Color32::GRAY);
+				}
+				for &i in &front {
+					ui.painter().circle_filled(to_screen(&objectives[i]), 3.0, Color32::LIGHT_GREEN);
+				}
+				if let Some(o) = objectives.get(self.selected) {
+					ui.painter().circle_stroke(to_screen(o), 5.0, Stroke::new(1.5, Color32::WHITE));
+				}
+
+				// clicking picks the closest agent of the front
+				if let Some(pointer) = resp.interact_pointer_pos().filter(|_| resp.clicked()) {
+					let closest = front.iter().min_by(|&&a, &&b| {
+						to_screen(&objectives[a]).distance(pointer).total_cmp(&to_screen(&objectives[b]).distance(pointer))
+					});
+					if let Some(&i) = closest {
+						self.selected = i;
+					}
+				}
+
+				ui.label(format!("{} agents on the front", front.len()));
+				ui.label(format!("{}: {:.1} to {:.1}", OBJECTIVES[x], min[0], max[0]));
+				ui.label(format!("{}: {:.1} to {:.1}", OBJECTIVES[y], min[1], max[1]));
+			});
+	}
 }
 
 impl Tab for EvolveTab {
     fn new() -> Self {
 		let goal = Grid::from_string(include_str!("templates/cross.txt"), [2, 2]).unwrap();
 
-		let params = EvolveParams {
-			goal,
-			max_steps: 25,
-
-			same_weight: 1.0,
-			different_weight: -10.0,
-			size_weight: -0.5,
-			size_pow: 1.5,
-		};
+		let params = EvolveParams::new(goal);
 
         Self {
-			gen_alg: GeneticAlgorithm::<LS, EvolveParams>::new(1000, 500, 0.5, params),
+			map_elites: MapElites::new([descriptor_axis(0, 12), descriptor_axis(1, 12)], 100, 0.5, params.clone()),
+			islands: Islands::new(1, 1000, 500, 0.5, params),
+			island: 0,
 			running: false,
 			visible_count: 16,
 			visible_up_to: 1000,
@@ -59,6 +612,52 @@ impl Tab for EvolveTab {
 			selected: 0,
 			send_selected: None,
 			send_target: false,
+
+			import_window: false,
+			import_path: String::new(),
+			import_image: None,
+			import_settings: ImportSettings::default(),
+			import_preview: None,
+			import_disclaimer: None,
+
+			pareto_window: false,
+			pareto_axes: [0, 2],
+
+			stats_window: false,
+			stats_filename: String::new(),
+			stats_disclaimer: None,
+
+			run_saving_window: false,
+			run_loading_window: false,
+			run_filename: String::new(),
+			run_filenames: Vec::new(),
+			run_selected: 0,
+			run_disclaimer: None,
+			autosave_interval: 0,
+			autosaved_generation: 0,
+			autosave_disclaimer: None,
+
+			lineage_strip: true,
+			lineage_bodies: HashMap::new(),
+			unique_genotypes: None,
+
+			seed_window: false,
+			seed_rules: Vec::new(),
+			seed_growth: Growth::default(),
+			seed_copies: 1,
+
+			stop_conditions: vec![
+				(false, StopCondition::TargetFitness(0.0)),
+				(false, StopCondition::Stagnation(100)),
+				(false, StopCondition::TimeLimit(Duration::from_secs(600))),
+				(false, StopCondition::GenerationCap(1000)),
+				(false, StopCondition::DiversityCollapse(0.5)),
+			],
+			stop_reason: None,
+			run_time: Duration::ZERO,
+
+			qd_mode: false,
+			qd_selected: None,
 		}
     }
 	
@@ -66,8 +665,25 @@ impl Tab for EvolveTab {
 		if self.running {
 			let start = Instant::now();
 			while start.elapsed().as_millis() < self.evolve_budget as u128 {
-				self.gen_alg.perform_generation();
+				self.step();
 			}
+			self.run_time += start.elapsed();
+
+			if !self.qd_mode {
+				let conditions: Vec<StopCondition> = self.stop_conditions.iter().filter(|c| c.0).map(|c| c.1).collect();
+				self.stop_reason = self.islands.stop_reason(&conditions, self.run_time);
+				if self.stop_reason.is_some() {
+					self.running = false;
+				}
+			}
+		}
+
+		let generation = self.islands.generation_number();
+		if !self.qd_mode && self.autosave_interval > 0 && generation >= self.autosaved_generation + self.autosave_interval {
+			self.autosaved_generation = generation;
+			self.autosave_disclaimer = self.check_run_filename()
+				.and_then(|_| save_run(&self.islands.serialize(), &self.run_filename).map_err(|e| e.to_string()))
+				.err();
 		}
     }
 
@@ -76,43 +692,171 @@ impl Tab for EvolveTab {
 			.resizable(false)
 			.default_width(150.0)
 			.show(ctx, |ui| {
-				ui.label(format!("Generation: {}", self.gen_alg.generation_number()));
+				ui.horizontal(|ui| {
+					ui.selectable_value(&mut self.qd_mode, false, "Goal");
+					ui.selectable_value(&mut self.qd_mode, true, "MAP-Elites");
+				});
+				if self.qd_mode {
+					ui.label(format!("Iteration: {}", self.map_elites.iteration_number()));
+					ui.label(format!("Coverage: {:.0}%", self.map_elites.coverage() * 100.0));
+				}
+				else {
+					ui.label(format!("Generation: {}", self.islands.generation_number()));
+				}
 
 				if centered_button(ui, vec2(150.0, 25.0), if self.running { "Pause" } else { "Evolve" }).clicked() {
 					self.running = !self.running;
+					self.stop_reason = None;
+				}
+				if let Some(reason) = &self.stop_reason {
+					ui.colored_label(Color32::YELLOW, format!("Paused: {reason}"));
 				}
 
 				ui.add_enabled_ui(!self.running, |ui| {
 					if centered_button(ui, vec2(150.0, 25.0), "Step").clicked() {
-						self.gen_alg.perform_generation();
+						self.step();
 					}
 					if centered_button(ui, vec2(150.0, 25.0), "Reset").clicked() {
-						self.gen_alg.reset();
+						if self.qd_mode {
+							self.map_elites.set_params(self.islands.islands()[self.island].params().clone());
+							self.map_elites.reset();
+							self.qd_selected = None;
+						}
+						else {
+							self.islands.reset();
+							self.autosaved_generation = 0;
+							self.run_time = Duration::ZERO;
+							self.stop_reason = None;
+						}
 					}
 				});
 				
 
 				ui.separator();
-				ui.label("GA settings");
+				if self.qd_mode {
+					self.draw_map_elites_settings(ui);
+				}
+				else {
+					self.draw_island_settings(ui);
+
+					ui.label(if self.islands.islands().len() > 1 { format!("GA settings (island {})", self.island + 1) } else { "GA settings".into() });
+					let gen_alg = &self.islands.islands()[self.island];
+					let key = (self.island, gen_alg.generation_number(), gen_alg.best().1.to_bits());
+					if self.unique_genotypes.map(|(k, _)| k) != Some(key) {
+						self.unique_genotypes = Some((key, unique_genotypes(gen_alg.agents())));
+					}
+					let unique = self.unique_genotypes.unwrap().1;
+
+					let gen_alg = &mut self.islands.islands_mut()[self.island];
+					drag_label(ui, &mut gen_alg.generation_count, 2..=10000, 5.0, "Generation Count");
+					drag_label(ui, &mut gen_alg.survivors_count, 1..=(gen_alg.generation_count-1), 1.0, "Survivors Count");
+					drag_label(ui, &mut gen_alg.elite_count, 0..=gen_alg.survivors_count, 0.05, "Elites");
+
+					ComboBox::from_label("Selection")
+						.selected_text(gen_alg.selection.name())
+						.show_ui(ui, |ui| {
+							for name in Selection::NAMES {
+								if ui.selectable_label(gen_alg.selection.name() == name, name).clicked() {
+									gen_alg.selection = Selection::from_name(name).unwrap();
+								}
+							}
+						});
+					let generation_count = gen_alg.generation_count;
+					match &mut gen_alg.selection {
+						Selection::Tournament { size, replacement } => {
+							drag_label(ui, size, 2..=(generation_count-1), 0.05, "Tournament Size");
+							ui.checkbox(replacement, "With Replacement");
+						},
+						Selection::Boltzmann { temperature } => {
+							drag_label(ui, temperature, 0.01..=100.0, 0.05, "Temperature");
+						},
+						_ => {},
+					}
+					drag_label(ui, &mut gen_alg.mutation_factor, 0.0..=1.0, 0.002, "Mutation Factor");
+					drag_label(ui, &mut gen_alg.crossover_rate, 0.0..=1.0, 0.002, "Crossover Rate");
+					Self::draw_mutation_settings(ui, &mut gen_alg.mutations);
+					ui.checkbox(&mut gen_alg.parallel, "Parallel Evaluation");
+					ui.label(format!("Cache hits: {:.0}% (last {:.0}%)", gen_alg.fitness_cache.hit_rate() * 100.0, gen_alg.fitness_cache.last_hit_rate() * 100.0));
+					ui.label(format!("Unique genotypes: {} of {}", unique, gen_alg.agents().len()));
+					ui.checkbox(&mut gen_alg.multi_objective, "Multi-objective (NSGA-II)");
+					if gen_alg.multi_objective && centered_button(ui, vec2(150.0, 25.0), "Pareto front").clicked() {
+						self.pareto_window = true;
+					}
+
+					ui.add_space(5.0);
+					drag_label(ui, &mut gen_alg.novelty_weight, 0.0..=1.0, 0.002, "Novelty Weight");
+					if gen_alg.novelty_weight > 0.0 {
+						drag_label(ui, &mut gen_alg.novelty_neighbours, 1..=100, 0.05, "Neighbours");
+						ui.label(format!("Novelty archive: {}", gen_alg.novelty_archive.len()));
+					}
+
+					ui.add_space(5.0);
+					if centered_button(ui, vec2(150.0, 25.0), "Statistics").clicked() {
+						self.stats_window = true;
+					}
 
-				drag_label(ui, &mut self.gen_alg.generation_count, 2..=10000, 5.0, "Generation Count");
-				drag_label(ui, &mut self.gen_alg.survivors_count, 1..=(self.gen_alg.generation_count-1), 1.0, "Survivors Count");
-				drag_label(ui, &mut self.gen_alg.tournament_size, 2..=(self.gen_alg.generation_count-1), 0.05, "Tournament Size");
-				drag_label(ui, &mut self.gen_alg.mutation_factor, 0.0..=1.0, 0.002, "Mutation Factor");
+					ui.add_space(5.0);
+					self.draw_stop_settings(ui);
+				}
 				
 				ui.add_space(5.0);
 				ui.label("Fitness settings:");
-				drag_label(ui, &mut self.gen_alg.params_mut().same_weight, 0.0..=10.0, 0.01, "Equal Cells");
-				drag_label(ui, &mut self.gen_alg.params_mut().different_weight, -10.0..=0.0, 0.01, "Different Cells");
-				drag_label(ui, &mut self.gen_alg.params_mut().size_pow, 0.0..=10.0, 0.01, "Size Power");
-				drag_label(ui, &mut self.gen_alg.params_mut().size_weight, -10.0..=0.0, 0.01, "Size");
-				drag_label(ui, &mut self.gen_alg.params_mut().max_steps, 1..=500, 0.04, "Max Steps");
+				let gen_alg = &mut self.islands.islands_mut()[self.island];
+				// edited on a copy, as changing the params clears the fitness cache
+				let params = gen_alg.params();
+				let old = ([params.same_weight, params.different_weight, params.size_pow, params.size_weight], params.max_steps, params.symmetry);
+				let (mut weights, mut max_steps, mut symmetry) = old;
+				drag_label(ui, &mut weights[0], 0.0..=10.0, 0.01, "Equal Cells");
+				drag_label(ui, &mut weights[1], -10.0..=0.0, 0.01, "Different Cells");
+				drag_label(ui, &mut weights[2], 0.0..=10.0, 0.01, "Size Power");
+				drag_label(ui, &mut weights[3], -10.0..=0.0, 0.01, "Size");
+				drag_label(ui, &mut max_steps, 1..=500, 0.04, "Max Steps");
+				ComboBox::from_label("Symmetry")
+					.selected_text(symmetry.name())
+					.show_ui(ui, |ui| {
+						for name in Symmetry::NAMES {
+							ui.selectable_value(&mut symmetry, Symmetry::from_name(name).unwrap(), name);
+						}
+					});
+				if (weights, max_steps, symmetry) != old {
+					let params = gen_alg.params_mut();
+					[params.same_weight, params.different_weight, params.size_pow, params.size_weight] = weights;
+					params.max_steps = max_steps;
+					params.symmetry = symmetry;
+				}
 
 				ui.separator();
 
-				drag_label(ui, &mut self.visible_count, 1..=self.visible_up_to, 0.3, "Visible");
-				drag_label(ui, &mut self.visible_up_to, self.visible_count..=self.gen_alg.agents().len(), 0.3, "Visible Range");
+				if !self.qd_mode {
+					drag_label(ui, &mut self.visible_count, 1..=self.visible_up_to, 0.3, "Visible");
+					drag_label(ui, &mut self.visible_up_to, self.visible_count..=self.islands.islands()[self.island].agents().len(), 0.3, "Visible Range");
+				}
 				drag_label(ui, &mut self.evolve_budget, 1..=1000, 0.2, "Evolve Budget");
+
+				if !self.qd_mode {
+					ui.separator();
+					ui.label("Run");
+					ui.add_enabled_ui(!self.running, |ui| {
+						if centered_button(ui, vec2(150.0, 25.0), "Save run").clicked() {
+							self.run_saving_window = true;
+						}
+						if centered_button(ui, vec2(150.0, 25.0), "Load run").clicked() {
+							self.run_loading_window = true;
+							self.run_filenames = get_run_filenames();
+							self.run_selected = 0;
+						}
+					});
+					drag_label(ui, &mut self.autosave_interval, 0..=10000, 0.2, "Autosave Interval (0 = off)");
+					if self.autosave_interval > 0 {
+						ui.horizontal(|ui| {
+							ui.label("to:");
+							ui.text_edit_singleline(&mut self.run_filename);
+						});
+					}
+					if let Some(e) = &self.autosave_disclaimer {
+						ui.colored_label(Color32::RED, format!("Autosave failed: {e}"));
+					}
+				}
 			});
 
 			
@@ -124,37 +868,87 @@ impl Tab for EvolveTab {
 				ui.label("target:");
 
 				let (target_rect, _) = ui.allocate_exact_size(vec2(140.0, 100.0), Sense::hover());
-				draw_grid_ui(ui, &self.gen_alg.params().goal, target_rect);
+				draw_grid_ui(ui, &self.islands.islands()[self.island].params().goal, target_rect);
 
 				if centered_button(ui, vec2(150.0, 25.0), "Send to Edit").clicked() {
 					self.send_target = true;
 				}
+				if centered_button(ui, vec2(150.0, 25.0), "Import from image").clicked() {
+					self.import_window = true;
+					if let Some(image) = &self.import_image {
+						self.import_preview = Some(image_to_grid(image, &self.import_settings));
+					}
+				}
 
 				ui.separator();
 				ui.separator();
 
 				ui.label("selected:");
 
-				let inspected = &self.gen_alg.agents()[self.selected];
-				let (agent_rect, _) = ui.allocate_exact_size(vec2(140.0, 100.0), Sense::hover());
-				draw_grid_ui(ui, inspected.0.0.state(), agent_rect);
+				if self.qd_mode {
+					let elite = self.qd_selected.and_then(|[x, y]| self.map_elites.cell(x, y));
+					let (agent_rect, _) = ui.allocate_exact_size(vec2(140.0, 100.0), Sense::hover());
+					if let Some(elite) = elite {
+						draw_grid_ui(ui, elite.agent.0.state(), agent_rect);
+					}
+
+					ui.separator();
+
+					match elite {
+						Some(elite) => {
+							ui.label(format!("fitness: {:.2}", elite.fitness));
+							for (name, value) in DESCRIPTORS.iter().zip(&elite.descriptors) {
+								ui.label(format!("{}: {:.2}", name.to_lowercase(), value));
+							}
+						},
+						None => { ui.label("click a niche of the archive"); },
+					}
+				}
+				else {
+					let inspected = &self.islands.islands()[self.island].agents()[self.selected];
+					let (agent_rect, _) = ui.allocate_exact_size(vec2(140.0, 100.0), Sense::hover());
+					draw_grid_ui(ui, inspected.0.0.state(), agent_rect);
 				
-				ui.separator();
+					ui.separator();
 
-				ui.label(format!("{}{} out of {}", self.selected + 1, number_suffix(self.selected + 1), self.gen_alg.agents().len()));
-				ui.label(format!("fitness: {:.2}", inspected.1));
+					ui.label(format!("{}{} out of {}", self.selected + 1, number_suffix(self.selected + 1), self.islands.islands()[self.island].agents().len()));
+					ui.label(format!("fitness: {:.2}", inspected.1));
+					if let Some(novelty) = self.islands.islands()[self.island].novelty().get(self.selected) {
+						ui.label(format!("novelty: {:.2}", novelty));
+					}
+					if let Some(objectives) = self.islands.islands()[self.island].objectives().get(self.selected) {
+						for (name, value) in OBJECTIVES.iter().zip(objectives) {
+							ui.label(format!("{}: {:.1}", name.to_lowercase(), value.abs()));
+						}
+					}
+					ui.checkbox(&mut self.lineage_strip, "Show lineage");
+				}
 
 				ui.separator();
 
-				if centered_button(ui, vec2(150.0, 25.0), "Send to Edit").clicked() {
-					self.send_selected = Some(0);
-				}
-				if centered_button(ui, vec2(150.0, 25.0), "Send to Grow").clicked() {
-					self.send_selected = Some(2);
-				}
+				ui.add_enabled_ui(!self.qd_mode || self.selected_elite().is_some(), |ui| {
+					if centered_button(ui, vec2(150.0, 25.0), "Send to Edit").clicked() {
+						self.send_selected = Some(0);
+					}
+					if centered_button(ui, vec2(150.0, 25.0), "Send to Grow").clicked() {
+						self.send_selected = Some(2);
+					}
+				});
 			});
 
+			if !self.qd_mode && self.lineage_strip {
+				TopBottomPanel::bottom("Lineage")
+					.resizable(false)
+					.exact_height(135.0)
+					.show(ctx, |ui| self.draw_lineage(ui));
+			}
+
 			CentralPanel::default().show(ctx, |ui| {
+				if self.qd_mode {
+					self.draw_archive(ui);
+					return;
+				}
+
 				let origin = ui.next_widget_position();
 				let available = ui.available_size();
 				let aspect = available.x / available.y;
@@ -190,13 +984,13 @@ impl Tab for EvolveTab {
 
 					let resp = ui.allocate_rect(rect, Sense::click());
 
-					let agent_index = (i as f32 / (self.visible_count - 1) as f32 * (self.visible_up_to-1).min(self.gen_alg.agents().len() - 1) as f32) as usize;
+					let agent_index = (i as f32 / (self.visible_count - 1) as f32 * (self.visible_up_to-1).min(self.islands.islands()[self.island].agents().len() - 1) as f32) as usize;
 					
 					let importance = if resp.hovered() {50} else {0} + if self.selected == agent_index {40} else {0};
 					let background = Color32::from_gray(importance);
 					ui.painter().rect(rect, 0.0, background, Stroke::new(2.0, Color32::DARK_GRAY));
 
-					draw_grid_ui(ui, self.gen_alg.agents()[agent_index].0.0.state(), rect.expand(-5.0));
+					draw_grid_ui(ui, self.islands.islands()[self.island].agents()[agent_index].0.0.state(), rect.expand(-5.0));
 
 					if resp.clicked() {
 						self.selected = agent_index;
@@ -206,22 +1000,81 @@ impl Tab for EvolveTab {
 					});
 				}
 			});
+
+		if self.import_window {
+			self.draw_import_window(ctx);
+		}
+		if self.pareto_window {
+			self.draw_pareto_window(ctx);
+		}
+		if self.stats_window {
+			self.draw_stats_window(ctx);
+		}
+		if self.run_saving_window {
+			self.draw_run_saving_window(ctx);
+		}
+		if self.run_loading_window {
+			self.draw_run_loading_window(ctx);
+		}
+		if self.seed_window {
+			self.draw_seed_window(ctx);
+		}
     }
 
     fn send_to(&mut self) -> Option<(usize, Vec<Grid>)> {
 		if self.send_target {
 			self.send_target = false;
-			return Some((0, vec![self.gen_alg.params().goal.clone()]));
+			return Some((0, vec![self.islands.islands()[self.island].params().goal.clone()]));
 		}
 
         if let Some(i) = self.send_selected.take() {
-			return Some((i, self.gen_alg.agents()[self.selected].0.0.rules().into()));
+			if self.qd_mode {
+				return self.selected_elite().map(|ls| (i, ls.0.rules().into()));
+			}
+			return Some((i, self.islands.islands()[self.island].agents()[self.selected].0.0.rules().into()));
 		}
 		None
     }
 
     fn receive(&mut self, system: Vec<Grid>) {
 		let goal = system.into_iter().next().unwrap();
-        self.gen_alg.params_mut().goal = goal;
+        self.set_goal(goal);
     }
+
+	fn send_settings(&mut self) -> Option<GlsFile> {
+		if self.send_target || self.send_selected.is_none() { return None; }
+
+		let island = &self.islands.islands()[self.island];
+		let ls = if self.qd_mode { self.selected_elite()? } else { &island.agents()[self.selected].0 };
+		let params = island.params();
+
+		let mut file = GlsFile::new(Vec::new());
+		file.axiom = ls.1.axiom.clone();
+		file.goal = Some(params.goal.clone());
+		file.growth = Some(GrowthSettings { max_steps: ls.1.steps(params.max_steps) as u32 });
+		file.evolution = Some(EvolutionSettings {
+			generation_count: island.generation_count as u32,
+			survivors_count: island.survivors_count as u32,
+			tournament_size: match island.selection {
+				Selection::Tournament { size, .. } => size as u32,
+				_ => 2,
+			},
+			mutation_factor: island.mutation_factor,
+			same_weight: params.same_weight,
+			different_weight: params.different_weight,
+			size_weight: params.size_weight,
+			size_pow: params.size_pow,
+		});
+		Some(file)
+	}
+
+	fn receive_seed(&mut self, mut rules: Vec<Grid>, axiom: Grid) {
+		let mut growth = Growth::new(axiom);
+		rules.canonicalize_with(&mut growth.axiom);
+		self.seed_rules = rules;
+		self.seed_growth = growth;
+		self.seed_window = true;
+		self.running = false;
+		self.qd_mode = false;
+	}
 }
\ No newline at end of file
@@ -1,6 +1,6 @@
-use std::{fs::{self, read_dir}, path::PathBuf};
+use std::{fs::{self, read_dir}, path::PathBuf, time::{SystemTime, UNIX_EPOCH}};
 
-use soft_evolution::l_system::grid::Grid;
+use soft_evolution::gls_file::GlsFile;
 
 pub fn is_alphanumeric(s: &str) -> bool {
 	let mut alphanumeric = s.is_ascii();
@@ -16,38 +16,46 @@ pub fn is_alphanumeric(s: &str) -> bool {
 	alphanumeric
 }
 
-pub fn save_rules(rules: &[Grid], filename: &str) -> Result<(), std::io::Error> {
-	let mut data = Vec::new();
-	for rule in rules {
-		data.extend(rule.serialize())
-	}
-	fs::write(PathBuf::from(format!("./saves/{}.gls", filename)), data)
+pub fn save_file(file: &GlsFile, filename: &str) -> Result<(), std::io::Error> {
+	fs::write(PathBuf::from(format!("./saves/{}.gls", filename)), file.serialize())
 }
 
-pub fn load_rules(filename: &str) -> Result<Vec<Grid>, String> {
-	let mut rules = Vec::new();
+pub fn load_file(filename: &str) -> Result<GlsFile, String> {
 	let data = fs::read(PathBuf::from(format!("./saves/{}.gls", filename)));
 	if let Err(e) = data {
 		return Err(e.to_string());
 	}
-	let data = data.unwrap();
 
-	let mut cursor = 0;
-	while cursor < data.len() {
-		let res = Grid::deserialize(&data[cursor..]);
-		if let Err(_) = res {
-			return Err("Invalid file".into());
-		}
+	GlsFile::deserialize(&data.unwrap())
+}
 
-		let (grid, c) = res.unwrap();
-		rules.push(grid);
-		cursor += c;
-	}
+pub fn export_path(filename: &str, extension: &str) -> Result<PathBuf, std::io::Error> {
+	fs::create_dir_all("./exports/")?;
+	Ok(PathBuf::from(format!("./exports/{}.{}", filename, extension)))
+}
+
+pub fn unix_time() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
 
-	Ok(rules)
+pub fn save_run(data: &[u8], filename: &str) -> Result<(), std::io::Error> {
+	fs::create_dir_all("./saves/")?;
+	fs::write(PathBuf::from(format!("./saves/{}.glsrun", filename)), data)
+}
+
+pub fn load_run(filename: &str) -> Result<Vec<u8>, String> {
+	fs::read(PathBuf::from(format!("./saves/{}.glsrun", filename))).map_err(|e| e.to_string())
 }
 
 pub fn get_filenames() -> Vec<String> {
+	get_filenames_with_extension("gls")
+}
+
+pub fn get_run_filenames() -> Vec<String> {
+	get_filenames_with_extension("glsrun")
+}
+
+fn get_filenames_with_extension(wanted: &str) -> Vec<String> {
 	let mut files = Vec::new();
 
 	if let Ok(iter) = read_dir("./saves/") {
@@ -76,7 +84,7 @@ pub fn get_filenames() -> Vec<String> {
 			let extension = extension.unwrap();
 
 			if !is_alphanumeric(&name) { continue; }
-			if extension != "gls" { continue; }
+			if extension != wanted { continue; }
 
 			files.push(name);
 		}
@@ -1,12 +1,13 @@
-use egui_macroquad::{macroquad::prelude::*, egui::{Context, SidePanel, panel::Side, vec2, Slider}};
-use soft_evolution::l_system::{grid::Grid, LSystem, cell::{Direction, Cell}};
+use egui_macroquad::{macroquad::prelude::*, egui::{Context, SidePanel, panel::Side, vec2, Slider, Window, Color32}};
+use soft_evolution::{l_system::{grid::Grid, LSystem, cell::{Direction, Cell}}, gls_file::GlsFile, render::animation::{AnimationSettings, AnimationFormat, save_animation}};
 
-use crate::{controls::Controls, state::Tab, drawing::{draw_grid_lines, pixel_width, draw_grid_axes, draw_grid_animated, draw_grid}, ui::centered_button};
+use crate::{controls::Controls, state::Tab, drawing::{draw_grid_lines, pixel_width, draw_grid_axes, draw_grid_animated, draw_grid}, ui::{centered_button, drag_label}, files::{is_alphanumeric, export_path}};
 
 pub struct GrowTab {
 	controls: Controls,
 	
 	system: LSystem,
+	axiom: Grid,
 	prev_system: Grid,
 	animated_rule: usize,
 	animated_from: Option<([i32; 2], Direction)>,
@@ -20,6 +21,12 @@ pub struct GrowTab {
 	animate: bool,
 
 	send: Option<usize>,
+
+	export_window: bool,
+	export_filename: String,
+	export_format: AnimationFormat,
+	export_settings: AnimationSettings,
+	export_disclaimer: Option<String>,
 }
 
 impl GrowTab {
@@ -43,6 +50,57 @@ impl GrowTab {
 		}
 
 	}
+
+	fn draw_export_window(&mut self, ctx: &Context) {
+		Window::new("Export animation")
+			.collapsible(false)
+			.constraint_to(ctx.screen_rect())
+			.show(ctx, |ui| {
+				ui.text_edit_singleline(&mut self.export_filename);
+
+				ui.horizontal(|ui| {
+					ui.radio_value(&mut self.export_format, AnimationFormat::Gif, "GIF");
+					ui.radio_value(&mut self.export_format, AnimationFormat::Apng, "APNG");
+				});
+
+				drag_label(ui, &mut self.export_settings.steps, 1..=10000, 0.2, "Steps");
+				drag_label(ui, &mut self.export_settings.tween_frames, 0..=30, 0.05, "Frames per step (0 = no animation)");
+				drag_label(ui, &mut self.export_settings.frame_delay, 0.01..=2.0, 0.005, "Frame delay [s]");
+				drag_label(ui, &mut self.export_settings.final_frames, 0..=100, 0.1, "Final frame repeats");
+				drag_label(ui, &mut self.export_settings.render.cell_size, 1..=64, 0.1, "Cell size [px]");
+				ui.checkbox(&mut self.export_settings.render.grid_lines, "Grid lines");
+				ui.checkbox(&mut self.export_settings.render.axes, "Axes");
+
+				ui.colored_label(Color32::RED, self.export_disclaimer.as_ref().unwrap_or(&"".into()));
+
+				if ui.button("export").clicked() {
+					if self.export_filename.trim().len() == 0 {
+						self.export_disclaimer = Some("Empty filename".into());
+					}
+					else if !is_alphanumeric(&self.export_filename) {
+						self.export_disclaimer = Some("Non-alphanumeric characters found".into());
+					}
+					else {
+						let system = LSystem::new(self.axiom.clone(), self.system.rules().into());
+						let result = export_path(&self.export_filename, self.export_format.extension())
+							.map_err(|e| e.to_string())
+							.and_then(|path| save_animation(&system, &self.export_settings, self.export_format, path));
+
+						match result {
+							Ok(()) => {
+								self.export_window = false;
+								self.export_disclaimer = None;
+							},
+							Err(e) => self.export_disclaimer = Some(e),
+						}
+					}
+				}
+				if ui.button("cancel").clicked() {
+					self.export_window = false;
+					self.export_disclaimer = None;
+				}
+			});
+	}
 }
 
 impl Tab for GrowTab {
@@ -53,6 +111,7 @@ impl Tab for GrowTab {
 			system: LSystem::new(Grid::single(Cell::Stem(0, Direction::UP)), vec![
 				Grid::single(Cell::Stem(0, Direction::UP))
 			]),
+			axiom: Grid::single(Cell::Stem(0, Direction::UP)),
 			prev_system: Grid::single(Cell::Empty),
 			animated_rule: 0,
 			animated_from: None,
@@ -66,6 +125,16 @@ impl Tab for GrowTab {
 			animate: true,
 
 			send: None,
+
+			export_window: false,
+			export_filename: String::new(),
+			export_format: AnimationFormat::Gif,
+			export_settings: AnimationSettings {
+				tween_frames: 8,
+				frame_delay: 0.04,
+				..Default::default()
+			},
+			export_disclaimer: None,
 		}
     }
 
@@ -110,6 +179,7 @@ impl Tab for GrowTab {
 			.resizable(false)
 			.default_width(150.0)
 			.show(ctx, |ui| {
+				ui.set_enabled(!self.export_window);
 				ui.label(format!("steps: {}", self.iteration));
 
 				let text = if self.running { "Pause" } else { "Grow" };
@@ -122,7 +192,7 @@ impl Tab for GrowTab {
 						self.step_system();		
 					}
 					if centered_button(ui, vec2(150.0, 25.0), "Reset").clicked() {
-						self.system.set_state(Grid::single(Cell::Stem(0, Direction::UP)));
+						self.system.set_state(self.axiom.clone());
 						self.iteration = 0;
 					}
 				});
@@ -143,6 +213,16 @@ impl Tab for GrowTab {
 
 				ui.separator();
 
+				if centered_button(ui, vec2(150.0, 25.0), "Export animation").clicked() {
+					self.export_window = true;
+					self.running = false;
+					if self.iteration > 0 {
+						self.export_settings.steps = self.iteration;
+					}
+				}
+
+				ui.separator();
+
 				if centered_button(ui, vec2(150.0, 25.0), "Send to Edit").clicked() {
 					self.send = Some(0);
 				}
@@ -151,6 +231,10 @@ impl Tab for GrowTab {
 				}
 			}
 		);
+
+		if self.export_window {
+			self.draw_export_window(ctx);
+		}
     }
 
     fn send_to(&mut self) -> Option<(usize, Vec<Grid>)> {
@@ -167,10 +251,21 @@ impl Tab for GrowTab {
     }
 
     fn receive(&mut self, system: Vec<Grid>) {
-        self.system = LSystem::new(Grid::single(Cell::Stem(0, Direction::UP)), system);
+		self.axiom = Grid::single(Cell::Stem(0, Direction::UP));
+        self.system = LSystem::new(self.axiom.clone(), system);
 		self.running = false;
 		self.iteration = 0;
     }
-	
-	
+
+	fn send_settings(&mut self) -> Option<GlsFile> {
+		if self.send != Some(0) { return None; }
+		let mut file = GlsFile::new(Vec::new());
+		file.axiom = self.axiom.clone();
+		Some(file)
+	}
+
+	fn receive_settings(&mut self, file: GlsFile) {
+		self.system = LSystem::new(file.axiom.clone(), self.system.rules().into());
+		self.axiom = file.axiom;
+	}
 }
\ No newline at end of file
@@ -1,4 +1,3 @@
-mod ls_evolve;
 mod controls;
 mod state;
 mod edit_tab;
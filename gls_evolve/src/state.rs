@@ -1,5 +1,5 @@
 use egui_macroquad::egui::{self, Context};
-use soft_evolution::l_system::grid::Grid;
+use soft_evolution::{l_system::grid::Grid, gls_file::GlsFile};
 
 use crate::{edit_tab::EditTab, evolve_tab::EvolveTab, grow_tab::GrowTab};
 
@@ -9,6 +9,13 @@ pub trait Tab {
 	fn draw_ui(&mut self, ctx: &Context);
 	fn send_to(&mut self) -> Option<(usize, Vec<Grid>)>;
 	fn receive(&mut self, system: Vec<Grid>);
+	// rules meant to start the population of the Evolve tab, rather than to become its goal
+	// rules and the axiom they grow from
+	fn send_seed(&mut self) -> Option<(Vec<Grid>, Grid)> { None }
+	fn receive_seed(&mut self, _rules: Vec<Grid>, _axiom: Grid) {}
+	// the file the rules from `send_to` belong to, for their axiom, goal and settings. Its rules are left empty
+	fn send_settings(&mut self) -> Option<GlsFile> { None }
+	fn receive_settings(&mut self, _file: GlsFile) {}
 }
 
 
@@ -52,9 +59,18 @@ impl State {
 		});
 		egui_macroquad::draw();
 
+		let seed = tab.send_seed();
+		let settings = tab.send_settings();
 		if let Some((i, grid)) = tab.send_to() {
 			self.current_tab = i;
 			self.tabs[self.current_tab].receive(grid);
+			if let Some(file) = settings {
+				self.tabs[self.current_tab].receive_settings(file);
+			}
+		}
+		if let Some((rules, axiom)) = seed {
+			self.current_tab = 1;
+			self.tabs[self.current_tab].receive_seed(rules, axiom);
 		}
 	}
 }
\ No newline at end of file
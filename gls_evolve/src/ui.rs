@@ -129,4 +129,33 @@ pub fn drag_label<Num: Numeric>(ui: &mut Ui, v: &mut Num, range: RangeInclusive<
 		ui.add(DragValue::new(v).clamp_range(range).speed(speed));
 		ui.label(label);
 	});
-}
\ No newline at end of file
+}
+// line chart of several series sharing one value range, with a legend below
+pub fn plot_lines(ui: &mut Ui, size: Vec2, series: &[(&str, Color32, Vec<f32>)]) {
+	let (rect, _) = ui.allocate_exact_size(size, Sense::hover());
+	ui.painter().rect(rect, 0.0, Color32::BLACK, Stroke::new(1.0, Color32::DARK_GRAY));
+
+	let values = series.iter().flat_map(|(_, _, v)| v.iter().copied()).filter(|v| v.is_finite());
+	let (min, max) = values.fold((f32::MAX, -f32::MAX), |(min, max), v| (min.min(v), max.max(v)));
+	let plot = rect.shrink(4.0);
+
+	for (_, colour, values) in series {
+		if values.len() < 2 { continue; }
+
+		let points = values.iter().enumerate().map(|(i, &v)| {
+			let x = i as f32 / (values.len() - 1) as f32;
+			let y = if max > min { (v.clamp(min, max) - min) / (max - min) } else { 0.5 };
+			plot.lerp_inside(vec2(x, 1.0 - y))
+		}).collect();
+		ui.painter().add(egui_macroquad::egui::Shape::line(points, Stroke::new(1.5, *colour)));
+	}
+
+	if min <= max {
+		ui.label(format!("{min:.2} to {max:.2}"));
+	}
+	ui.horizontal_wrapped(|ui| {
+		for (name, colour, _) in series {
+			ui.colored_label(*colour, *name);
+		}
+	});
+}
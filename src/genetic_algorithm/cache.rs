@@ -0,0 +1,95 @@
+use std::collections::{HashMap, VecDeque};
+
+// fitness, objectives and behaviour of an evaluated agent
+pub(super) type Evaluation = (f32, Vec<f32>, Vec<f32>);
+
+// evaluated agents by genome hash, so duplicates are copied instead of grown again. The oldest are forgotten first
+#[derive(Clone, Debug)]
+pub struct FitnessCache<T> {
+	entries: HashMap<u64, (T, Evaluation)>,
+	order: VecDeque<u64>,
+	mode: [bool; 2], // multi-objective and novelty, the evaluations differ between them
+	pub limit: usize, // 0 disables the cache
+	hits: u64,
+	lookups: u64,
+	last: [u64; 2], // hits and lookups of the last evaluated generation
+}
+
+impl<T> Default for FitnessCache<T> {
+	fn default() -> Self {
+		Self {
+			entries: HashMap::new(),
+			order: VecDeque::new(),
+			mode: [false; 2],
+			limit: 10000,
+			hits: 0,
+			lookups: 0,
+			last: [0; 2],
+		}
+	}
+}
+
+impl<T> FitnessCache<T> {
+	// called before every evaluation, as the mode and limit may have changed since
+	pub(super) fn prepare(&mut self, mode: [bool; 2]) {
+		if self.mode != mode {
+			self.mode = mode;
+			self.clear();
+		}
+		self.trim();
+	}
+
+	pub(super) fn get(&self, hash: u64) -> Option<&(T, Evaluation)> {
+		self.entries.get(&hash)
+	}
+
+	pub(super) fn insert(&mut self, hash: u64, agent: T, evaluation: Evaluation) {
+		if self.limit == 0 { return; }
+		if self.entries.insert(hash, (agent, evaluation)).is_none() {
+			self.order.push_back(hash);
+		}
+		self.trim();
+	}
+
+	fn trim(&mut self) {
+		while self.entries.len() > self.limit {
+			let oldest = self.order.pop_front().unwrap();
+			self.entries.remove(&oldest);
+		}
+	}
+
+	pub(super) fn count(&mut self, hits: u64, lookups: u64) {
+		self.hits += hits;
+		self.lookups += lookups;
+		self.last = [hits, lookups];
+	}
+
+	// forgets the entries, needed whenever the fitness function changes
+	pub fn clear(&mut self) {
+		self.entries.clear();
+		self.order.clear();
+	}
+
+	pub fn reset_hit_rate(&mut self) {
+		self.hits = 0;
+		self.lookups = 0;
+		self.last = [0; 2];
+	}
+
+	// share of agents that didn't have to be evaluated since the last reset
+	pub fn hit_rate(&self) -> f32 {
+		self.hits as f32 / self.lookups.max(1) as f32
+	}
+
+	pub fn last_hit_rate(&self) -> f32 {
+		self.last[0] as f32 / self.last[1].max(1) as f32
+	}
+
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+}
@@ -0,0 +1,405 @@
+use std::collections::VecDeque;
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::gls_file::{Reader, GRID_HEADER, write_section};
+
+use super::{GeneticAlgorithm, evolve::Evolve, islands::{Islands, Topology}, selection::Selection, novelty::NoveltyArchive, cache::FitnessCache, genealogy::Genealogy, mutation::{MutationTable, OperatorStats}, statistics::GenerationStats};
+
+// run files start with these bytes, rules files with `gls_file::MAGIC`
+pub const MAGIC: [u8; 4] = *b"GLSR";
+pub const FORMAT_VERSION: u16 = 2;
+
+const SECTION_ISLANDS: [u8; 4] = *b"ISLS";
+const SECTION_ISLAND: [u8; 4] = *b"ISLE";
+const SECTION_SETTINGS: [u8; 4] = *b"SETT";
+const SECTION_RNG: [u8; 4] = *b"RNG ";
+const SECTION_PARAMS: [u8; 4] = *b"PARM";
+const SECTION_AGENTS: [u8; 4] = *b"AGNT";
+const SECTION_OBJECTIVES: [u8; 4] = *b"OBJS";
+const SECTION_NOVELTY: [u8; 4] = *b"NOVS";
+const SECTION_NOVELTY_ARCHIVE: [u8; 4] = *b"NOVA";
+const SECTION_HISTORY: [u8; 4] = *b"HIST";
+const SECTION_MUTATIONS: [u8; 4] = *b"MUTS";
+
+// binary form of agents and params in run files, in the style of `Grid::serialize` and `Grid::deserialize`
+pub trait Persist: Sized {
+	fn persist(&self) -> Vec<u8>;
+	// the value and the number of bytes it was read from
+	fn restore(data: &[u8]) -> Result<(Self, usize), String>;
+	// the same from a run file of an older format version. Types that didn't change keep reading the current format
+	fn restore_version(data: &[u8], _version: u16) -> Result<(Self, usize), String> {
+		Self::restore(data)
+	}
+}
+
+impl<T, U> GeneticAlgorithm<T, U> where T: Evolve<U> + Clone + Persist, U: Sync + Persist {
+	// sections of one population, without a file header
+	fn serialize_sections(&self) -> Vec<u8> {
+		let mut data = Vec::new();
+
+		let mut settings = Vec::new();
+		settings.extend_from_slice(&(self.generation_count as u32).to_be_bytes());
+		settings.extend_from_slice(&(self.survivors_count as u32).to_be_bytes());
+		settings.extend_from_slice(&self.mutation_factor.to_be_bytes());
+		settings.extend_from_slice(&self.crossover_rate.to_be_bytes());
+		write_selection(&mut settings, &self.selection);
+		settings.extend_from_slice(&(self.elite_count as u32).to_be_bytes());
+		settings.push(self.parallel as u8);
+		settings.push(self.multi_objective as u8);
+		settings.extend_from_slice(&self.novelty_weight.to_be_bytes());
+		settings.extend_from_slice(&(self.novelty_neighbours as u32).to_be_bytes());
+		settings.extend_from_slice(&self.generation_number.to_be_bytes());
+		write_section(&mut data, SECTION_SETTINGS, &settings);
+
+		write_section(&mut data, SECTION_RNG, &write_rng(&self.rng));
+		write_section(&mut data, SECTION_PARAMS, &self.params.persist());
+
+		let mut agents = Vec::new();
+		agents.extend_from_slice(&(self.agents.len() as u32).to_be_bytes());
+		for (agent, fitness) in &self.agents {
+			agents.extend_from_slice(&fitness.to_be_bytes());
+			agents.extend(agent.persist());
+		}
+		write_section(&mut data, SECTION_AGENTS, &agents);
+
+		if !self.objectives.is_empty() {
+			let mut objectives = Vec::new();
+			write_vectors(&mut objectives, self.objectives.iter());
+			write_section(&mut data, SECTION_OBJECTIVES, &objectives);
+		}
+		if !self.novelty.is_empty() {
+			let mut novelty = Vec::new();
+			write_floats(&mut novelty, &self.novelty);
+			write_section(&mut data, SECTION_NOVELTY, &novelty);
+		}
+
+		let mut archive = Vec::new();
+		archive.extend_from_slice(&(self.novelty_archive.limit as u32).to_be_bytes());
+		archive.extend_from_slice(&(self.novelty_archive.additions as u32).to_be_bytes());
+		write_vectors(&mut archive, self.novelty_archive.entries.iter());
+		write_section(&mut data, SECTION_NOVELTY_ARCHIVE, &archive);
+
+		let mut history = Vec::new();
+		history.extend_from_slice(&(self.history.len() as u32).to_be_bytes());
+		for stats in &self.history {
+			history.extend_from_slice(&stats.generation.to_be_bytes());
+			for value in [stats.best, stats.mean, stats.median, stats.worst, stats.std_dev, stats.diversity, stats.duration, stats.elapsed] {
+				history.extend_from_slice(&value.to_be_bytes());
+			}
+			history.extend_from_slice(&(stats.rule_counts.len() as u32).to_be_bytes());
+			for count in &stats.rule_counts {
+				history.extend_from_slice(&count.to_be_bytes());
+			}
+		}
+		write_section(&mut data, SECTION_HISTORY, &history);
+
+		// the operator names come from the agent type, only the weights and statistics are stored
+		let mut mutations = vec![self.mutations.adaptive as u8];
+		mutations.extend_from_slice(&(self.mutations.weights.len() as u32).to_be_bytes());
+		for ((weight, stats), quality) in self.mutations.weights.iter().zip(&self.mutations.stats).zip(&self.mutations.quality) {
+			mutations.extend_from_slice(&weight.to_be_bytes());
+			mutations.extend_from_slice(&stats.uses.to_be_bytes());
+			mutations.extend_from_slice(&stats.improvements.to_be_bytes());
+			mutations.extend_from_slice(&quality.to_be_bytes());
+		}
+		write_section(&mut data, SECTION_MUTATIONS, &mutations);
+
+		data
+	}
+
+	fn deserialize_sections(mut reader: Reader, version: u16) -> Result<Self, String> {
+		let mut settings = None;
+		let mut rng = None;
+		let mut params = None;
+		let mut agents = None;
+		let mut objectives = Vec::new();
+		let mut novelty = Vec::new();
+		let mut novelty_archive = NoveltyArchive::default();
+		let mut history = Vec::new();
+		let mut mutations = MutationTable::new(T::mutations());
+
+		while !reader.is_empty() {
+			let tag = reader.bytes(4)?;
+			let length = reader.u32()? as usize;
+			let mut section = Reader::new(reader.bytes(length)?);
+
+			match [tag[0], tag[1], tag[2], tag[3]] {
+				SECTION_SETTINGS => settings = Some((
+					section.u32()? as usize,
+					section.u32()? as usize,
+					section.f32()?,
+					section.f32()?,
+					read_selection(&mut section)?,
+					section.u32()? as usize,
+					section.u8()? != 0,
+					section.u8()? != 0,
+					section.f32()?,
+					section.u32()? as usize,
+					section.u32()?,
+				)),
+				SECTION_RNG => rng = Some(read_rng(&mut section)?),
+				SECTION_PARAMS => params = Some(section.parse(|data| U::restore_version(data, version))?),
+				SECTION_AGENTS => {
+					// a fitness, the rule count and at least the axiom
+					let count = section.count(4 + 4 + GRID_HEADER)?;
+					let mut list = Vec::with_capacity(count);
+					for _ in 0..count {
+						let fitness = section.f32()?;
+						list.push((section.parse(|data| T::restore_version(data, version))?, fitness));
+					}
+					agents = Some(list);
+				},
+				SECTION_OBJECTIVES => objectives = read_vectors(&mut section)?,
+				SECTION_NOVELTY => novelty = read_floats(&mut section)?,
+				SECTION_NOVELTY_ARCHIVE => {
+					novelty_archive.limit = section.u32()? as usize;
+					novelty_archive.additions = section.u32()? as usize;
+					novelty_archive.entries = VecDeque::from(read_vectors(&mut section)?);
+				},
+				SECTION_HISTORY => {
+					let count = section.count(4 + 8 * 4 + 4)?;
+					for _ in 0..count {
+						let generation = section.u32()?;
+						let [best, mean, median, worst, std_dev, diversity, duration, elapsed] = [
+							section.f32()?, section.f32()?, section.f32()?, section.f32()?,
+							section.f32()?, section.f32()?, section.f32()?, section.f32()?,
+						];
+						let rule_counts = (0..section.count(4)?).map(|_| section.u32()).collect::<Result<_, _>>()?;
+						history.push(GenerationStats { generation, best, mean, median, worst, std_dev, diversity, rule_counts, duration, elapsed });
+					}
+				},
+				SECTION_MUTATIONS => {
+					mutations.adaptive = section.u8()? != 0;
+					// operators are only ever appended, older runs can have fewer. The new ones keep their default weights
+					let count = section.u32()? as usize;
+					let known = if version < FORMAT_VERSION { count <= mutations.names.len() } else { count == mutations.names.len() };
+					if !known {
+						return Err("Run was saved with different mutation operators".into());
+					}
+					for i in 0..count {
+						mutations.weights[i] = section.f32()?;
+						mutations.stats[i] = OperatorStats { uses: section.u64()?, improvements: section.u64()? };
+						mutations.quality[i] = section.f32()?;
+					}
+				},
+				_ => {},
+			}
+		}
+
+		let Some(settings) = settings else { return Err("Run has no settings".into()); };
+		let Some(rng) = rng else { return Err("Run has no random state".into()); };
+		let Some(params) = params else { return Err("Run has no fitness settings".into()); };
+		let Some(mut agents) = agents else { return Err("Run has no agents".into()); };
+		if agents.is_empty() {
+			return Err("Run has no agents".into());
+		}
+		// scores kept per agent have to line up with the agents, objectives also with each other
+		if !objectives.is_empty() && (objectives.len() != agents.len() || objectives.iter().any(|o| o.len() != objectives[0].len())) {
+			return Err("Run has objectives that don't match its agents".into());
+		}
+		if !novelty.is_empty() && novelty.len() != agents.len() {
+			return Err("Run has novelty scores that don't match its agents".into());
+		}
+
+		let (generation_count, survivors_count, mutation_factor, crossover_rate, selection, elite_count, parallel, multi_objective, novelty_weight, novelty_neighbours, generation_number) = settings;
+		if survivors_count == 0 || survivors_count >= generation_count {
+			return Err("Run keeps no survivors or as many as its population".into());
+		}
+
+		// the grown bodies aren't stored, the fitnesses are. The raw ones and the behaviours are kept to score injected agents against
+		let novelty_rewarded = novelty_weight > 0.0;
+		let mut scores = Vec::with_capacity(agents.len());
+		let mut behaviours = Vec::new();
+		for (agent, _) in &mut agents {
+			agent.reset();
+			scores.push(agent.fitness(&params));
+			if novelty_rewarded { behaviours.push(agent.behaviour(&params)); }
+		}
+		let complete = (!multi_objective || objectives.len() == agents.len()) && (!novelty_rewarded || novelty.len() == agents.len());
+		let scored = complete.then_some([multi_objective, novelty_rewarded]);
+
+		// the genealogy isn't stored either, the agents start over as its roots
+		let mut genealogy = Genealogy::default();
+		let ids: Vec<u64> = agents.iter().map(|_| genealogy.born(Vec::new(), generation_number, "loaded")).collect();
+		genealogy.evaluated(&ids, &agents);
+
+		Ok(Self {
+			rng,
+			mutation_factor,
+			crossover_rate,
+			generation_count,
+			survivors_count,
+			selection,
+			elite_count,
+			parallel,
+			multi_objective,
+			novelty_weight,
+			novelty_neighbours,
+			novelty_archive,
+			fitness_cache: FitnessCache::default(),
+			mutations,
+			offspring: Vec::new(),
+			agents,
+			ids,
+			genealogy,
+			objectives,
+			novelty,
+			scores,
+			behaviours,
+			scored,
+			parents: Vec::new(),
+			generation_number,
+			history,
+			params,
+		})
+	}
+}
+
+impl<T, U> Islands<T, U> where T: Evolve<U> + Clone + Persist, U: Sync + Send + Clone + Persist {
+	// everything needed to continue the run exactly where it stopped
+	pub fn serialize(&self) -> Vec<u8> {
+		let mut data = Vec::new();
+
+		data.extend_from_slice(&MAGIC);
+		data.extend_from_slice(&FORMAT_VERSION.to_be_bytes());
+
+		let mut settings = Vec::new();
+		settings.push(match self.topology { Topology::Ring => 0, Topology::Random => 1 });
+		settings.extend_from_slice(&self.migration_interval.to_be_bytes());
+		settings.extend_from_slice(&(self.migrants as u32).to_be_bytes());
+		settings.push(self.parallel as u8);
+		settings.extend_from_slice(&self.generation_number.to_be_bytes());
+		settings.push(self.seed.is_some() as u8);
+		settings.extend_from_slice(&self.seed.unwrap_or(0).to_be_bytes());
+		write_section(&mut data, SECTION_ISLANDS, &settings);
+
+		write_section(&mut data, SECTION_RNG, &write_rng(&self.rng));
+
+		for island in &self.islands {
+			write_section(&mut data, SECTION_ISLAND, &island.serialize_sections());
+		}
+
+		data
+	}
+
+	pub fn deserialize(data: &[u8]) -> Result<Self, String> {
+		if !data.starts_with(&MAGIC) {
+			return Err("Not a run file".into());
+		}
+
+		let mut reader = Reader::new(&data[MAGIC.len()..]);
+		// version 1 runs are from before genomes had their own axiom
+		let version = reader.u16()?;
+		if !(1..=FORMAT_VERSION).contains(&version) {
+			return Err(format!("Unsupported run file version {version}"));
+		}
+
+		let mut settings = None;
+		let mut rng = None;
+		let mut islands = Vec::new();
+
+		while !reader.is_empty() {
+			let tag = reader.bytes(4)?;
+			let length = reader.u32()? as usize;
+			let mut section = Reader::new(reader.bytes(length)?);
+
+			match [tag[0], tag[1], tag[2], tag[3]] {
+				SECTION_ISLANDS => settings = Some((
+					if section.u8()? == 0 { Topology::Ring } else { Topology::Random },
+					section.u32()?,
+					section.u32()? as usize,
+					section.u8()? != 0,
+					section.u32()?,
+					(section.u8()? != 0, section.u64()?),
+				)),
+				SECTION_RNG => rng = Some(read_rng(&mut section)?),
+				SECTION_ISLAND => islands.push(GeneticAlgorithm::deserialize_sections(section, version)?),
+				_ => {},
+			}
+		}
+
+		let Some((topology, migration_interval, migrants, parallel, generation_number, (seeded, seed))) = settings else {
+			return Err("Run has no island settings".into());
+		};
+		let Some(rng) = rng else { return Err("Run has no random state".into()); };
+		if islands.is_empty() {
+			return Err("Run has no islands".into());
+		}
+
+		Ok(Self {
+			rng,
+			seed: seeded.then_some(seed),
+			islands,
+			topology,
+			migration_interval,
+			migrants,
+			parallel,
+			generation_number,
+		})
+	}
+}
+
+fn write_selection(data: &mut Vec<u8>, selection: &Selection) {
+	let (tag, size, replacement, temperature) = match *selection {
+		Selection::Tournament { size, replacement } => (0, size, replacement, 0.0),
+		Selection::Roulette => (1, 0, false, 0.0),
+		Selection::Rank => (2, 0, false, 0.0),
+		Selection::Truncation => (3, 0, false, 0.0),
+		Selection::Boltzmann { temperature } => (4, 0, false, temperature),
+	};
+	data.push(tag);
+	data.extend_from_slice(&(size as u32).to_be_bytes());
+	data.push(replacement as u8);
+	data.extend_from_slice(&temperature.to_be_bytes());
+}
+
+fn read_selection(reader: &mut Reader) -> Result<Selection, String> {
+	let (tag, size, replacement, temperature) = (reader.u8()?, reader.u32()? as usize, reader.u8()? != 0, reader.f32()?);
+	match tag {
+		0 => Ok(Selection::Tournament { size, replacement }),
+		1 => Ok(Selection::Roulette),
+		2 => Ok(Selection::Rank),
+		3 => Ok(Selection::Truncation),
+		4 => Ok(Selection::Boltzmann { temperature }),
+		_ => Err(format!("Unknown selection strategy {tag}")),
+	}
+}
+
+fn write_rng(rng: &ChaCha8Rng) -> Vec<u8> {
+	let mut data = Vec::new();
+	data.extend_from_slice(&rng.get_seed());
+	data.extend_from_slice(&rng.get_stream().to_be_bytes());
+	data.extend_from_slice(&rng.get_word_pos().to_be_bytes());
+	data
+}
+
+fn read_rng(reader: &mut Reader) -> Result<ChaCha8Rng, String> {
+	let mut rng = ChaCha8Rng::from_seed(reader.bytes(32)?.try_into().unwrap());
+	rng.set_stream(reader.u64()?);
+	rng.set_word_pos(reader.u128()?);
+	Ok(rng)
+}
+
+fn write_floats(data: &mut Vec<u8>, values: &[f32]) {
+	data.extend_from_slice(&(values.len() as u32).to_be_bytes());
+	for value in values {
+		data.extend_from_slice(&value.to_be_bytes());
+	}
+}
+
+fn read_floats(reader: &mut Reader) -> Result<Vec<f32>, String> {
+	(0..reader.count(4)?).map(|_| reader.f32()).collect()
+}
+
+fn write_vectors<'a, I: ExactSizeIterator<Item = &'a Vec<f32>>>(data: &mut Vec<u8>, vectors: I) {
+	data.extend_from_slice(&(vectors.len() as u32).to_be_bytes());
+	for vector in vectors {
+		write_floats(data, vector);
+	}
+}
+
+fn read_vectors(reader: &mut Reader) -> Result<Vec<Vec<f32>>, String> {
+	(0..reader.count(4)?).map(|_| read_floats(reader)).collect()
+}
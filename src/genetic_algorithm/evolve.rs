@@ -1,8 +1,36 @@
-use rand::rngs::ThreadRng;
+use rand::Rng;
 
-pub trait Evolve<T> {
-	fn new_random(rng: &mut ThreadRng) -> Self;
+// agents are moved between threads and share the params when fitnesses are evaluated in parallel
+pub trait Evolve<T: Sync>: Send {
+	fn new_random<R: Rng>(rng: &mut R) -> Self;
 	fn reset(&mut self);
-	fn new_mutated(other: &Self, factor: f32, rng: &mut ThreadRng) -> Self;
+	fn new_mutated<R: Rng>(other: &Self, factor: f32, rng: &mut R) -> Self;
+	// names and default weights of separate mutation operators. Without any, `new_mutated` is used alone
+	fn mutations() -> Vec<(&'static str, f32)> where Self: Sized { Vec::new() }
+	// multiplier of an operator's weight at the given mutation factor, so the factor can shift the odds between operators
+	fn mutation_bias(_operator: usize, _factor: f32) -> f32 where Self: Sized { 1.0 }
+	// mutation by the operator of that index, along with the index of the operator actually applied
+	fn new_mutated_by<R: Rng>(other: &Self, _operator: usize, factor: f32, rng: &mut R) -> (Self, usize) where Self: Sized {
+		(Self::new_mutated(other, factor, rng), 0)
+	}
+	// recombination of two parents, None if the type doesn't support it
+	fn crossover<R: Rng>(_a: &Self, _b: &Self, _rng: &mut R) -> Option<Self> where Self: Sized { None }
 	fn fitness(&mut self, params: &T) -> f32;
+	// separate scores, all maximized, for multi-objective evolution. Just the fitness by default
+	fn objectives(&mut self, params: &T) -> Vec<f32> {
+		vec![self.fitness(params)]
+	}
+	// behaviour of the agent for quality-diversity search, called after the fitness was evaluated
+	fn descriptors(&self, _params: &T) -> Vec<f32> {
+		Vec::new()
+	}
+	// phenotype for novelty search, compared by euclidean distance. The descriptors by default
+	fn behaviour(&self, params: &T) -> Vec<f32> {
+		self.descriptors(params)
+	}
+	// for the statistics: how different two genotypes are and how many rules one has
+	fn genotype_distance(&self, _other: &Self) -> f32 where Self: Sized { 0.0 }
+	fn rule_count(&self) -> usize { 0 }
+	// equal for agents that evaluate the same, so their results can be reused. None is never cached
+	fn genome_hash(&self) -> Option<u64> { None }
 }
\ No newline at end of file
@@ -0,0 +1,93 @@
+use std::collections::{HashMap, HashSet};
+
+use super::evolve::Evolve;
+
+#[derive(Clone, Debug)]
+pub struct Ancestor<T> {
+	pub id: u64,
+	pub parents: Vec<u64>, // two after crossover, none for random, injected and seed agents
+	pub generation: u32, // the generation it was born into
+	pub origin: &'static str, // the mutation that made it, or how it entered the population
+	pub agent: T, // as first evaluated, reset so only its genome is kept and not the grown body
+	pub fitness: f32, // when first evaluated
+}
+
+// where the agents of a population came from. Only the ancestors of living agents are kept
+#[derive(Clone, Debug)]
+pub struct Genealogy<T> {
+	ancestors: HashMap<u64, Ancestor<T>>,
+	births: HashMap<u64, (Vec<u64>, u32, &'static str)>, // agents not evaluated yet
+	next_id: u64,
+}
+
+impl<T> Default for Genealogy<T> {
+	fn default() -> Self {
+		Self {
+			ancestors: HashMap::new(),
+			births: HashMap::new(),
+			next_id: 0,
+		}
+	}
+}
+
+impl<T: Clone> Genealogy<T> {
+	// a new id for an agent, recorded once it's evaluated
+	pub(super) fn born(&mut self, parents: Vec<u64>, generation: u32, origin: &'static str) -> u64 {
+		let id = self.next_id;
+		self.next_id += 1;
+		self.births.insert(id, (parents, generation, origin));
+		id
+	}
+
+	pub(super) fn evaluated<U: Sync>(&mut self, ids: &[u64], agents: &[(T, f32)]) where T: Evolve<U> {
+		for (id, (agent, fitness)) in ids.iter().zip(agents) {
+			if let Some((parents, generation, origin)) = self.births.remove(id) {
+				let mut agent = agent.clone();
+				agent.reset();
+				self.ancestors.insert(*id, Ancestor { id: *id, parents, generation, origin, agent, fitness: *fitness });
+			}
+		}
+	}
+
+	// forgets every agent that isn't one of the living or their ancestor
+	pub(super) fn prune(&mut self, living: &[u64]) {
+		let mut kept = HashSet::new();
+		let mut stack = living.to_vec();
+		while let Some(id) = stack.pop() {
+			if !kept.insert(id) { continue; }
+			if let Some(ancestor) = self.ancestors.get(&id) {
+				stack.extend(&ancestor.parents);
+			}
+		}
+		self.ancestors.retain(|id, _| kept.contains(id));
+		self.births.retain(|id, _| kept.contains(id));
+	}
+
+	pub fn clear(&mut self) {
+		self.ancestors.clear();
+		self.births.clear();
+	}
+
+	pub fn get(&self, id: u64) -> Option<&Ancestor<T>> {
+		self.ancestors.get(&id)
+	}
+
+	// the agent, its first parent, that one's first parent and so on
+	pub fn lineage(&self, id: u64) -> Vec<&Ancestor<T>> {
+		let mut lineage = Vec::new();
+		let mut next = self.ancestors.get(&id);
+		while let Some(ancestor) = next {
+			lineage.push(ancestor);
+			next = ancestor.parents.first().and_then(|parent| self.ancestors.get(parent));
+		}
+		lineage
+	}
+
+	pub fn len(&self) -> usize {
+		self.ancestors.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.ancestors.is_empty()
+	}
+}
@@ -0,0 +1,204 @@
+use std::time::{Duration, Instant};
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use super::{GeneticAlgorithm, evolve::Evolve, stopping::{StopCondition, RunSummary}};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Topology {
+	Ring, // every island sends to the next one
+	Random, // every island sends to a random other one
+}
+
+// separately evolving populations that exchange their best agents every few generations
+pub struct Islands<T, U> where T: Evolve<U>, U: Sync {
+	pub(super) rng: ChaCha8Rng,
+	pub(super) seed: Option<u64>, // seeds new islands reproducibly when the model was seeded
+	pub(super) islands: Vec<GeneticAlgorithm<T, U>>,
+	pub topology: Topology,
+	pub migration_interval: u32, // generations between migrations, 0 never migrates
+	pub migrants: usize, // best agents sent by every island
+	pub parallel: bool, // islands evolve on separate threads, only has an effect with the `parallel` feature
+	pub(super) generation_number: u32,
+}
+
+impl<T, U> Islands<T, U> where T: Evolve<U> + Clone, U: Sync + Send + Clone {
+	pub fn new(count: usize, generation_count: usize, survivors_count: usize, mutation_factor: f32, params: U) -> Self {
+		let islands = (0..count.max(1)).map(|_| GeneticAlgorithm::new(generation_count, survivors_count, mutation_factor, params.clone())).collect();
+		Self::with_islands(islands, ChaCha8Rng::from_entropy(), None)
+	}
+
+	// the first island is seeded like a lone `GeneticAlgorithm::new_seeded`
+	pub fn new_seeded(count: usize, generation_count: usize, survivors_count: usize, mutation_factor: f32, params: U, seed: u64) -> Self {
+		let islands = (0..count.max(1)).map(|i| {
+			GeneticAlgorithm::new_seeded(generation_count, survivors_count, mutation_factor, params.clone(), island_seed(seed, i))
+		}).collect();
+		Self::with_islands(islands, ChaCha8Rng::seed_from_u64(!seed), Some(seed))
+	}
+
+	fn with_islands(islands: Vec<GeneticAlgorithm<T, U>>, rng: ChaCha8Rng, seed: Option<u64>) -> Self {
+		Self {
+			rng,
+			seed,
+			islands,
+			topology: Topology::Ring,
+			migration_interval: 10,
+			migrants: 5,
+			parallel: true,
+			generation_number: 0,
+		}
+	}
+
+	pub fn perform_generation(&mut self) {
+		self.evolve_islands();
+		self.generation_number += 1;
+
+		if self.migration_interval > 0 && self.generation_number.is_multiple_of(self.migration_interval) {
+			self.migrate();
+		}
+	}
+
+	fn evolve_islands(&mut self) {
+		#[cfg(feature = "parallel")]
+		if self.parallel {
+			use rayon::prelude::*;
+			self.islands.par_iter_mut().for_each(|island| island.perform_generation());
+			return;
+		}
+
+		self.islands.iter_mut().for_each(|island| island.perform_generation());
+	}
+
+	pub fn perform_generations(&mut self, n: u32) {
+		for _ in 0..n {
+			self.perform_generation();
+		}
+	}
+
+	// a target fitness is reached when any island reaches it, stagnation and diversity collapse once every island does
+	pub fn stop_reason(&self, conditions: &[StopCondition], elapsed: Duration) -> Option<StopCondition> {
+		conditions.iter().copied().find(|c| {
+			let reached = |island: &GeneticAlgorithm<T, U>| c.reached(island.history(), self.generation_number, elapsed);
+			match c {
+				StopCondition::TargetFitness(_) => self.islands.iter().any(reached),
+				_ => self.islands.iter().all(reached),
+			}
+		})
+	}
+
+	pub fn run_until(&mut self, conditions: &[StopCondition]) -> RunSummary {
+		let start = Instant::now();
+		let first_generation = self.generation_number;
+
+		let stopped_by = if conditions.is_empty() { None } else {
+			loop {
+				if let Some(reason) = self.stop_reason(conditions, start.elapsed()) {
+					break Some(reason);
+				}
+				self.perform_generation();
+			}
+		};
+
+		RunSummary {
+			stopped_by,
+			generations: self.generation_number - first_generation,
+			generation_number: self.generation_number,
+			best_fitness: self.best().1,
+			duration: start.elapsed(),
+		}
+	}
+
+	pub fn migrate(&mut self) {
+		let count = self.islands.len();
+		if count < 2 || self.migrants == 0 { return; }
+
+		let mut arrivals: Vec<Vec<T>> = vec![Vec::new(); count];
+		for (i, island) in self.islands.iter().enumerate() {
+			let destination = match self.topology {
+				Topology::Ring => (i + 1) % count,
+				Topology::Random => (i + self.rng.gen_range(1..count)) % count,
+			};
+			arrivals[destination].extend(island.agents().iter().take(self.migrants).map(|e| e.0.clone()));
+		}
+
+		#[cfg(feature = "parallel")]
+		if self.parallel {
+			use rayon::prelude::*;
+			self.islands.par_iter_mut().zip(arrivals).for_each(|(island, agents)| island.inject(agents));
+			return;
+		}
+
+		for (island, agents) in self.islands.iter_mut().zip(arrivals) {
+			island.inject(agents);
+		}
+	}
+
+	pub fn reset(&mut self) {
+		self.generation_number = 0;
+		self.islands.iter_mut().for_each(|island| island.reset());
+	}
+
+	// every island gets its own copy of the agents in place of its worst ones
+	pub fn inject(&mut self, agents: &[T]) {
+		self.islands.iter_mut().for_each(|island| island.inject(agents.to_vec()));
+	}
+
+	// every island starts over from the seed and its own mutants
+	pub fn reset_from_seed(&mut self, seed: &T) {
+		self.generation_number = 0;
+		self.islands.iter_mut().for_each(|island| island.reset_from_seed(seed));
+	}
+
+	// new islands copy the settings of the first one
+	pub fn set_island_count(&mut self, count: usize) {
+		let count = count.max(1);
+		self.islands.truncate(count);
+
+		while self.islands.len() < count {
+			let first = &self.islands[0];
+			let mut island = match self.seed {
+				Some(seed) => GeneticAlgorithm::new_seeded(first.generation_count, first.survivors_count, first.mutation_factor, first.params().clone(), island_seed(seed, self.islands.len())),
+				None => GeneticAlgorithm::new(first.generation_count, first.survivors_count, first.mutation_factor, first.params().clone()),
+			};
+			island.crossover_rate = first.crossover_rate;
+			island.selection = first.selection;
+			island.elite_count = first.elite_count;
+			island.parallel = first.parallel;
+			island.multi_objective = first.multi_objective;
+			island.novelty_weight = first.novelty_weight;
+			island.novelty_neighbours = first.novelty_neighbours;
+			self.islands.push(island);
+		}
+	}
+
+	pub fn islands(&self) -> &[GeneticAlgorithm<T, U>] {
+		&self.islands
+	}
+
+	pub fn islands_mut(&mut self) -> &mut [GeneticAlgorithm<T, U>] {
+		&mut self.islands
+	}
+
+	// index of the island with the fittest agent
+	pub fn best_island(&self) -> usize {
+		(0..self.islands.len()).max_by(|&a, &b| self.islands[a].best().1.total_cmp(&self.islands[b].best().1)).unwrap()
+	}
+
+	pub fn best(&self) -> &(T, f32) {
+		self.islands[self.best_island()].best()
+	}
+
+	pub fn generation_number(&self) -> u32 {
+		self.generation_number
+	}
+
+	pub fn seed(&self) -> Option<u64> {
+		self.seed
+	}
+}
+
+fn island_seed(seed: u64, island: usize) -> u64 {
+	seed.wrapping_add((island as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15))
+}
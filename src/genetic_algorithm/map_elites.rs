@@ -0,0 +1,217 @@
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use super::evolve::Evolve;
+
+// one dimension of the archive: which descriptor it's indexed by and how that descriptor is binned
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Axis {
+	pub descriptor: usize,
+	pub min: f32,
+	pub max: f32,
+	pub bins: usize,
+}
+
+impl Axis {
+	// values outside of the range fall into the outermost bins
+	pub fn bin(&self, value: f32) -> usize {
+		let t = if self.max > self.min { (value - self.min) / (self.max - self.min) } else { 0.0 };
+		((t * self.bins as f32).floor().max(0.0) as usize).min(self.bins.max(1) - 1)
+	}
+}
+
+pub struct Elite<T> {
+	pub agent: T,
+	pub fitness: f32,
+	pub descriptors: Vec<f32>,
+}
+
+// quality-diversity search: a grid of niches over two behaviour descriptors, each keeping the fittest agent that landed in it
+pub struct MapElites<T, U> where T: Evolve<U>, U: Sync {
+	rng: ChaCha8Rng,
+	pub mutation_factor: f32,
+	pub crossover_rate: f32,
+	pub batch_size: usize, // agents evaluated per iteration
+	pub parallel: bool, // only has an effect with the `parallel` feature
+	axes: [Axis; 2],
+	archive: Vec<Option<Elite<T>>>, // row-major, x along the first axis
+	iteration_number: u32,
+	params: U,
+}
+
+impl<T, U> MapElites<T, U> where T: Evolve<U>, U: Sync {
+	pub fn new(axes: [Axis; 2], batch_size: usize, mutation_factor: f32, params: U) -> Self {
+		Self::with_rng(axes, batch_size, mutation_factor, params, ChaCha8Rng::from_entropy())
+	}
+
+	pub fn new_seeded(axes: [Axis; 2], batch_size: usize, mutation_factor: f32, params: U, seed: u64) -> Self {
+		Self::with_rng(axes, batch_size, mutation_factor, params, ChaCha8Rng::seed_from_u64(seed))
+	}
+
+	fn with_rng(axes: [Axis; 2], batch_size: usize, mutation_factor: f32, params: U, rng: ChaCha8Rng) -> Self {
+		let mut ret = Self {
+			rng,
+			mutation_factor,
+			crossover_rate: 0.0,
+			batch_size,
+			parallel: true,
+			axes,
+			archive: (0..axes[0].bins * axes[1].bins).map(|_| None).collect(),
+			iteration_number: 0,
+			params,
+		};
+
+		let batch = (0..batch_size).map(|_| T::new_random(&mut ret.rng)).collect();
+		ret.evaluate_and_insert(batch);
+
+		ret
+	}
+
+	fn evaluate(&self, batch: Vec<T>) -> Vec<Elite<T>> {
+		let params = &self.params;
+		let evaluate = |mut agent: T| {
+			let fitness = agent.fitness(params);
+			let descriptors = agent.descriptors(params);
+			Elite { agent, fitness, descriptors }
+		};
+
+		#[cfg(feature = "parallel")]
+		if self.parallel {
+			use rayon::prelude::*;
+			return batch.into_par_iter().map(evaluate).collect();
+		}
+
+		batch.into_iter().map(evaluate).collect()
+	}
+
+	fn evaluate_and_insert(&mut self, batch: Vec<T>) {
+		for elite in self.evaluate(batch) {
+			self.insert(elite);
+		}
+	}
+
+	// true if the elite took over its niche
+	fn insert(&mut self, elite: Elite<T>) -> bool {
+		let Some(index) = self.index_of(&elite.descriptors) else { return false; };
+
+		match &self.archive[index] {
+			Some(current) if current.fitness >= elite.fitness => false,
+			_ => {
+				self.archive[index] = Some(elite);
+				true
+			},
+		}
+	}
+
+	fn index_of(&self, descriptors: &[f32]) -> Option<usize> {
+		let x = self.axes[0].bin(*descriptors.get(self.axes[0].descriptor)?);
+		let y = self.axes[1].bin(*descriptors.get(self.axes[1].descriptor)?);
+		Some(y * self.axes[0].bins + x)
+	}
+
+	pub fn perform_iteration(&mut self) {
+		let occupied: Vec<usize> = (0..self.archive.len()).filter(|&i| self.archive[i].is_some()).collect();
+
+		let mut batch = Vec::with_capacity(self.batch_size);
+		for _ in 0..self.batch_size {
+			let Some(&parent) = occupied.choose(&mut self.rng) else {
+				batch.push(T::new_random(&mut self.rng));
+				continue;
+			};
+			let parent = &self.archive[parent].as_ref().unwrap().agent;
+
+			let child = if self.crossover_rate > 0.0 && self.rng.gen_bool(self.crossover_rate as f64) {
+				let other = &self.archive[*occupied.choose(&mut self.rng).unwrap()].as_ref().unwrap().agent;
+				T::crossover(parent, other, &mut self.rng)
+			}
+			else {
+				None
+			};
+
+			batch.push(T::new_mutated(child.as_ref().unwrap_or(parent), self.mutation_factor, &mut self.rng));
+		}
+
+		self.evaluate_and_insert(batch);
+		self.iteration_number += 1;
+	}
+
+	pub fn perform_iterations(&mut self, n: u32) {
+		for _ in 0..n {
+			self.perform_iteration();
+		}
+	}
+
+	pub fn reset(&mut self) {
+		self.iteration_number = 0;
+		self.archive.iter_mut().for_each(|e| *e = None);
+
+		let batch = (0..self.batch_size).map(|_| T::new_random(&mut self.rng)).collect();
+		self.evaluate_and_insert(batch);
+	}
+
+	pub fn axes(&self) -> [Axis; 2] {
+		self.axes
+	}
+
+	// the elites are sorted into the new niches, the ones that lose their niche are dropped
+	pub fn set_axes(&mut self, axes: [Axis; 2]) {
+		let old = std::mem::take(&mut self.archive);
+		self.axes = axes;
+		self.archive = (0..axes[0].bins * axes[1].bins).map(|_| None).collect();
+
+		for elite in old.into_iter().flatten() {
+			self.insert(elite);
+		}
+	}
+
+	// re-evaluates every elite, needed after the params change
+	pub fn reevaluate(&mut self) {
+		let old = std::mem::take(&mut self.archive);
+		self.archive = (0..old.len()).map(|_| None).collect();
+
+		let batch = old.into_iter().flatten().map(|mut e| {
+			e.agent.reset();
+			e.agent
+		}).collect();
+		self.evaluate_and_insert(batch);
+	}
+
+	pub fn bins(&self) -> [usize; 2] {
+		[self.axes[0].bins, self.axes[1].bins]
+	}
+
+	pub fn cell(&self, x: usize, y: usize) -> Option<&Elite<T>> {
+		if x >= self.axes[0].bins || y >= self.axes[1].bins { return None; }
+		self.archive[y * self.axes[0].bins + x].as_ref()
+	}
+
+	pub fn elites(&self) -> impl Iterator<Item = &Elite<T>> {
+		self.archive.iter().flatten()
+	}
+
+	// share of the niches that are occupied
+	pub fn coverage(&self) -> f32 {
+		self.elites().count() as f32 / self.archive.len().max(1) as f32
+	}
+
+	pub fn best(&self) -> Option<&Elite<T>> {
+		self.elites().max_by(|a, b| a.fitness.total_cmp(&b.fitness))
+	}
+
+	pub fn set_params(&mut self, params: U) {
+		self.params = params;
+	}
+
+	pub fn params(&self) -> &U {
+		&self.params
+	}
+
+	pub fn params_mut(&mut self) -> &mut U {
+		&mut self.params
+	}
+
+	pub fn iteration_number(&self) -> u32 {
+		self.iteration_number
+	}
+}
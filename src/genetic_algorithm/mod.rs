@@ -1,78 +1,337 @@
 pub mod evolve;
+pub mod selection;
+pub mod pareto;
+pub mod map_elites;
+pub mod novelty;
+pub mod islands;
+pub mod statistics;
+pub mod checkpoint;
+pub mod stopping;
+pub mod cache;
+pub mod genealogy;
+pub mod mutation;
 
 use evolve::Evolve;
+use selection::Selection;
+use novelty::NoveltyArchive;
+use statistics::GenerationStats;
+use stopping::{StopCondition, RunSummary};
+use cache::FitnessCache;
+use genealogy::Genealogy;
+use mutation::MutationTable;
 
-use rand::{seq::SliceRandom, rngs::ThreadRng, thread_rng, Rng};
+use std::{collections::HashMap, time::{Duration, Instant}};
 
-pub struct GeneticAlgorithm<T, U> where T: Evolve<U> {
-	rng: ThreadRng,
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+// the tunable values of a `GeneticAlgorithm`, to store them or carry them over to another run
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Settings {
 	pub mutation_factor: f32,
+	pub crossover_rate: f32,
 	pub generation_count: usize,
 	pub survivors_count: usize,
-	pub tournament_size: usize,
+	pub selection: Selection,
+	pub elite_count: usize,
+	pub parallel: bool,
+	pub multi_objective: bool,
+	pub novelty_weight: f32,
+	pub novelty_neighbours: usize,
+}
+
+pub struct GeneticAlgorithm<T, U> where T: Evolve<U>, U: Sync {
+	rng: ChaCha8Rng,
+	pub mutation_factor: f32,
+	pub crossover_rate: f32,
+	pub generation_count: usize,
+	pub survivors_count: usize,
+	pub selection: Selection,
+	pub elite_count: usize, // the fittest agents always survive, on top of the selected ones
+	pub parallel: bool, // only has an effect with the `parallel` feature
+	pub multi_objective: bool, // rank agents by non-dominated sorting of their objectives instead of their fitness
+	pub novelty_weight: f32, // 0 ranks by fitness alone, 1 by novelty alone. In multi-objective mode any weight adds novelty as an objective
+	pub novelty_neighbours: usize,
+	pub novelty_archive: NoveltyArchive,
+	pub fitness_cache: FitnessCache<T>,
+	pub mutations: MutationTable,
 	agents: Vec<(T, f32)>,
+	ids: Vec<u64>, // per agent, to look it up in the genealogy
+	genealogy: Genealogy<T>,
+	objectives: Vec<Vec<f32>>, // per agent, only evaluated in multi-objective mode
+	novelty: Vec<f32>, // per agent, only evaluated when novelty is rewarded
+	scores: Vec<f32>, // per agent, the fitness before novelty is blended in
+	behaviours: Vec<Vec<f32>>, // per agent, only evaluated when novelty is rewarded
+	scored: Option<[bool; 2]>, // multi-objective and novelty mode of the stored results, None if they're out of date
+	parents: Vec<usize>, // indices of the survivors chosen as parents, repeated by how often they were selected
+	offspring: Vec<(usize, usize, f32, Vec<f32>)>, // index, mutation operator and parent score and objectives of the offspring not yet evaluated
 	generation_number: u32,
+	history: Vec<GenerationStats>,
 	params: U,
 }
 
-impl<T, U> GeneticAlgorithm<T, U> where T: Evolve<U> {
+impl<T, U> GeneticAlgorithm<T, U> where T: Evolve<U> + Clone, U: Sync {
 	pub fn new(generation_count: usize, survivors_count: usize, mutation_factor: f32, params: U) -> Self {
-		let mut rng = thread_rng();
+		Self::with_rng(generation_count, survivors_count, mutation_factor, params, ChaCha8Rng::from_entropy())
+	}
+
+	// the same seed and settings always evolve the same population
+	pub fn new_seeded(generation_count: usize, survivors_count: usize, mutation_factor: f32, params: U, seed: u64) -> Self {
+		Self::with_rng(generation_count, survivors_count, mutation_factor, params, ChaCha8Rng::seed_from_u64(seed))
+	}
+
+	fn with_rng(generation_count: usize, survivors_count: usize, mutation_factor: f32, params: U, mut rng: ChaCha8Rng) -> Self {
 		let agents = (0..generation_count).map(|_| (T::new_random(&mut rng), 0.0)).collect();
+		let mut genealogy = Genealogy::default();
+		let ids = (0..generation_count).map(|_| genealogy.born(Vec::new(), 0, "random")).collect();
 		let mut ret = Self {
 			mutation_factor,
+			crossover_rate: 0.0,
 			rng,
 			generation_count,
 			survivors_count,
 			agents,
+			ids,
+			genealogy,
+			objectives: Vec::new(),
+			novelty: Vec::new(),
+			scores: Vec::new(),
+			behaviours: Vec::new(),
+			scored: None,
+			parents: Vec::new(),
 			generation_number: 0,
+			history: Vec::new(),
 			params,
-			tournament_size: 2,
+			selection: Selection::default(),
+			elite_count: 1,
+			parallel: true,
+			multi_objective: false,
+			novelty_weight: 0.0,
+			novelty_neighbours: 15,
+			novelty_archive: NoveltyArchive::default(),
+			fitness_cache: FitnessCache::default(),
+			mutations: MutationTable::new(T::mutations()),
+			offspring: Vec::new(),
 		};
 
-		ret.calculate_fitnesses();
+		let start = Instant::now();
+		ret.calculate_fitnesses(0);
+		ret.sort();
+		ret.record_statistics(start.elapsed().as_secs_f32());
 
 		ret
 	}
 
-	fn calculate_fitnesses(&mut self) {
-		for (agent, fitness) in &mut self.agents {
-			*fitness = agent.fitness(&self.params);
+	// evaluates the agents from `from` on, the earlier ones keep their stored results. Only a whole generation is added to the novelty archive
+	fn calculate_fitnesses(&mut self, from: usize) {
+		let multi_objective = self.multi_objective;
+		let novelty = self.novelty_weight > 0.0;
+		let params = &self.params;
+
+		self.fitness_cache.prepare([multi_objective, novelty]);
+
+		// agents already in the cache, or twins of one evaluated now, are copied instead of grown
+		let mut results = vec![None; self.agents.len()];
+		let mut pending: Vec<bool> = (0..self.agents.len()).map(|i| i >= from).collect();
+		let mut twins = Vec::new();
+		let mut first: HashMap<u64, usize> = HashMap::new();
+		let mut lookups = 0;
+		for (i, (agent, _)) in self.agents.iter_mut().enumerate().skip(from) {
+			let Some(hash) = agent.genome_hash().filter(|_| self.fitness_cache.limit > 0) else { continue; };
+			lookups += 1;
+			if let Some((cached, evaluation)) = self.fitness_cache.get(hash) {
+				*agent = cached.clone();
+				results[i] = Some(evaluation.clone());
+				pending[i] = false;
+			}
+			else if let Some(&twin) = first.get(&hash) {
+				twins.push((i, twin));
+				pending[i] = false;
+			}
+			else {
+				first.insert(hash, i);
+			}
+		}
+		let hits = lookups - first.len() as u64;
+		self.fitness_cache.count(hits, lookups);
+
+		let evaluated = evaluate_all(&mut self.agents, &pending, self.parallel, |agent| {
+			let (fitness, objectives) = if multi_objective { (0.0, agent.objectives(params)) } else { (agent.fitness(params), Vec::new()) };
+			let behaviour = if novelty { agent.behaviour(params) } else { Vec::new() };
+			(fitness, objectives, behaviour)
+		});
+		for (result, evaluation) in results.iter_mut().zip(evaluated) {
+			if evaluation.is_some() { *result = evaluation; }
+		}
+
+		for (&hash, &i) in &first {
+			self.fitness_cache.insert(hash, self.agents[i].0.clone(), results[i].clone().unwrap());
+		}
+		for &(i, twin) in &twins {
+			self.agents[i].0 = self.agents[twin].0.clone();
+			results[i] = results[twin].clone();
+		}
+
+		self.scores.truncate(from);
+		self.objectives.truncate(from);
+		self.behaviours.truncate(from);
+		self.novelty.truncate(from);
+		let mut objectives = Vec::new();
+		for (fitness, agent_objectives, behaviour) in results.into_iter().skip(from).map(Option::unwrap) {
+			self.scores.push(fitness);
+			if multi_objective { objectives.push(agent_objectives); }
+			if novelty { self.behaviours.push(behaviour); }
+		}
+
+		if novelty {
+			let scored = self.novelty_archive.score_from(&self.behaviours, from, self.novelty_neighbours, self.parallel);
+			if from == 0 {
+				self.novelty_archive.extend(&self.behaviours, &scored);
+			}
+			self.novelty.extend(scored);
+		}
+
+		// ranks and scaling depend on the whole generation, so they're redone for the stored results too
+		let mut fitnesses = self.scores.clone();
+		if multi_objective {
+			for (objectives, &novelty) in objectives.iter_mut().zip(self.novelty.iter().skip(from)) {
+				objectives.push(novelty);
+			}
+			self.objectives.extend(objectives);
+			fitnesses = pareto::crowded_fitnesses(&self.objectives);
+		}
+		else if novelty {
+			// both are scaled to 0..1 over the generation before blending
+			let (fitness, novelty) = (normalized(&fitnesses), normalized(&self.novelty));
+			for i in 0..fitnesses.len() {
+				fitnesses[i] = fitness[i] * (1.0 - self.novelty_weight) + novelty[i] * self.novelty_weight;
+			}
+		}
+		self.scored = Some([multi_objective, novelty]);
+
+		for (agent, fitness) in self.agents.iter_mut().zip(fitnesses) {
+			agent.1 = fitness;
+		}
+
+		self.genealogy.evaluated(&self.ids, &self.agents);
+		self.genealogy.prune(&self.ids);
+	}
+
+	// sorting is only for presentation
+	fn sort(&mut self) {
+		let mut order: Vec<usize> = (0..self.agents.len()).collect();
+		order.sort_unstable_by(|&a, &b| self.agents[b].1.total_cmp(&self.agents[a].1));
+
+		let mut agents: Vec<Option<(T, f32)>> = self.agents.drain(..).map(Some).collect();
+		self.agents = order.iter().map(|&i| agents[i].take().unwrap()).collect();
+		self.ids = order.iter().map(|&i| self.ids[i]).collect();
+		if !self.objectives.is_empty() {
+			self.objectives = order.iter().map(|&i| std::mem::take(&mut self.objectives[i])).collect();
+		}
+		if !self.novelty.is_empty() {
+			self.novelty = order.iter().map(|&i| self.novelty[i]).collect();
+		}
+		if !self.behaviours.is_empty() {
+			self.behaviours = order.iter().map(|&i| std::mem::take(&mut self.behaviours[i])).collect();
+		}
+		if !self.scores.is_empty() {
+			self.scores = order.iter().map(|&i| self.scores[i]).collect();
 		}
 	}
 
 	fn reproduce(&mut self) {
+		if self.parents.is_empty() {
+			self.parents = (0..self.agents.len()).collect();
+		}
+
 		while self.agents.len() < self.generation_count {
-			let parent = &self.agents.choose(&mut self.rng).unwrap().0;
-			let new = T::new_mutated(parent, self.mutation_factor, &mut self.rng);
+			let parent = *self.parents.choose(&mut self.rng).unwrap();
+			let mut parents = vec![self.ids[parent]];
+
+			let child = if self.crossover_rate > 0.0 && self.rng.gen_bool(self.crossover_rate as f64) {
+				let other = *self.parents.choose(&mut self.rng).unwrap();
+				let child = T::crossover(&self.agents[parent].0, &self.agents[other].0, &mut self.rng);
+				if child.is_some() { parents.push(self.ids[other]); }
+				child
+			}
+			else {
+				None
+			};
+
+			let (new, operator) = Self::mutate(child.as_ref().unwrap_or(&self.agents[parent].0), &self.mutations, self.mutation_factor, &mut self.rng);
+			let origin = match operator {
+				Some(operator) => {
+					let parent_score = self.scores.get(parent).copied().unwrap_or(-f32::MAX);
+					let parent_objectives = self.objectives.get(parent).cloned().unwrap_or_default();
+					self.offspring.push((self.agents.len(), operator, parent_score, parent_objectives));
+					self.mutations.names[operator]
+				},
+				None => "mutation",
+			};
+			self.ids.push(self.genealogy.born(parents, self.generation_number + 1, origin));
 			self.agents.push((new, 0.0));
 		}
 	}
 
+	// by an operator of the table, or by `new_mutated` when the type has none
+	fn mutate(source: &T, mutations: &MutationTable, factor: f32, rng: &mut ChaCha8Rng) -> (T, Option<usize>) {
+		if mutations.names.is_empty() {
+			return (T::new_mutated(source, factor, rng), None);
+		}
+		let operator = mutations.choose_biased(|i| T::mutation_bias(i, factor), rng);
+		let (agent, operator) = T::new_mutated_by(source, operator, factor, rng);
+		(agent, Some(operator))
+	}
+
+	// every evaluated offspring counts as a success for its operator if it beat its parent. Ranks and novelty
+	// depend on the generation, so it's compared by its fitness, or in multi-objective mode by dominating the parent
+	fn score_mutations(&mut self) {
+		let novelty = self.novelty_weight > 0.0;
+		let outcomes: Vec<(usize, bool)> = std::mem::take(&mut self.offspring).into_iter()
+			.map(|(i, operator, parent_score, parent_objectives)| {
+				let improved = if self.multi_objective {
+					// without the novelty, which is always the last objective
+					let objectives = &self.objectives[i][..self.objectives[i].len() - novelty as usize];
+					parent_objectives.is_empty() || pareto::dominates(objectives, &parent_objectives)
+				}
+				else {
+					self.scores[i] > parent_score
+				};
+				(operator, improved)
+			})
+			.collect();
+		self.mutations.record(&outcomes);
+	}
+
 	fn select(&mut self) {
-		// in-place tournament selection
+		let fitnesses: Vec<f32> = self.agents.iter().map(|e| e.1).collect();
+		let selected = self.selection.select(&fitnesses, self.survivors_count, &mut self.rng);
 
-		let mut tournament = Vec::with_capacity(self.tournament_size);
-		for i in 1..self.survivors_count {
-			tournament.clear();
-			for _ in 0..self.tournament_size {
-				tournament.push(self.rng.gen_range(i..self.agents.len()));
-			}
+		let mut order: Vec<usize> = (0..self.agents.len()).collect();
+		order.sort_by(|&a, &b| fitnesses[b].total_cmp(&fitnesses[a]));
 
-			let mut max_index = 0;
-			let mut max_fitness = -f32::MAX;
-			for j in 0..self.tournament_size {
-				let contender = &self.agents[tournament[j]];
-				if contender.1 > max_fitness {
-					max_index = j;
-					max_fitness = contender.1;
-				}
+		// elites first, then every selected agent once
+		let mut new_index = vec![usize::MAX; self.agents.len()];
+		let mut kept = Vec::new();
+		for &i in order.iter().take(self.elite_count).chain(&selected) {
+			if new_index[i] == usize::MAX {
+				new_index[i] = kept.len();
+				kept.push(i);
 			}
+		}
+
+		self.parents = selected.iter().map(|&i| new_index[i]).collect();
 
-			self.agents.swap(i, tournament[max_index]);
+		// the parents' scores are kept to tell whether their offspring improved on them
+		self.scores = kept.iter().map(|&i| self.scores.get(i).copied().unwrap_or(-f32::MAX)).collect();
+		if !self.objectives.is_empty() {
+			self.objectives = kept.iter().map(|&i| self.objectives.get(i).cloned().unwrap_or_default()).collect();
 		}
-		
-		self.agents.truncate(self.survivors_count);
+
+		let mut agents: Vec<Option<(T, f32)>> = self.agents.drain(..).map(Some).collect();
+		self.agents = kept.iter().map(|&i| agents[i].take().unwrap()).collect();
+		self.ids = kept.iter().map(|&i| self.ids[i]).collect();
 	}
 
 	// when the generation size is increased between the generations, new random agents need to be spawned
@@ -80,27 +339,119 @@ impl<T, U> GeneticAlgorithm<T, U> where T: Evolve<U> {
 		while self.agents.len() < self.generation_count {
 			let agent = T::new_random(&mut self.rng);
 			self.agents.push((agent, -f32::MAX));
+			self.ids.push(self.genealogy.born(Vec::new(), self.generation_number + 1, "random"));
 		}
 	}
 
 	pub fn perform_generation(&mut self) {
+		let start = Instant::now();
+
 		self.refill();
 		self.select();
 		self.agents.iter_mut().for_each(|e| e.0.reset());
 		self.reproduce();
-		self.calculate_fitnesses();
-		// sorting is only for presentation
-		self.agents.sort_unstable_by(|e1, e2| e2.1.total_cmp(&e1.1)); 
+		self.calculate_fitnesses(0);
+		self.score_mutations();
+		self.sort();
 
 		self.generation_number += 1;
+		self.record_statistics(start.elapsed().as_secs_f32());
+	}
+
+	fn record_statistics(&mut self, duration: f32) {
+		let mut fitnesses: Vec<f32> = self.agents.iter().map(|e| e.1).collect();
+		fitnesses.sort_by(|a, b| b.total_cmp(a));
+
+		let n = fitnesses.len().max(1) as f32;
+		let mean = fitnesses.iter().sum::<f32>() / n;
+		let variance = fitnesses.iter().map(|f| (f - mean) * (f - mean)).sum::<f32>() / n;
+
+		// pairs half a generation apart, so the measure doesn't depend on the order
+		let half = self.agents.len() / 2;
+		let diversity = (0..half).map(|i| self.agents[i].0.genotype_distance(&self.agents[i + half].0)).sum::<f32>() / half.max(1) as f32;
+
+		let mut rule_counts = Vec::new();
+		for (agent, _) in &self.agents {
+			let count = agent.rule_count();
+			if rule_counts.len() <= count {
+				rule_counts.resize(count + 1, 0);
+			}
+			rule_counts[count] += 1;
+		}
+
+		let elapsed = self.history.last().map_or(0.0, |s| s.elapsed) + duration;
+		self.history.push(GenerationStats {
+			generation: self.generation_number,
+			best: fitnesses.first().copied().unwrap_or(0.0),
+			mean,
+			median: fitnesses.get(fitnesses.len() / 2).copied().unwrap_or(0.0),
+			worst: fitnesses.last().copied().unwrap_or(0.0),
+			std_dev: variance.sqrt(),
+			diversity,
+			rule_counts,
+			duration,
+			elapsed,
+		});
 	}
 
 	pub fn reset(&mut self) {
+		let start = Instant::now();
 		self.generation_number = 0;
-		for agent in &mut self.agents {
+		self.history.clear();
+		self.novelty_archive.clear();
+		self.fitness_cache.reset_hit_rate();
+		self.mutations.reset_stats();
+		self.genealogy.clear();
+		for (agent, id) in self.agents.iter_mut().zip(&mut self.ids) {
 			*agent = (T::new_random(&mut self.rng), 0.0);
+			*id = self.genealogy.born(Vec::new(), 0, "random");
+		}
+		self.calculate_fitnesses(0);
+		self.sort();
+		self.record_statistics(start.elapsed().as_secs_f32());
+	}
+
+	// the agents, migrants or hand-made genomes, replace the worst ones. Only they are evaluated,
+	// unless the others were scored with different settings
+	pub fn inject(&mut self, agents: Vec<T>) {
+		let count = agents.len().min(self.agents.len());
+		let up_to_date = self.scored == Some([self.multi_objective, self.novelty_weight > 0.0]) && self.scores.len() == self.agents.len();
+		self.agents.truncate(self.agents.len() - count);
+		self.ids.truncate(self.agents.len());
+		let from = if up_to_date { self.agents.len() } else { 0 };
+		self.agents.extend(agents.into_iter().take(count).map(|agent| (agent, 0.0)));
+		for _ in 0..count {
+			self.ids.push(self.genealogy.born(Vec::new(), self.generation_number, "injected"));
+		}
+
+		self.agents[from..].iter_mut().for_each(|e| e.0.reset());
+		self.calculate_fitnesses(from);
+		self.sort();
+	}
+
+	// starts over from a generation of the seed and its mutants
+	pub fn reset_from_seed(&mut self, seed: &T) {
+		let start = Instant::now();
+		self.generation_number = 0;
+		self.history.clear();
+		self.novelty_archive.clear();
+		self.fitness_cache.reset_hit_rate();
+		self.mutations.reset_stats();
+		self.genealogy.clear();
+
+		let seed_id = self.genealogy.born(Vec::new(), 0, "seed");
+		self.agents = vec![(seed.clone(), 0.0)];
+		self.ids = vec![seed_id];
+		while self.agents.len() < self.generation_count {
+			let (agent, operator) = Self::mutate(seed, &self.mutations, self.mutation_factor, &mut self.rng);
+			self.agents.push((agent, 0.0));
+			self.ids.push(self.genealogy.born(vec![seed_id], 0, operator.map_or("mutation", |o| self.mutations.names[o])));
 		}
-		self.calculate_fitnesses();
+		self.agents.iter_mut().for_each(|e| e.0.reset());
+
+		self.calculate_fitnesses(0);
+		self.sort();
+		self.record_statistics(start.elapsed().as_secs_f32());
 	}
 
 	pub fn perform_generations(&mut self, n: u32) {
@@ -109,8 +460,67 @@ impl<T, U> GeneticAlgorithm<T, U> where T: Evolve<U> {
 		}
 	}
 
+	// the first of the conditions reached, `elapsed` is the time the run has taken so far
+	pub fn stop_reason(&self, conditions: &[StopCondition], elapsed: Duration) -> Option<StopCondition> {
+		conditions.iter().copied().find(|c| c.reached(&self.history, self.generation_number, elapsed))
+	}
+
+	// evolves until any of the conditions is reached, returns at once without conditions
+	pub fn run_until(&mut self, conditions: &[StopCondition]) -> RunSummary {
+		let start = Instant::now();
+		let first_generation = self.generation_number;
+
+		let stopped_by = if conditions.is_empty() { None } else {
+			loop {
+				if let Some(reason) = self.stop_reason(conditions, start.elapsed()) {
+					break Some(reason);
+				}
+				self.perform_generation();
+			}
+		};
+
+		RunSummary {
+			stopped_by,
+			generations: self.generation_number - first_generation,
+			generation_number: self.generation_number,
+			best_fitness: self.best().1,
+			duration: start.elapsed(),
+		}
+	}
+
+	pub fn settings(&self) -> Settings {
+		Settings {
+			mutation_factor: self.mutation_factor,
+			crossover_rate: self.crossover_rate,
+			generation_count: self.generation_count,
+			survivors_count: self.survivors_count,
+			selection: self.selection,
+			elite_count: self.elite_count,
+			parallel: self.parallel,
+			multi_objective: self.multi_objective,
+			novelty_weight: self.novelty_weight,
+			novelty_neighbours: self.novelty_neighbours,
+		}
+	}
+
+	// takes effect from the next generation, like setting the fields one by one
+	pub fn apply_settings(&mut self, settings: Settings) {
+		self.mutation_factor = settings.mutation_factor;
+		self.crossover_rate = settings.crossover_rate;
+		self.generation_count = settings.generation_count;
+		self.survivors_count = settings.survivors_count;
+		self.selection = settings.selection;
+		self.elite_count = settings.elite_count;
+		self.parallel = settings.parallel;
+		self.multi_objective = settings.multi_objective;
+		self.novelty_weight = settings.novelty_weight;
+		self.novelty_neighbours = settings.novelty_neighbours;
+	}
+
+	// changing the params clears the fitness cache
 	pub fn set_params(&mut self, params: U) {
 		self.params = params;
+		self.fitness_cache.clear();
 	}
 
 	pub fn params(&self) -> &U {
@@ -118,6 +528,8 @@ impl<T, U> GeneticAlgorithm<T, U> where T: Evolve<U> {
 	}
 
 	pub fn params_mut(&mut self) -> &mut U {
+		self.fitness_cache.clear();
+		self.scored = None;
 		&mut self.params
 	}
 
@@ -137,7 +549,55 @@ impl<T, U> GeneticAlgorithm<T, U> where T: Evolve<U> {
 		&self.agents
 	}
 
+	// objectives of every agent in the order of `agents`, empty unless in multi-objective mode
+	pub fn objectives(&self) -> &[Vec<f32>] {
+		&self.objectives
+	}
+
+	// novelty of every agent in the order of `agents`, empty unless novelty is rewarded
+	pub fn novelty(&self) -> &[f32] {
+		&self.novelty
+	}
+
+	// indices of the agents no other agent dominates
+	pub fn pareto_front(&self) -> Vec<usize> {
+		if self.objectives.is_empty() { return Vec::new(); }
+		(0..self.agents.len()).filter(|&i| self.agents[i].1 >= 0.0).collect()
+	}
+
+	// ids of the agents in the order of `agents`
+	pub fn ids(&self) -> &[u64] {
+		&self.ids
+	}
+
+	pub fn genealogy(&self) -> &Genealogy<T> {
+		&self.genealogy
+	}
+
+	// statistics of every generation since the start or the last reset
+	pub fn history(&self) -> &[GenerationStats] {
+		&self.history
+	}
+
 	pub fn generation_number(&self) -> u32 {
 		self.generation_number
 	}
-} 
\ No newline at end of file
+} 
+
+// only the agents marked pending are evaluated
+fn evaluate_all<T, V, F>(agents: &mut [(T, f32)], pending: &[bool], parallel: bool, evaluate: F) -> Vec<Option<V>> where T: Send, V: Send, F: Fn(&mut T) -> V + Sync {
+	#[cfg(feature = "parallel")]
+	if parallel {
+		use rayon::prelude::*;
+		return agents.par_iter_mut().zip(pending).map(|((agent, _), &pending)| pending.then(|| evaluate(agent))).collect();
+	}
+	let _ = parallel;
+
+	agents.iter_mut().zip(pending).map(|((agent, _), &pending)| pending.then(|| evaluate(agent))).collect()
+}
+
+fn normalized(values: &[f32]) -> Vec<f32> {
+	let min = values.iter().copied().fold(f32::MAX, f32::min);
+	let max = values.iter().copied().fold(-f32::MAX, f32::max);
+	values.iter().map(|v| if max > min { (v - min) / (max - min) } else { 0.0 }).collect()
+}
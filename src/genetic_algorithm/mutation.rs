@@ -0,0 +1,112 @@
+use rand::Rng;
+
+// share of the weight every operator keeps when adapting, so none dies out
+const MIN_SHARE: f32 = 0.02;
+// how fast the quality estimates follow the latest generation
+const ADAPTATION_RATE: f32 = 0.3;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct OperatorStats {
+	pub uses: u64,
+	pub improvements: u64, // offspring fitter than their parent
+}
+
+impl OperatorStats {
+	pub fn success_rate(&self) -> f32 {
+		self.improvements as f32 / self.uses.max(1) as f32
+	}
+}
+
+// how likely each mutation operator of `Evolve::mutations` is picked, and how well it did
+#[derive(Clone, Debug, PartialEq)]
+pub struct MutationTable {
+	pub names: Vec<&'static str>,
+	pub weights: Vec<f32>,
+	pub adaptive: bool, // probability matching: weights follow the recent success rates
+	pub(super) stats: Vec<OperatorStats>,
+	pub(super) quality: Vec<f32>, // recent success rate estimates for the adaptation
+}
+
+impl MutationTable {
+	pub fn new(mutations: Vec<(&'static str, f32)>) -> Self {
+		let count = mutations.len();
+		let (names, weights) = mutations.into_iter().unzip();
+		Self {
+			names,
+			weights,
+			adaptive: false,
+			stats: vec![OperatorStats::default(); count],
+			quality: vec![0.0; count],
+		}
+	}
+
+	pub fn choose<R: Rng>(&self, rng: &mut R) -> usize {
+		choose_weighted(&self.weights, rng)
+	}
+
+	// the same with every weight multiplied by the operator's bias
+	pub fn choose_biased<R: Rng>(&self, bias: impl Fn(usize) -> f32, rng: &mut R) -> usize {
+		let weights: Vec<f32> = self.weights.iter().enumerate().map(|(i, weight)| weight * bias(i)).collect();
+		choose_weighted(&weights, rng)
+	}
+
+	// outcomes of one generation's offspring as operator indices and whether they beat their parent
+	pub(super) fn record(&mut self, outcomes: &[(usize, bool)]) {
+		let mut generation = vec![OperatorStats::default(); self.names.len()];
+		for &(operator, improved) in outcomes {
+			generation[operator].uses += 1;
+			generation[operator].improvements += improved as u64;
+		}
+
+		for (total, stats) in self.stats.iter_mut().zip(&generation) {
+			total.uses += stats.uses;
+			total.improvements += stats.improvements;
+		}
+
+		if self.adaptive {
+			self.adapt(&generation);
+		}
+	}
+
+	fn adapt(&mut self, generation: &[OperatorStats]) {
+		for (quality, stats) in self.quality.iter_mut().zip(generation) {
+			if stats.uses > 0 {
+				*quality += ADAPTATION_RATE * (stats.success_rate() - *quality);
+			}
+		}
+
+		let total_quality: f32 = self.quality.iter().sum();
+		if total_quality <= 0.0 { return; }
+
+		// the total weight stays the same, so the table stays readable
+		let total_weight: f32 = self.weights.iter().sum();
+		let share = 1.0 - MIN_SHARE * self.weights.len() as f32;
+		for (weight, quality) in self.weights.iter_mut().zip(&self.quality) {
+			*weight = total_weight * (MIN_SHARE + share * quality / total_quality);
+		}
+	}
+
+	pub fn stats(&self) -> &[OperatorStats] {
+		&self.stats
+	}
+
+	pub fn reset_stats(&mut self) {
+		self.stats.fill(OperatorStats::default());
+		self.quality.fill(0.0);
+	}
+}
+
+// index picked with a chance proportional to its weight, uniformly when no weight is positive
+pub fn choose_weighted<R: Rng>(weights: &[f32], rng: &mut R) -> usize {
+	let total: f32 = weights.iter().map(|w| w.max(0.0)).sum();
+	if total <= 0.0 || !total.is_finite() {
+		return rng.gen_range(0..weights.len());
+	}
+
+	let mut pick = rng.gen_range(0.0..total);
+	for (i, weight) in weights.iter().enumerate() {
+		pick -= weight.max(0.0);
+		if pick < 0.0 { return i; }
+	}
+	weights.len() - 1
+}
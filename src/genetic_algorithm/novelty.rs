@@ -0,0 +1,80 @@
+use std::collections::VecDeque;
+
+pub fn distance(a: &[f32], b: &[f32]) -> f32 {
+	a.iter().zip(b).map(|(a, b)| (a - b) * (a - b)).sum::<f32>().sqrt()
+}
+
+// behaviours of past agents novelty is measured against, the oldest are forgotten first
+#[derive(Clone, Debug)]
+pub struct NoveltyArchive {
+	pub(super) entries: VecDeque<Vec<f32>>,
+	pub limit: usize,
+	pub additions: usize, // most novel agents archived per generation
+}
+
+impl Default for NoveltyArchive {
+	fn default() -> Self {
+		Self {
+			entries: VecDeque::new(),
+			limit: 1000,
+			additions: 5,
+		}
+	}
+}
+
+impl NoveltyArchive {
+	// mean distance of every behaviour to its `k` nearest neighbours among the others and the archive
+	pub fn score(&self, behaviours: &[Vec<f32>], k: usize, parallel: bool) -> Vec<f32> {
+		self.score_from(behaviours, 0, k, parallel)
+	}
+
+	// the same for the behaviours from `from` on, the earlier ones are only neighbours
+	pub fn score_from(&self, behaviours: &[Vec<f32>], from: usize, k: usize, parallel: bool) -> Vec<f32> {
+		let sparseness = |i: usize| {
+			let mut distances: Vec<f32> = behaviours.iter().enumerate()
+				.filter(|&(j, _)| j != i)
+				.map(|(_, b)| b)
+				.chain(&self.entries)
+				.map(|b| distance(&behaviours[i], b))
+				.collect();
+
+			let k = k.clamp(1, distances.len().max(1));
+			if distances.is_empty() { return 0.0; }
+			distances.select_nth_unstable_by(k - 1, f32::total_cmp);
+			distances[..k].iter().sum::<f32>() / k as f32
+		};
+
+		#[cfg(feature = "parallel")]
+		if parallel {
+			use rayon::prelude::*;
+			return (from..behaviours.len()).into_par_iter().map(sparseness).collect();
+		}
+		let _ = parallel;
+
+		(from..behaviours.len()).map(sparseness).collect()
+	}
+
+	pub fn extend(&mut self, behaviours: &[Vec<f32>], novelty: &[f32]) {
+		let mut order: Vec<usize> = (0..behaviours.len()).collect();
+		order.sort_by(|&a, &b| novelty[b].total_cmp(&novelty[a]));
+
+		for &i in order.iter().take(self.additions) {
+			self.entries.push_back(behaviours[i].clone());
+		}
+		while self.entries.len() > self.limit {
+			self.entries.pop_front();
+		}
+	}
+
+	pub fn clear(&mut self) {
+		self.entries.clear();
+	}
+
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+}
@@ -0,0 +1,89 @@
+// non-dominated sorting and crowding distances as in NSGA-II. Every objective is maximized
+
+// a is at least as good as b in every objective and better in one
+pub fn dominates(a: &[f32], b: &[f32]) -> bool {
+	let mut better = false;
+	for (a, b) in a.iter().zip(b) {
+		if a < b { return false; }
+		if a > b { better = true; }
+	}
+	better
+}
+
+// indices grouped into fronts, the first front is not dominated by anything
+pub fn non_dominated_sort(objectives: &[Vec<f32>]) -> Vec<Vec<usize>> {
+	let n = objectives.len();
+	let mut dominated_by = vec![0usize; n];
+	let mut dominating: Vec<Vec<usize>> = vec![Vec::new(); n];
+
+	for i in 0..n {
+		for j in i + 1..n {
+			if dominates(&objectives[i], &objectives[j]) {
+				dominating[i].push(j);
+				dominated_by[j] += 1;
+			}
+			else if dominates(&objectives[j], &objectives[i]) {
+				dominating[j].push(i);
+				dominated_by[i] += 1;
+			}
+		}
+	}
+
+	let mut fronts = Vec::new();
+	let mut front: Vec<usize> = (0..n).filter(|&i| dominated_by[i] == 0).collect();
+	while !front.is_empty() {
+		let mut next = Vec::new();
+		for &i in &front {
+			for &j in &dominating[i] {
+				dominated_by[j] -= 1;
+				if dominated_by[j] == 0 {
+					next.push(j);
+				}
+			}
+		}
+		fronts.push(front);
+		front = next;
+	}
+
+	fronts
+}
+
+// crowding distance of every member of the front, in the same order. The extremes are infinitely far
+pub fn crowding_distances(objectives: &[Vec<f32>], front: &[usize]) -> Vec<f32> {
+	let mut distances = vec![0.0; front.len()];
+	let Some(first) = front.first() else { return distances; };
+
+	let dimensions = objectives[*first].len();
+	for values in (0..dimensions).map(|m| front.iter().map(|&i| objectives[i][m]).collect::<Vec<f32>>()) {
+		let mut order: Vec<usize> = (0..front.len()).collect();
+		order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+
+		let (lowest, highest) = (order[0], *order.last().unwrap());
+		let (min, max) = (values[lowest], values[highest]);
+
+		distances[lowest] = f32::INFINITY;
+		distances[highest] = f32::INFINITY;
+		if max - min <= 0.0 { continue; }
+
+		for k in 1..order.len().saturating_sub(1) {
+			distances[order[k]] += (values[order[k + 1]] - values[order[k - 1]]) / (max - min);
+		}
+	}
+
+	distances
+}
+
+// scalar that orders agents like the crowded comparison: -front index, plus less than 1 for the crowding distance.
+// agents of the first front are exactly the ones with a non-negative value
+pub fn crowded_fitnesses(objectives: &[Vec<f32>]) -> Vec<f32> {
+	let mut fitnesses = vec![0.0; objectives.len()];
+
+	for (rank, front) in non_dominated_sort(objectives).iter().enumerate() {
+		for (&i, distance) in front.iter().zip(crowding_distances(objectives, front)) {
+			let crowding = if distance.is_finite() { distance / (1.0 + distance) * 0.99 } else { 0.99 };
+			fitnesses[i] = crowding - rank as f32;
+		}
+	}
+
+	fitnesses
+}
@@ -0,0 +1,131 @@
+use rand::Rng;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Selection {
+	// the fittest of `size` random contenders wins. Without replacement, winners leave the pool
+	Tournament { size: usize, replacement: bool },
+	// chance proportional to fitness above the worst agent
+	Roulette,
+	// chance proportional to the position in the ranking, the worst agent has weight 1
+	Rank,
+	// the fittest agents, each once
+	Truncation,
+	// chance proportional to exp((fitness - best) / temperature)
+	Boltzmann { temperature: f32 },
+}
+
+impl Default for Selection {
+	fn default() -> Self {
+		Selection::Tournament { size: 2, replacement: false }
+	}
+}
+
+impl Selection {
+	pub const NAMES: [&'static str; 5] = ["Tournament", "Roulette", "Rank", "Truncation", "Boltzmann"];
+
+	pub fn name(&self) -> &'static str {
+		match self {
+			Selection::Tournament { .. } => Self::NAMES[0],
+			Selection::Roulette => Self::NAMES[1],
+			Selection::Rank => Self::NAMES[2],
+			Selection::Truncation => Self::NAMES[3],
+			Selection::Boltzmann { .. } => Self::NAMES[4],
+		}
+	}
+
+	// strategy of the given name with default parameters
+	pub fn from_name(name: &str) -> Option<Self> {
+		match name {
+			"Tournament" => Some(Selection::default()),
+			"Roulette" => Some(Selection::Roulette),
+			"Rank" => Some(Selection::Rank),
+			"Truncation" => Some(Selection::Truncation),
+			"Boltzmann" => Some(Selection::Boltzmann { temperature: 1.0 }),
+			_ => None,
+		}
+	}
+
+	// indices of `count` selected agents. They can repeat, except for truncation and tournaments without replacement
+	pub fn select<R: Rng>(&self, fitnesses: &[f32], count: usize, rng: &mut R) -> Vec<usize> {
+		if fitnesses.is_empty() { return Vec::new(); }
+
+		match *self {
+			Selection::Tournament { size, replacement } => tournament(fitnesses, count, size.max(1), replacement, rng),
+			Selection::Roulette => {
+				let worst = fitnesses.iter().copied().fold(f32::MAX, f32::min);
+				let weights: Vec<f64> = fitnesses.iter().map(|f| (f - worst) as f64).collect();
+				weighted(&weights, count, rng)
+			},
+			Selection::Rank => {
+				let order = by_fitness(fitnesses);
+				let mut weights = vec![0.0; fitnesses.len()];
+				for (rank, &i) in order.iter().enumerate() {
+					weights[i] = (fitnesses.len() - rank) as f64;
+				}
+				weighted(&weights, count, rng)
+			},
+			Selection::Truncation => {
+				let mut order = by_fitness(fitnesses);
+				order.truncate(count);
+				order
+			},
+			Selection::Boltzmann { temperature } => {
+				let best = fitnesses.iter().copied().fold(-f32::MAX, f32::max);
+				let temperature = temperature.max(f32::EPSILON) as f64;
+				let weights: Vec<f64> = fitnesses.iter().map(|&f| ((f - best) as f64 / temperature).exp()).collect();
+				weighted(&weights, count, rng)
+			},
+		}
+	}
+}
+
+// indices sorted from the fittest
+fn by_fitness(fitnesses: &[f32]) -> Vec<usize> {
+	let mut order: Vec<usize> = (0..fitnesses.len()).collect();
+	order.sort_by(|&a, &b| fitnesses[b].total_cmp(&fitnesses[a]));
+	order
+}
+
+fn tournament<R: Rng>(fitnesses: &[f32], count: usize, size: usize, replacement: bool, rng: &mut R) -> Vec<usize> {
+	let mut pool: Vec<usize> = (0..fitnesses.len()).collect();
+	let mut selected = Vec::with_capacity(count);
+
+	for _ in 0..count {
+		if pool.is_empty() { break; }
+
+		let mut winner = rng.gen_range(0..pool.len());
+		for _ in 1..size {
+			let contender = rng.gen_range(0..pool.len());
+			if fitnesses[pool[contender]] > fitnesses[pool[winner]] {
+				winner = contender;
+			}
+		}
+
+		selected.push(pool[winner]);
+		if !replacement {
+			pool.swap_remove(winner);
+		}
+	}
+
+	selected
+}
+
+// sampling with replacement, uniform when all weights are zero
+fn weighted<R: Rng>(weights: &[f64], count: usize, rng: &mut R) -> Vec<usize> {
+	let mut cumulative = Vec::with_capacity(weights.len());
+	let mut total = 0.0;
+	for w in weights {
+		total += w.max(0.0);
+		cumulative.push(total);
+	}
+
+	if total <= 0.0 || !total.is_finite() {
+		return (0..count).map(|_| rng.gen_range(0..weights.len())).collect();
+	}
+
+	(0..count).map(|_| {
+		let x = rng.gen_range(0.0..total);
+		cumulative.partition_point(|&c| c <= x).min(weights.len() - 1)
+	}).collect()
+}
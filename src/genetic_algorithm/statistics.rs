@@ -0,0 +1,51 @@
+use std::{fs::File, io::{BufWriter, Write}, path::Path};
+
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GenerationStats {
+	pub generation: u32,
+	pub best: f32,
+	pub mean: f32,
+	pub median: f32,
+	pub worst: f32,
+	pub std_dev: f32,
+	pub diversity: f32, // mean genotype distance between pairs of agents
+	pub rule_counts: Vec<u32>, // how many agents have as many rules as the index
+	pub duration: f32, // seconds spent on the generation
+	pub elapsed: f32, // seconds spent on all generations so far
+}
+
+impl GenerationStats {
+	pub fn mean_rule_count(&self) -> f32 {
+		let agents: u32 = self.rule_counts.iter().sum();
+		let rules: u32 = self.rule_counts.iter().enumerate().map(|(count, &n)| count as u32 * n).sum();
+		rules as f32 / agents.max(1) as f32
+	}
+}
+
+pub const CSV_HEADER: &str = "generation,best,mean,median,worst,std_dev,diversity,mean_rules,rule_counts,duration,elapsed";
+
+// the rule count distribution is written as space separated `rules:agents` pairs
+pub fn write_csv<W: Write>(history: &[GenerationStats], mut writer: W) -> Result<(), String> {
+	writeln!(writer, "{CSV_HEADER}").map_err(|e| e.to_string())?;
+
+	for stats in history {
+		let rule_counts: Vec<String> = stats.rule_counts.iter().enumerate()
+			.filter(|&(_, &n)| n > 0)
+			.map(|(count, n)| format!("{count}:{n}"))
+			.collect();
+
+		writeln!(
+			writer, "{},{},{},{},{},{},{},{},{},{},{}",
+			stats.generation, stats.best, stats.mean, stats.median, stats.worst, stats.std_dev, stats.diversity,
+			stats.mean_rule_count(), rule_counts.join(" "), stats.duration, stats.elapsed,
+		).map_err(|e| e.to_string())?;
+	}
+
+	writer.flush().map_err(|e| e.to_string())
+}
+
+pub fn save_csv<P: AsRef<Path>>(history: &[GenerationStats], path: P) -> Result<(), String> {
+	let file = File::create(path).map_err(|e| e.to_string())?;
+	write_csv(history, BufWriter::new(file))
+}
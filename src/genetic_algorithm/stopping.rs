@@ -0,0 +1,64 @@
+use std::{fmt::Display, time::Duration};
+
+use super::statistics::GenerationStats;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StopCondition {
+	// the best fitness reaches the value
+	TargetFitness(f32),
+	// the best fitness hasn't improved for this many generations
+	Stagnation(u32),
+	// wall-clock time of the run
+	TimeLimit(Duration),
+	// the generation number reaches the value
+	GenerationCap(u32),
+	// the mean genotype distance between agents falls to the value
+	DiversityCollapse(f32),
+}
+
+impl StopCondition {
+	pub fn reached(&self, history: &[GenerationStats], generation: u32, elapsed: Duration) -> bool {
+		match *self {
+			StopCondition::TargetFitness(target) => history.last().is_some_and(|s| s.best >= target),
+			StopCondition::Stagnation(generations) => !history.is_empty() && generations_without_improvement(history) >= generations,
+			StopCondition::TimeLimit(limit) => elapsed >= limit,
+			StopCondition::GenerationCap(cap) => generation >= cap,
+			StopCondition::DiversityCollapse(min) => history.last().is_some_and(|s| s.diversity <= min),
+		}
+	}
+}
+
+impl Display for StopCondition {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			StopCondition::TargetFitness(target) => write!(f, "target fitness {target} reached"),
+			StopCondition::Stagnation(generations) => write!(f, "no improvement for {generations} generations"),
+			StopCondition::TimeLimit(limit) => write!(f, "time limit of {:.1}s reached", limit.as_secs_f32()),
+			StopCondition::GenerationCap(cap) => write!(f, "generation {cap} reached"),
+			StopCondition::DiversityCollapse(min) => write!(f, "diversity fell to {min}"),
+		}
+	}
+}
+
+// generations since the best fitness last rose above every earlier one
+pub fn generations_without_improvement(history: &[GenerationStats]) -> u32 {
+	let mut best = f32::NEG_INFINITY;
+	let mut improved = 0;
+	for (i, stats) in history.iter().enumerate() {
+		if stats.best > best {
+			best = stats.best;
+			improved = i;
+		}
+	}
+	history.len().saturating_sub(improved + 1) as u32
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RunSummary {
+	pub stopped_by: Option<StopCondition>, // none when no condition was given
+	pub generations: u32, // performed by the run
+	pub generation_number: u32,
+	pub best_fitness: f32,
+	pub duration: Duration,
+}
@@ -0,0 +1,326 @@
+use crate::l_system::{grid::Grid, cell::{Cell, Direction}, is_valid, is_valid_for};
+
+// every container file starts with these bytes, headerless (version 0) files start with a grid width instead
+pub const MAGIC: [u8; 4] = *b"GLS\x1A";
+pub const FORMAT_VERSION: u16 = 1;
+
+const SECTION_RULES: [u8; 4] = *b"RULE";
+const SECTION_AXIOM: [u8; 4] = *b"AXIM";
+const SECTION_GOAL: [u8; 4] = *b"GOAL";
+const SECTION_GROWTH: [u8; 4] = *b"GROW";
+const SECTION_EVOLUTION: [u8; 4] = *b"EVOL";
+const SECTION_METADATA: [u8; 4] = *b"META";
+
+// bytes of a serialized grid before its cells
+pub(crate) const GRID_HEADER: usize = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GrowthSettings {
+	pub max_steps: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EvolutionSettings {
+	pub generation_count: u32,
+	pub survivors_count: u32,
+	pub tournament_size: u32,
+	pub mutation_factor: f32,
+
+	pub same_weight: f32,
+	pub different_weight: f32,
+	pub size_weight: f32,
+	pub size_pow: f32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Metadata {
+	pub name: String,
+	pub notes: String,
+	pub created: u64, // seconds since the unix epoch, 0 if unknown
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GlsFile {
+	pub version: u16, // version the file was read as, FORMAT_VERSION for new files
+	pub rules: Vec<Grid>,
+	pub axiom: Grid,
+	pub goal: Option<Grid>,
+	pub growth: Option<GrowthSettings>,
+	pub evolution: Option<EvolutionSettings>,
+	pub metadata: Metadata,
+}
+
+impl GlsFile {
+	pub fn new(rules: Vec<Grid>) -> Self {
+		Self {
+			version: FORMAT_VERSION,
+			rules,
+			axiom: Grid::single(Cell::Stem(0, Direction::UP)),
+			goal: None,
+			growth: None,
+			evolution: None,
+			metadata: Metadata::default(),
+		}
+	}
+
+	pub fn serialize(&self) -> Vec<u8> {
+		let mut data = Vec::new();
+
+		data.extend_from_slice(&MAGIC);
+		data.extend_from_slice(&FORMAT_VERSION.to_be_bytes());
+
+		let mut rules = Vec::new();
+		rules.extend_from_slice(&(self.rules.len() as u32).to_be_bytes());
+		for rule in &self.rules {
+			rules.extend(rule.serialize());
+		}
+		write_section(&mut data, SECTION_RULES, &rules);
+
+		write_section(&mut data, SECTION_AXIOM, &self.axiom.serialize());
+
+		if let Some(goal) = &self.goal {
+			write_section(&mut data, SECTION_GOAL, &goal.serialize());
+		}
+
+		if let Some(growth) = &self.growth {
+			write_section(&mut data, SECTION_GROWTH, &growth.max_steps.to_be_bytes());
+		}
+
+		if let Some(evolution) = &self.evolution {
+			let mut section = Vec::new();
+			section.extend_from_slice(&evolution.generation_count.to_be_bytes());
+			section.extend_from_slice(&evolution.survivors_count.to_be_bytes());
+			section.extend_from_slice(&evolution.tournament_size.to_be_bytes());
+			section.extend_from_slice(&evolution.mutation_factor.to_be_bytes());
+			section.extend_from_slice(&evolution.same_weight.to_be_bytes());
+			section.extend_from_slice(&evolution.different_weight.to_be_bytes());
+			section.extend_from_slice(&evolution.size_weight.to_be_bytes());
+			section.extend_from_slice(&evolution.size_pow.to_be_bytes());
+			write_section(&mut data, SECTION_EVOLUTION, &section);
+		}
+
+		let mut metadata = Vec::new();
+		write_string(&mut metadata, &self.metadata.name);
+		write_string(&mut metadata, &self.metadata.notes);
+		metadata.extend_from_slice(&self.metadata.created.to_be_bytes());
+		write_section(&mut data, SECTION_METADATA, &metadata);
+
+		data
+	}
+
+	pub fn deserialize(data: &[u8]) -> Result<Self, String> {
+		let file = if data.starts_with(&MAGIC) {
+			let mut reader = Reader::new(&data[MAGIC.len()..]);
+			let version = reader.u16()?;
+
+			match version {
+				1 => Self::deserialize_v1(reader)?,
+				_ => return Err(format!("Unsupported file version {version}")),
+			}
+		} else {
+			Self::deserialize_headerless(data)?
+		};
+
+		file.validate()?;
+		Ok(file)
+	}
+
+	// what growing the rules relies on: there is a rule and every stem, in the rules and the axiom, refers to one
+	pub fn validate(&self) -> Result<(), String> {
+		if self.rules.is_empty() {
+			return Err("File contains no rules".into());
+		}
+		if !is_valid(&self.rules) || !is_valid_for(&self.axiom, self.rules.len()) {
+			return Err("Stems refer to missing rules".into());
+		}
+		Ok(())
+	}
+
+	// version 0: bare concatenation of serialized rules, as written before the container format existed
+	fn deserialize_headerless(data: &[u8]) -> Result<Self, String> {
+		let mut rules = Vec::new();
+
+		let mut cursor = 0;
+		while cursor < data.len() {
+			let (grid, c) = Grid::deserialize(&data[cursor..]).map_err(|_| String::from("Invalid file"))?;
+			rules.push(grid);
+			cursor += c;
+		}
+
+		let mut file = Self::new(rules);
+		file.version = 0;
+		Ok(file)
+	}
+
+	fn deserialize_v1(mut reader: Reader) -> Result<Self, String> {
+		let mut file = Self::new(Vec::new());
+		file.version = 1;
+
+		while !reader.is_empty() {
+			let tag = reader.bytes(4)?;
+			let length = reader.u32()? as usize;
+			let mut section = Reader::new(reader.bytes(length)?);
+
+			match [tag[0], tag[1], tag[2], tag[3]] {
+				SECTION_RULES => {
+					let count = section.count(GRID_HEADER)?;
+					for _ in 0..count {
+						file.rules.push(section.grid()?);
+					}
+				},
+				SECTION_AXIOM => file.axiom = section.grid()?,
+				SECTION_GOAL => file.goal = Some(section.grid()?),
+				SECTION_GROWTH => file.growth = Some(GrowthSettings {
+					max_steps: section.u32()?,
+				}),
+				SECTION_EVOLUTION => file.evolution = Some(EvolutionSettings {
+					generation_count: section.u32()?,
+					survivors_count: section.u32()?,
+					tournament_size: section.u32()?,
+					mutation_factor: section.f32()?,
+					same_weight: section.f32()?,
+					different_weight: section.f32()?,
+					size_weight: section.f32()?,
+					size_pow: section.f32()?,
+				}),
+				SECTION_METADATA => file.metadata = Metadata {
+					name: section.string()?,
+					notes: section.string()?,
+					created: section.u64()?,
+				},
+				// sections added by newer minor revisions are skipped
+				_ => {},
+			}
+		}
+
+		Ok(file)
+	}
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct GlsFileParts {
+	version: u16,
+	rules: Vec<Grid>,
+	axiom: Grid,
+	goal: Option<Grid>,
+	growth: Option<GrowthSettings>,
+	evolution: Option<EvolutionSettings>,
+	metadata: Metadata,
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for GlsFile {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let parts = GlsFileParts::deserialize(deserializer)?;
+		let file = GlsFile {
+			version: parts.version,
+			rules: parts.rules,
+			axiom: parts.axiom,
+			goal: parts.goal,
+			growth: parts.growth,
+			evolution: parts.evolution,
+			metadata: parts.metadata,
+		};
+		file.validate().map_err(serde::de::Error::custom)?;
+		Ok(file)
+	}
+}
+
+pub(crate) fn write_section(data: &mut Vec<u8>, tag: [u8; 4], section: &[u8]) {
+	data.extend_from_slice(&tag);
+	data.extend_from_slice(&(section.len() as u32).to_be_bytes());
+	data.extend_from_slice(section);
+}
+
+pub(crate) fn write_string(data: &mut Vec<u8>, string: &str) {
+	data.extend_from_slice(&(string.len() as u32).to_be_bytes());
+	data.extend_from_slice(string.as_bytes());
+}
+
+pub(crate) struct Reader<'a> {
+	data: &'a [u8],
+	cursor: usize,
+}
+
+impl<'a> Reader<'a> {
+	pub(crate) fn new(data: &'a [u8]) -> Self {
+		Self {
+			data,
+			cursor: 0,
+		}
+	}
+
+	pub(crate) fn is_empty(&self) -> bool {
+		self.cursor >= self.data.len()
+	}
+
+	pub(crate) fn remaining(&self) -> usize {
+		self.data.len().saturating_sub(self.cursor)
+	}
+
+	pub(crate) fn bytes(&mut self, n: usize) -> Result<&'a [u8], String> {
+		if self.data.len() - self.cursor < n {
+			return Err("Unexpected end of file".into());
+		}
+
+		let ret = &self.data[self.cursor..self.cursor + n];
+		self.cursor += n;
+		Ok(ret)
+	}
+
+	// a count of items taking at least `size` bytes each, refused when the rest of the data can't hold that many
+	pub(crate) fn count(&mut self, size: usize) -> Result<usize, String> {
+		let count = self.u32()? as usize;
+		if count.saturating_mul(size) > self.remaining() {
+			return Err("Count larger than the file".into());
+		}
+		Ok(count)
+	}
+
+	pub(crate) fn u16(&mut self) -> Result<u16, String> {
+		Ok(u16::from_be_bytes(self.bytes(2)?.try_into().unwrap()))
+	}
+
+	pub(crate) fn u32(&mut self) -> Result<u32, String> {
+		Ok(u32::from_be_bytes(self.bytes(4)?.try_into().unwrap()))
+	}
+
+	pub(crate) fn u64(&mut self) -> Result<u64, String> {
+		Ok(u64::from_be_bytes(self.bytes(8)?.try_into().unwrap()))
+	}
+
+	pub(crate) fn f32(&mut self) -> Result<f32, String> {
+		Ok(f32::from_be_bytes(self.bytes(4)?.try_into().unwrap()))
+	}
+
+	pub(crate) fn string(&mut self) -> Result<String, String> {
+		let length = self.u32()? as usize;
+		String::from_utf8(self.bytes(length)?.to_vec()).map_err(|_| String::from("Invalid text in file"))
+	}
+
+	pub(crate) fn u8(&mut self) -> Result<u8, String> {
+		Ok(self.bytes(1)?[0])
+	}
+
+	pub(crate) fn u128(&mut self) -> Result<u128, String> {
+		Ok(u128::from_be_bytes(self.bytes(16)?.try_into().unwrap()))
+	}
+
+	// values that parse themselves and report how many bytes they took
+	pub(crate) fn parse<T, F: FnOnce(&'a [u8]) -> Result<(T, usize), String>>(&mut self, parse: F) -> Result<T, String> {
+		let (value, length) = parse(&self.data[self.cursor..])?;
+		self.cursor += length;
+		Ok(value)
+	}
+
+	pub(crate) fn grid(&mut self) -> Result<Grid, String> {
+		let (grid, length) = Grid::deserialize(&self.data[self.cursor..]).map_err(|_| String::from("Invalid grid in file"))?;
+		self.cursor += length;
+		Ok(grid)
+	}
+}
@@ -0,0 +1,201 @@
+use std::{fs, path::Path};
+
+use crate::{l_system::{grid::Grid, cell::Cell}, render::Image};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColourMapping {
+	pub colour: [u8; 3],
+	pub cell: Cell,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImportSettings {
+	pub target_size: u32, // cells along the longer side of the image, 0 keeps one cell per pixel
+	pub threshold: f32, // pixels darker than this (0..1) become cells
+	pub invert: bool, // pixels lighter than the threshold become cells instead
+	pub alpha_mask: bool, // transparent pixels are always empty, regardless of their colour
+	pub mapping: Vec<ColourMapping>, // if not empty, every cell takes the type of the closest colour
+}
+
+impl Default for ImportSettings {
+	fn default() -> Self {
+		Self {
+			target_size: 20,
+			threshold: 0.5,
+			invert: false,
+			alpha_mask: true,
+			mapping: Vec::new(),
+		}
+	}
+}
+
+pub fn load_image<P: AsRef<Path>>(path: P) -> Result<Image, String> {
+	let data = fs::read(path).map_err(|e| e.to_string())?;
+
+	if data.starts_with(b"\x89PNG") {
+		decode_png(&data)
+	}
+	else if data.starts_with(b"BM") {
+		decode_bmp(&data)
+	}
+	else {
+		Err("Unsupported image format, expected PNG or BMP".into())
+	}
+}
+
+pub fn decode_png(data: &[u8]) -> Result<Image, String> {
+	let mut decoder = png::Decoder::new(data);
+	decoder.set_transformations(png::Transformations::normalize_to_color8());
+	let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+
+	let mut buffer = vec![0; reader.output_buffer_size()];
+	let info = reader.next_frame(&mut buffer).map_err(|e| e.to_string())?;
+	let buffer = &buffer[..info.buffer_size()];
+
+	let pixels = match info.color_type {
+		png::ColorType::Rgba => buffer.to_vec(),
+		png::ColorType::Rgb => buffer.chunks_exact(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect(),
+		png::ColorType::GrayscaleAlpha => buffer.chunks_exact(2).flat_map(|p| [p[0], p[0], p[0], p[1]]).collect(),
+		png::ColorType::Grayscale => buffer.iter().flat_map(|&p| [p, p, p, 255]).collect(),
+		png::ColorType::Indexed => return Err("Unexpected indexed colour".into()),
+	};
+
+	Ok(Image::from_rgba(info.width, info.height, pixels))
+}
+
+// uncompressed 8, 24 and 32 bit bitmaps
+pub fn decode_bmp(data: &[u8]) -> Result<Image, String> {
+	let u16_at = |i: usize| data.get(i..i + 2).map(|b| u16::from_le_bytes([b[0], b[1]])).ok_or(String::from("Truncated bitmap"));
+	let u32_at = |i: usize| data.get(i..i + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])).ok_or(String::from("Truncated bitmap"));
+
+	let pixel_offset = u32_at(10)? as usize;
+	let header_size = u32_at(14)? as usize;
+	let width = u32_at(18)? as i32;
+	let height = u32_at(22)? as i32;
+	let bits = u16_at(28)?;
+	let compression = u32_at(30)?;
+
+	// 3 is BI_BITFIELDS, which for 32 bit images written by common tools is plain BGRA
+	if compression != 0 && !(compression == 3 && bits == 32) {
+		return Err("Compressed bitmaps are not supported".into());
+	}
+	if width <= 0 || height == 0 {
+		return Err("Invalid bitmap size".into());
+	}
+	if ![8, 24, 32].contains(&bits) {
+		return Err(format!("{bits} bit bitmaps are not supported"));
+	}
+
+	let palette = if bits == 8 {
+		let colours = match u32_at(46)? { 0 => 256, n => n as usize };
+		let start = 14 + header_size;
+		(0..colours).map(|i| {
+			let b = data.get(start + i * 4..start + i * 4 + 3).ok_or(String::from("Truncated bitmap"))?;
+			Ok([b[2], b[1], b[0], 255])
+		}).collect::<Result<Vec<_>, String>>()?
+	}
+	else {
+		Vec::new()
+	};
+
+	let (width, bottom_up, height) = (width as u32, height > 0, height.unsigned_abs());
+
+	// the sizes come from the header, so they're checked against the data before anything is allocated
+	let row_size = (bits as u64 * width as u64).div_ceil(32) * 4;
+	let end = row_size.checked_mul(height as u64).and_then(|size| size.checked_add(pixel_offset as u64));
+	if end.is_none_or(|end| end > data.len() as u64) {
+		return Err("Truncated bitmap".into());
+	}
+	let size = width.checked_mul(height).and_then(|n| n.checked_mul(4)).ok_or(String::from("Bitmap too large"))?;
+	let row_size = row_size as usize;
+
+	let mut pixels = Vec::with_capacity(size as usize);
+	for y in 0..height {
+		let row = if bottom_up { height - 1 - y } else { y } as usize;
+		let start = pixel_offset + row * row_size;
+		let row = data.get(start..start + row_size).ok_or(String::from("Truncated bitmap"))?;
+
+		for x in 0..width as usize {
+			match bits {
+				8 => pixels.extend_from_slice(palette.get(row[x] as usize).ok_or(String::from("Invalid palette index"))?),
+				24 => pixels.extend_from_slice(&[row[x * 3 + 2], row[x * 3 + 1], row[x * 3], 255]),
+				_ => pixels.extend_from_slice(&[row[x * 4 + 2], row[x * 4 + 1], row[x * 4], row[x * 4 + 3]]),
+			}
+		}
+	}
+
+	Ok(Image::from_rgba(width, height, pixels))
+}
+
+pub fn image_to_grid(image: &Image, settings: &ImportSettings) -> Grid {
+	let longer = image.width().max(image.height());
+	let cells_per_pixel = if settings.target_size == 0 || settings.target_size >= longer {
+		1.0
+	}
+	else {
+		settings.target_size as f32 / longer as f32
+	};
+
+	let width = ((image.width() as f32 * cells_per_pixel).round() as u32).max(1);
+	let height = ((image.height() as f32 * cells_per_pixel).round() as u32).max(1);
+
+	let mut contents = Vec::with_capacity((width * height) as usize);
+
+	// grid rows go bottom to top, image rows top to bottom
+	for y in (0..height).rev() {
+		for x in 0..width {
+			let x0 = x * image.width() / width;
+			let x1 = ((x + 1) * image.width() / width).max(x0 + 1);
+			let y0 = y * image.height() / height;
+			let y1 = ((y + 1) * image.height() / height).max(y0 + 1);
+
+			contents.push(classify(average(image, [x0, y0], [x1, y1]), settings));
+		}
+	}
+
+	Grid::new(width, height, contents, [width / 2, height / 2])
+}
+
+// mean colour of the block, composited over white, and its mean alpha
+fn average(image: &Image, min: [u32; 2], max: [u32; 2]) -> ([f32; 3], f32) {
+	let mut colour = [0.0; 3];
+	let mut alpha = 0.0;
+
+	for y in min[1]..max[1] {
+		for x in min[0]..max[0] {
+			let [r, g, b, a] = image.pixel(x, y);
+			let a = a as f32 / 255.0;
+			colour[0] += r as f32 / 255.0 * a + 1.0 - a;
+			colour[1] += g as f32 / 255.0 * a + 1.0 - a;
+			colour[2] += b as f32 / 255.0 * a + 1.0 - a;
+			alpha += a;
+		}
+	}
+
+	let count = ((max[0] - min[0]) * (max[1] - min[1])) as f32;
+	(colour.map(|c| c / count), alpha / count)
+}
+
+fn classify((colour, alpha): ([f32; 3], f32), settings: &ImportSettings) -> Cell {
+	if settings.alpha_mask && alpha < 0.5 {
+		return Cell::Empty;
+	}
+
+	let luminance = 0.2126 * colour[0] + 0.7152 * colour[1] + 0.0722 * colour[2];
+	if (luminance < settings.threshold) == settings.invert {
+		return Cell::Empty;
+	}
+
+	let closest = settings.mapping.iter().min_by(|a, b| {
+		colour_distance(colour, a.colour).total_cmp(&colour_distance(colour, b.colour))
+	});
+
+	match closest {
+		Some(mapping) => mapping.cell,
+		None => Cell::Passive,
+	}
+}
+
+fn colour_distance(a: [f32; 3], b: [u8; 3]) -> f32 {
+	(0..3).map(|i| (a[i] - b[i] as f32 / 255.0).powi(2)).sum()
+}
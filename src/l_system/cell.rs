@@ -1,8 +1,9 @@
 use std::{fmt::Display, ops::Neg};
 
-use rand::{rngs::ThreadRng, Rng};
+use rand::Rng;
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Cell {
 	Stem(u8, Direction),
 	Passive,
@@ -10,7 +11,7 @@ pub enum Cell {
 }
 
 impl Cell {
-	pub fn random(rng: &mut ThreadRng, stem_types: u8) -> Self {
+	pub fn random<R: Rng>(rng: &mut R, stem_types: u8) -> Self {
 		match rng.gen_range(0..8) {
 			0 		=> Cell::Stem(rng.gen_range(0..stem_types), Direction::random(rng)),
 			1..=4 	=> Cell::Passive,
@@ -39,7 +40,8 @@ impl Cell {
 	}
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Direction {
 	UP, LEFT, DOWN, RIGHT
 }
@@ -64,7 +66,7 @@ impl Direction {
 		}
 	}
 
-	pub fn random(rng: &mut ThreadRng) -> Self {
+	pub fn random<R: Rng>(rng: &mut R) -> Self {
 		Direction::from_byte(rng.gen_range(0..4))
 	}
 
@@ -108,6 +110,16 @@ impl Direction {
 		}
 	}
 
+	pub fn mirror(&self, horizontally: bool) -> Direction {
+		match (self, horizontally) {
+			(Direction::LEFT, true) => Direction::RIGHT,
+			(Direction::RIGHT, true) => Direction::LEFT,
+			(Direction::UP, false) => Direction::DOWN,
+			(Direction::DOWN, false) => Direction::UP,
+			(dir, _) => *dir,
+		}
+	}
+
 	pub fn rotate_dir(&self, dir: Direction) -> Direction {
 		match self {
 			Direction::UP => 	dir,
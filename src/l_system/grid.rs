@@ -1,6 +1,6 @@
-use std::fmt::{Display, Debug};
+use std::{fmt::{Display, Debug}, hash::{Hash, Hasher}};
 
-use rand::{rngs::ThreadRng, Rng};
+use rand::Rng;
 
 use crate::l_system::cell::{Cell, Direction};
 
@@ -14,7 +14,7 @@ pub struct Grid {
 
 impl Grid {
 
-	pub fn random(rng: &mut ThreadRng, stem_types: u8) -> Grid {
+	pub fn random<R: Rng>(rng: &mut R, stem_types: u8) -> Grid {
 		let width = rng.gen_range(1..=4);
 		let height = rng.gen_range(1..=4);
 	
@@ -55,6 +55,78 @@ impl Grid {
 		Some(Grid::new(width, height, contents, shift))
 	}
 
+	// one string per row, top row first. '.' is empty, 'W' is passive and stems are written as their type followed by a direction arrow, e.g. "12>"
+	pub fn to_rows(&self) -> Vec<String> {
+		let mut rows = Vec::with_capacity(self.height as usize);
+
+		for y in (0..self.height).rev() {
+			let mut row = String::with_capacity(self.width as usize);
+			for x in 0..self.width {
+				match self.at_raw([x, y]) {
+					Cell::Stem(n, d) => row.push_str(&format!("{n}{d}")),
+					Cell::Passive => row.push('W'),
+					Cell::Empty => row.push('.'),
+				}
+			}
+			rows.push(row);
+		}
+
+		rows
+	}
+
+	pub fn from_rows<S: AsRef<str>>(rows: &[S], shift: [u32; 2]) -> Result<Grid, String> {
+		let mut width = 0;
+		let mut contents = Vec::new();
+
+		for row in rows.iter().rev() {
+			let mut row_length = 0;
+			let mut chars = row.as_ref().chars().peekable();
+
+			while let Some(ch) = chars.next() {
+				let cell = match ch {
+					'.' => Cell::Empty,
+					'0'..='9' => {
+						let mut n = ch.to_digit(10).unwrap();
+						while let Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+							n = n * 10 + digit;
+							chars.next();
+						}
+						if n > u8::MAX as u32 {
+							return Err(format!("Stem type {n} is too large"));
+						}
+
+						let dir = match chars.next() {
+							Some('^') => Direction::UP,
+							Some('<') => Direction::LEFT,
+							Some('v') => Direction::DOWN,
+							Some('>') => Direction::RIGHT,
+							_ => return Err(format!("Stem {n} has no direction")),
+						};
+						Cell::Stem(n as u8, dir)
+					},
+					_ => Cell::Passive,
+				};
+				contents.push(cell);
+				row_length += 1;
+			}
+
+			if width == 0 { width = row_length }
+			else if width != row_length {
+				return Err("Rows are of different lengths".into());
+			}
+		}
+
+		let height = rows.len() as u32;
+		if width == 0 || height == 0 {
+			return Err("Grid is empty".into());
+		}
+		if !(width > shift[0] && height > shift[1]) {
+			return Err("Origin is outside of the grid".into());
+		}
+
+		Ok(Grid::new(width, height, contents, shift))
+	}
+
 	pub fn new(width: u32, height: u32, contents: Vec<Cell>, shift: [u32; 2]) -> Self {
 		assert!(width > shift[0] && height > shift[1]);
 		assert!(width*height == contents.len() as u32);
@@ -299,7 +371,7 @@ impl Grid {
 		true
 	}
 
-	pub fn expand(&mut self, direction: Direction, rng: &mut ThreadRng, stem_types: u8) {
+	pub fn expand<R: Rng>(&mut self, direction: Direction, rng: &mut R, stem_types: u8) {
 		match direction{
 			Direction::UP => {
 				for _ in 0..self.width {
@@ -377,12 +449,58 @@ impl Grid {
 		*self = new;
 	}
 
+	// flips the cells and stem directions around the origin, left to right or top to bottom
+	pub fn mirror(&mut self, horizontally: bool) {
+		let mut contents = Vec::with_capacity(self.contents.len());
+		for y in 0..self.height {
+			for x in 0..self.width {
+				let raw = if horizontally { [self.width - 1 - x, y] } else { [x, self.height - 1 - y] };
+				contents.push(match self.at_raw_unchecked(raw) {
+					Cell::Stem(n, dir) => Cell::Stem(n, dir.mirror(horizontally)),
+					cell => cell,
+				});
+			}
+		}
+
+		self.contents = contents;
+		if horizontally { self.shift[0] = self.width - 1 - self.shift[0]; }
+		else { self.shift[1] = self.height - 1 - self.shift[1]; }
+	}
+
+	// moves the origin one cell in the direction, the cells stay where they are
+	pub fn shift_origin(&mut self, direction: Direction) {
+		let [x, y] = direction.rotate_coords([0, 1]);
+		let mut shifted = Grid::single(Cell::Empty);
+		shifted.insert(self, [-x, -y], Direction::UP);
+		*self = shifted;
+	}
+
 	pub fn clear(&mut self) {
 		for c in &mut self.contents {
 			*c = Cell::Empty;
 		}
 	}
 
+	// hashes the grid as `contract_empty` would leave it: the filled cells and the bounds around them and the origin
+	pub fn hash_contracted<H: Hasher>(&self, state: &mut H) {
+		let mut min = [0; 2];
+		let mut max = [0; 2];
+		for ([x, y], cell) in self {
+			if cell == Cell::Empty { continue; }
+			min = [min[0].min(x), min[1].min(y)];
+			max = [max[0].max(x), max[1].max(y)];
+		}
+		min.hash(state);
+		max.hash(state);
+
+		for (pos, cell) in self {
+			if cell != Cell::Empty {
+				pos.hash(state);
+				cell.hash(state);
+			}
+		}
+	}
+
 
 	pub fn contents(&self) -> &Vec<Cell> {
 		&self.contents
@@ -462,10 +580,15 @@ impl Grid {
 			u32::from_be_bytes(data[12..16].try_into().unwrap()),
 		];
 
-		let mut contents = Vec::with_capacity((width * height) as usize);
+		// every cell takes at least a byte, larger sizes can only come from broken data
+		let count = width as u64 * height as u64;
+		if count > (data.len() - 16) as u64 {
+			return Err(());
+		}
+		let mut contents = Vec::with_capacity(count as usize);
 
 		let mut cursor = 16;
-		for _ in 0..(width*height) {
+		for _ in 0..count {
 			if data.len() <= cursor {
 				return Err(());
 			}
@@ -585,4 +708,29 @@ impl Debug for Grid {
         Display::fmt(&self, f).unwrap();
 		Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct GridRows {
+	shift: [u32; 2],
+	rows: Vec<String>,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Grid {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		GridRows {
+			shift: self.shift,
+			rows: self.to_rows(),
+		}.serialize(serializer)
+	}
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Grid {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let rows = GridRows::deserialize(deserializer)?;
+		Grid::from_rows(&rows.rows, rows.shift).map_err(serde::de::Error::custom)
+	}
+}
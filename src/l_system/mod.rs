@@ -1,12 +1,14 @@
 pub mod cell;
 pub mod grid;
 pub mod ruleset;
+pub mod simplify;
 
 use std::collections::VecDeque;
 use grid::Grid;
 use cell::Cell;
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct LSystem {
 	state: Grid,
 	rules: Vec<Grid>,
@@ -33,6 +35,26 @@ impl LSystem {
 		}
 	}
 
+	// for systems read from elsewhere: checks what `try_step` relies on, that there are rules,
+	// that every stem refers to one and that every queued position holds a stem
+	pub fn with_queue(state: Grid, rules: Vec<Grid>, stem_queue: VecDeque<[i32; 2]>) -> Result<Self, String> {
+		if rules.is_empty() {
+			return Err("System has no rules".into());
+		}
+		if !is_valid(&rules) || !is_valid_for(&state, rules.len()) {
+			return Err("Stems refer to missing rules".into());
+		}
+		if stem_queue.iter().any(|&pos| !matches!(state.at(pos), Cell::Stem(_, _))) {
+			return Err("Queued position without a stem".into());
+		}
+
+		Ok(Self {
+			state,
+			rules,
+			stem_queue,
+		})
+	}
+
 	pub fn try_step(&mut self) -> bool {
 		if self.stem_queue.is_empty() { return false; }
 
@@ -100,17 +122,29 @@ impl LSystem {
 }
 
 pub fn is_valid(rules: &[Grid]) -> bool {
-	let max_stem = (rules.len() - 1) as u8;
+	rules.iter().all(|rule| is_valid_for(rule, rules.len()))
+}
 
-	for rule in rules {
-		for (_, cell) in rule {
-			if let Cell::Stem(n, _) = cell {
-				if n > max_stem {
-					return false;
-				}
-			}
-		}
-	}
+// whether the stems of the grid all refer to one of `rule_count` rules
+pub fn is_valid_for(grid: &Grid, rule_count: usize) -> bool {
+	grid.contents().iter().all(|cell| match cell {
+		Cell::Stem(n, _) => (*n as usize) < rule_count,
+		_ => true,
+	})
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct LSystemParts {
+	state: Grid,
+	rules: Vec<Grid>,
+	stem_queue: VecDeque<[i32; 2]>,
+}
 
-	true
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for LSystem {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let parts = LSystemParts::deserialize(deserializer)?;
+		LSystem::with_queue(parts.state, parts.rules, parts.stem_queue).map_err(serde::de::Error::custom)
+	}
 }
\ No newline at end of file
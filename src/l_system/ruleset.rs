@@ -1,20 +1,84 @@
-use rand::{rngs::ThreadRng, Rng, seq::SliceRandom};
+use std::{collections::hash_map::DefaultHasher, hash::{Hash, Hasher}};
 
-use super::{grid::Grid, cell::{Cell, Direction}};
+use rand::{Rng, seq::SliceRandom};
+
+use super::{LSystem, grid::Grid, cell::{Cell, Direction}};
+
+// stems refer to their rule by a u8, so the operators don't add rules past this many
+pub const MAX_RULES: usize = u8::MAX as usize;
+
+// symmetry the bodies grown from the usual axiom are constrained to
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Symmetry {
+	#[default]
+	None,
+	Mirror, // left to right
+	Rotational, // by quarter turns around the axiom
+}
+
+impl Symmetry {
+	pub const NAMES: [&'static str; 3] = ["None", "Mirror", "Rotational"];
+
+	pub fn name(&self) -> &'static str {
+		Self::NAMES[self.to_byte() as usize]
+	}
+
+	pub fn from_name(name: &str) -> Option<Self> {
+		Self::NAMES.iter().position(|n| n.eq_ignore_ascii_case(name)).map(|i| Self::from_byte(i as u8))
+	}
+
+	pub fn from_byte(n: u8) -> Self {
+		match n {
+			1 => Symmetry::Mirror,
+			2 => Symmetry::Rotational,
+			_ => Symmetry::None,
+		}
+	}
+
+	pub fn to_byte(&self) -> u8 {
+		match self {
+			Symmetry::None => 0,
+			Symmetry::Mirror => 1,
+			Symmetry::Rotational => 2,
+		}
+	}
+}
 
 pub trait Ruleset {
-	fn delete_rule(&mut self, rng: &mut ThreadRng);
-	fn add_rule(&mut self, rng: &mut ThreadRng);
-	fn expand_rule(&mut self, rng: &mut ThreadRng);
-	fn contract_rule(&mut self, rng: &mut ThreadRng);
-	fn separate_rule(&mut self, rng: &mut ThreadRng);
-	fn mutate_cells(&mut self, rng: &mut ThreadRng, factor: f64);
+	fn delete_rule<R: Rng>(&mut self, rng: &mut R);
+	fn add_rule<R: Rng>(&mut self, rng: &mut R);
+	fn expand_rule<R: Rng>(&mut self, rng: &mut R);
+	fn contract_rule<R: Rng>(&mut self, rng: &mut R);
+	fn separate_rule<R: Rng>(&mut self, rng: &mut R);
+	fn mutate_cells<R: Rng>(&mut self, rng: &mut R, factor: f64);
+	fn rotate_rule<R: Rng>(&mut self, rng: &mut R);
+	fn mirror_rule<R: Rng>(&mut self, rng: &mut R);
+	fn shift_rule_origin<R: Rng>(&mut self, rng: &mut R);
+	// the operators below return false when the rules have nothing to apply them to, or already `MAX_RULES` rules
+	fn retarget_stem<R: Rng>(&mut self, rng: &mut R) -> bool;
+	fn grow_stem<R: Rng>(&mut self, rng: &mut R) -> bool;
+	fn merge_rules<R: Rng>(&mut self, rng: &mut R) -> bool;
+	fn duplicate_rule<R: Rng>(&mut self, rng: &mut R, factor: f64) -> bool;
+	fn crossover_rules<R: Rng>(&mut self, other: &[Grid], rng: &mut R);
+	fn crossover_cells<R: Rng>(&mut self, other: &[Grid], rng: &mut R);
 	fn clear_dead_rules(&mut self);
 	fn contract_empty_borders(&mut self);
+	// trims the rules, drops those the axiom can't reach and numbers the rest in the order
+	// they're discovered from it. Rulesets that only differ in those ways end up equal
+	fn canonicalize_with(&mut self, axiom: &mut Grid);
+	// with the usual axiom of a single stem of rule 0
+	fn canonicalize(&mut self);
+	// equal for rulesets with the same canonical form
+	fn canonical_hash(&self) -> u64;
+	// equal for rulesets and axioms with the same canonical form
+	fn canonical_hash_with(&self, axiom: &Grid) -> u64;
+	// makes every rule symmetric around its origin, so the grown body is too apart from where growth overlaps
+	fn symmetrize(&mut self, symmetry: Symmetry);
 }
 
 impl Ruleset for Vec<Grid> {
-    fn delete_rule(&mut self, rng: &mut ThreadRng) {
+    fn delete_rule<R: Rng>(&mut self, rng: &mut R) {
 		let to_delete = rng.gen_range(1..self.len());
 
 		self.remove(to_delete);
@@ -30,8 +94,9 @@ impl Ruleset for Vec<Grid> {
 		}
     }
 
-    fn add_rule(&mut self, rng: &mut ThreadRng) {
+    fn add_rule<R: Rng>(&mut self, rng: &mut R) {
 		let rules_len = self.len();
+		if rules_len >= MAX_RULES { return; }
 		for rule in self.iter_mut() {
 			for cell in rule.contents_mut() {
 				if let Cell::Stem(n, _) = cell {
@@ -45,18 +110,18 @@ impl Ruleset for Vec<Grid> {
 		self.push(Grid::random(rng, (self.len() + 1) as u8));
     }
 
-    fn expand_rule(&mut self, rng: &mut ThreadRng) {
+    fn expand_rule<R: Rng>(&mut self, rng: &mut R) {
 		let rules_len = self.len();
 		let rule = self.choose_mut(rng).unwrap();
 		rule.expand(Direction::random(rng), rng, rules_len as u8);
     }
 
-    fn contract_rule(&mut self, rng: &mut ThreadRng) {
+    fn contract_rule<R: Rng>(&mut self, rng: &mut R) {
 		let rule = self.choose_mut(rng).unwrap();
 		rule.contract(Direction::random(rng));
     }
 
-    fn separate_rule(&mut self, rng: &mut ThreadRng) {
+    fn separate_rule<R: Rng>(&mut self, rng: &mut R) {
 		let choice = rng.gen_range(0..self.len());
 
 		let mut stem_count = 0;
@@ -70,7 +135,7 @@ impl Ruleset for Vec<Grid> {
 			}
 		}
 
-		if stem_count > 1 {
+		if stem_count > 1 && self.len() < MAX_RULES {
 			stem_count = rng.gen_range(0..stem_count);
 		
 			let new_index = self.len() as u8;
@@ -95,7 +160,7 @@ impl Ruleset for Vec<Grid> {
 		}
 	}
 
-    fn mutate_cells(&mut self, rng: &mut ThreadRng, factor: f64) {
+    fn mutate_cells<R: Rng>(&mut self, rng: &mut R, factor: f64) {
 		let rules_len = self.len();
 		for rule in self.iter_mut() {
 			for cell in rule.contents_mut() {
@@ -106,6 +171,122 @@ impl Ruleset for Vec<Grid> {
 		}
 	}
 
+    fn rotate_rule<R: Rng>(&mut self, rng: &mut R) {
+		let rule = self.choose_mut(rng).unwrap();
+		rule.rotate(Direction::from_byte(rng.gen_range(1..4)));
+	}
+
+    fn mirror_rule<R: Rng>(&mut self, rng: &mut R) {
+		let rule = self.choose_mut(rng).unwrap();
+		rule.mirror(rng.gen_bool(0.5));
+	}
+
+    fn shift_rule_origin<R: Rng>(&mut self, rng: &mut R) {
+		let rule = self.choose_mut(rng).unwrap();
+		rule.shift_origin(Direction::random(rng));
+	}
+
+	// points a stem at a different rule
+    fn retarget_stem<R: Rng>(&mut self, rng: &mut R) -> bool {
+		let rules_len = self.len() as u8;
+		if rules_len < 2 { return false; }
+		let Some(&[rule, i]) = stems(self).choose(rng) else { return false; };
+
+		if let Cell::Stem(n, _) = &mut self[rule].contents_mut()[i] {
+			let target = rng.gen_range(0..rules_len - 1);
+			*n = if target >= *n { target + 1 } else { target };
+		}
+		true
+	}
+
+	// turns a passive cell into a stem of a new random rule
+    fn grow_stem<R: Rng>(&mut self, rng: &mut R) -> bool {
+		if self.len() >= MAX_RULES { return false; }
+		let passive: Vec<[usize; 2]> = cells(self).filter(|&(_, cell)| cell == Cell::Passive).map(|(pos, _)| pos).collect();
+		let Some(&[rule, i]) = passive.choose(rng) else { return false; };
+
+		let new_index = self.len() as u8;
+		self[rule].contents_mut()[i] = Cell::Stem(new_index, Direction::random(rng));
+		self.push(Grid::random(rng, new_index + 1));
+		true
+	}
+
+	// replaces one of two rules that differ in at most `MERGE_DIFFERENCE` cells by the other
+    fn merge_rules<R: Rng>(&mut self, rng: &mut R) -> bool {
+		let mut pairs = Vec::new();
+		for a in 0..self.len() {
+			for b in (a + 1)..self.len() {
+				if rule_difference(&self[a], &self[b]) <= MERGE_DIFFERENCE {
+					pairs.push((a, b));
+				}
+			}
+		}
+		let Some(&(kept, merged)) = pairs.choose(rng) else { return false; };
+
+		self.remove(merged);
+		for rule in self.iter_mut() {
+			for cell in rule.contents_mut() {
+				if let Cell::Stem(n, _) = cell {
+					if *n as usize == merged { *n = kept as u8 }
+					else if *n as usize > merged { *n -= 1 }
+				}
+			}
+		}
+		true
+	}
+
+	// gives one stem its own copy of its rule, with mutated cells
+    fn duplicate_rule<R: Rng>(&mut self, rng: &mut R, factor: f64) -> bool {
+		if self.len() >= MAX_RULES { return false; }
+		let Some(&[rule, i]) = stems(self).choose(rng) else { return false; };
+		let Cell::Stem(n, dir) = self[rule].contents()[i] else { unreachable!() };
+
+		let new_index = self.len() as u8;
+		let mut copy = self[n as usize].clone();
+		for cell in copy.contents_mut() {
+			if rng.gen_bool(factor) {
+				*cell = Cell::random(rng, new_index + 1);
+			}
+		}
+
+		self[rule].contents_mut()[i] = Cell::Stem(new_index, dir);
+		self.push(copy);
+		true
+	}
+
+	// uniform crossover of whole rules, rule i of the result comes from either parent's rule i
+    fn crossover_rules<R: Rng>(&mut self, other: &[Grid], rng: &mut R) {
+		let shared = self.len().min(other.len());
+		for i in 0..shared {
+			if rng.gen_bool(0.5) {
+				self[i] = other[i].clone();
+			}
+		}
+
+		wrap_stems(self);
+	}
+
+	// copies a random rectangle of one of the other's rules onto the same rule, aligned by origin
+    fn crossover_cells<R: Rng>(&mut self, other: &[Grid], rng: &mut R) {
+		let i = rng.gen_range(0..self.len().min(other.len()));
+		let donor = &other[i];
+
+		let x0 = rng.gen_range(0..donor.width());
+		let x1 = rng.gen_range(x0..donor.width());
+		let y0 = rng.gen_range(0..donor.height());
+		let y1 = rng.gen_range(y0..donor.height());
+
+		let shift = donor.shift();
+		for y in y0..=y1 {
+			for x in x0..=x1 {
+				let pos = [x as i32 - shift[0] as i32, y as i32 - shift[1] as i32];
+				self[i].insert_cell(donor.at_raw([x, y]), pos);
+			}
+		}
+
+		wrap_stems(self);
+	}
+
     fn clear_dead_rules(&mut self) {
 		let mut used = vec![false; self.len()];
 		used[0] = true;
@@ -137,4 +318,180 @@ impl Ruleset for Vec<Grid> {
 			rule.contract_empty();
 		}
 	}
-}
\ No newline at end of file
+
+	fn canonicalize_with(&mut self, axiom: &mut Grid) {
+		let mut new_index = vec![None; self.len()];
+		let mut order = Vec::new(); // old indices by new index
+		fn discover(grid: &Grid, new_index: &mut [Option<u8>], order: &mut Vec<usize>) {
+			for cell in grid.contents() {
+				if let Cell::Stem(n, _) = cell {
+					if new_index[*n as usize].is_none() {
+						new_index[*n as usize] = Some(order.len() as u8);
+						order.push(*n as usize);
+					}
+				}
+			}
+		}
+
+		discover(axiom, &mut new_index, &mut order);
+		// an axiom without stems reaches nothing, but there has to be a rule. The first one is kept, with what it reaches
+		if order.is_empty() && !self.is_empty() {
+			new_index[0] = Some(0);
+			order.push(0);
+		}
+		let mut i = 0;
+		while i < order.len() {
+			discover(&self[order[i]], &mut new_index, &mut order);
+			i += 1;
+		}
+
+		let mut rules: Vec<Option<Grid>> = self.drain(..).map(Some).collect();
+		self.extend(order.iter().map(|&i| rules[i].take().unwrap()));
+		for grid in self.iter_mut().chain(std::iter::once(axiom)) {
+			for cell in grid.contents_mut() {
+				if let Cell::Stem(n, _) = cell {
+					*n = new_index[*n as usize].unwrap();
+				}
+			}
+		}
+
+		self.contract_empty_borders();
+	}
+
+	fn canonicalize(&mut self) {
+		self.canonicalize_with(&mut Grid::single(Cell::Stem(0, Direction::UP)));
+	}
+
+	fn canonical_hash(&self) -> u64 {
+		self.canonical_hash_with(&Grid::single(Cell::Stem(0, Direction::UP)))
+	}
+
+	fn canonical_hash_with(&self, axiom: &Grid) -> u64 {
+		let mut canonical = self.clone();
+		let mut axiom = axiom.clone();
+		canonical.canonicalize_with(&mut axiom);
+
+		let mut hasher = DefaultHasher::new();
+		canonical.len().hash(&mut hasher);
+		for rule in &canonical {
+			rule.hash_contracted(&mut hasher);
+		}
+		axiom.hash_contracted(&mut hasher);
+		hasher.finish()
+	}
+
+	fn symmetrize(&mut self, symmetry: Symmetry) {
+		for rule in self.iter_mut() {
+			*rule = symmetric_rule(rule, symmetry);
+		}
+	}
+}
+
+// the rule rebuilt from one half or quarter of it. Of the possible halves or quarters the one with the most cells is kept
+fn symmetric_rule(rule: &Grid, symmetry: Symmetry) -> Grid {
+	let candidates: Vec<Grid> = match symmetry {
+		Symmetry::None => return rule.clone(),
+		Symmetry::Mirror => [false, true].into_iter().map(|flip| {
+			let mut rule = rule.clone();
+			if flip { rule.mirror(true); }
+			mirrored_from_left(&rule)
+		}).collect(),
+		Symmetry::Rotational => (0..4).map(|turn| {
+			let mut rule = rule.clone();
+			rule.rotate(Direction::from_byte(turn));
+			rotated_from_quarter(&rule)
+		}).collect(),
+	};
+
+	// ties go to a candidate that still has a stem to grow from
+	let rank = |rule: &Grid| {
+		let filled = rule.contents().iter().filter(|&&cell| cell != Cell::Empty).count();
+		(filled, rule.contents().iter().any(|cell| matches!(cell, Cell::Stem(_, _))))
+	};
+	let best = candidates.iter().map(rank).max().unwrap();
+	candidates.into_iter().find(|rule| rank(rule) == best).unwrap()
+}
+
+// the cells left of the origin and their mirror images. Stems on the axis can't point sideways, they point up instead
+fn mirrored_from_left(rule: &Grid) -> Grid {
+	let mut mirrored = Grid::single(Cell::Empty);
+	for ([x, y], cell) in rule {
+		match cell {
+			Cell::Stem(n, Direction::LEFT | Direction::RIGHT) if x == 0 => mirrored.insert_cell(Cell::Stem(n, Direction::UP), [x, y]),
+			cell if x == 0 => mirrored.insert_cell(cell, [x, y]),
+			Cell::Stem(n, dir) if x < 0 => {
+				mirrored.insert_cell(cell, [x, y]);
+				mirrored.insert_cell(Cell::Stem(n, dir.mirror(true)), [-x, y]);
+			},
+			cell if x < 0 => {
+				mirrored.insert_cell(cell, [x, y]);
+				mirrored.insert_cell(cell, [-x, y]);
+			},
+			_ => {},
+		}
+	}
+	mirrored
+}
+
+// the origin and the quarter right of it, turned four times
+fn rotated_from_quarter(rule: &Grid) -> Grid {
+	let mut rotated = Grid::single(rule.at([0, 0]));
+	for ([x, y], cell) in rule {
+		if x <= 0 || y < 0 { continue; }
+		for turn in 0..4 {
+			let dir = Direction::from_byte(turn);
+			rotated.insert_cell(dir.rotate_cell(cell), dir.rotate_coords([x, y]));
+		}
+	}
+	rotated
+}
+
+// whether the rulesets have the same canonical form or grow the same state from the usual axiom within `max_steps`
+pub fn equivalent(a: &[Grid], b: &[Grid], max_steps: usize) -> bool {
+	let mut canonical = [a.to_vec(), b.to_vec()];
+	canonical.iter_mut().for_each(|rules| rules.canonicalize());
+	let [a, b] = canonical;
+	if a.len() == b.len() && a.iter().zip(&b).all(|(a, b)| rule_difference(a, b) == 0) {
+		return true;
+	}
+
+	let [a, b] = [a, b].map(|rules| {
+		let mut system = LSystem::new(Grid::single(Cell::Stem(0, Direction::UP)), rules);
+		for _ in 0..max_steps {
+			if !system.try_step() { break; }
+		}
+		system.state().clone()
+	});
+	rule_difference(&a, &b) == 0
+}
+
+// rules at most this many cells apart count as nearly identical for `merge_rules`
+const MERGE_DIFFERENCE: usize = 2;
+
+// rule and content indices of every cell
+fn cells(rules: &[Grid]) -> impl Iterator<Item = ([usize; 2], Cell)> + '_ {
+	rules.iter().enumerate().flat_map(|(rule, grid)| grid.contents().iter().enumerate().map(move |(i, cell)| ([rule, i], *cell)))
+}
+
+fn stems(rules: &[Grid]) -> Vec<[usize; 2]> {
+	cells(rules).filter(|(_, cell)| matches!(cell, Cell::Stem(_, _))).map(|(pos, _)| pos).collect()
+}
+
+// number of positions, relative to the origins, where the rules have different cells
+pub(super) fn rule_difference(a: &Grid, b: &Grid) -> usize {
+	let in_a = a.into_iter().filter(|&(pos, cell)| cell != b.at(pos)).count();
+	let only_in_b = b.into_iter().filter(|&(pos, cell)| !a.contains(pos) && cell != Cell::Empty).count();
+	in_a + only_in_b
+}
+
+// stems brought over from a longer ruleset could point past the end of this one
+fn wrap_stems(rules: &mut [Grid]) {
+	let rules_len = rules.len();
+	for rule in rules.iter_mut() {
+		for cell in rule.contents_mut() {
+			if let Cell::Stem(n, _) = cell {
+				*n = (*n as usize % rules_len) as u8;
+			}
+		}
+	}
+}
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+
+use super::{LSystem, grid::Grid, cell::Cell, ruleset::{Ruleset, rule_difference}};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Simplification {
+	pub removed_cells: usize,
+	pub removed_rules: usize,
+	pub steps: usize, // growth steps the result was checked for, it can grow differently after them
+	pub stopped: bool, // both systems stopped growing within the steps, so they grow the same at any later step too
+}
+
+// which rule cells matter when growing from the axiom for `max_steps`: the ones whose writes end up in the final state,
+// stems that get expanded and cells that overwrite a stem before it could be
+pub fn effective_cells(rules: &[Grid], axiom: &Grid, max_steps: usize) -> Vec<Vec<bool>> {
+	let mut effective: Vec<Vec<bool>> = rules.iter().map(|rule| vec![false; rule.contents().len()]).collect();
+	let mut writers: HashMap<[i32; 2], (usize, usize)> = HashMap::new(); // the rule cell that last wrote each state cell
+	let mut system = LSystem::new(axiom.clone(), rules.to_vec());
+
+	for _ in 0..max_steps {
+		let Some(&pos) = system.queue().front() else { break; };
+		let Cell::Stem(n, dir) = system.state().at(pos) else { unreachable!() };
+		if let Some(&(rule, i)) = writers.get(&pos) {
+			effective[rule][i] = true;
+		}
+
+		for (i, (rule_pos, cell)) in rules[n as usize].into_iter().enumerate() {
+			if cell == Cell::Empty { continue; }
+			let [x, y] = dir.rotate_coords(rule_pos);
+			let state_pos = [x + pos[0], y + pos[1]];
+			if let Cell::Stem(_, _) = system.state().at(state_pos) {
+				effective[n as usize][i] = true;
+			}
+			writers.insert(state_pos, (n as usize, i));
+		}
+
+		system.try_step();
+	}
+
+	for (pos, cell) in system.state() {
+		if cell == Cell::Empty { continue; }
+		if let Some(&(rule, i)) = writers.get(&pos) {
+			effective[rule][i] = true;
+		}
+	}
+	effective
+}
+
+// removes the cells that have no effect on growth within `max_steps`, then merges the rules that became identical and
+// canonicalizes the rest. The result is grown again and kept only if it grows the same state after `max_steps`, apart from
+// stem numbers. Cells that only matter later are removed too, unless the system stops growing before
+pub fn simplify(rules: &mut Vec<Grid>, axiom: &mut Grid, max_steps: usize) -> Simplification {
+	let original = (rules.clone(), axiom.clone());
+	let (expected, stopped) = grow_until_stopped(rules, axiom, max_steps);
+	let cell_count = |rules: &[Grid]| rules.iter().flat_map(|rule| rule.contents()).filter(|&&cell| cell != Cell::Empty).count();
+
+	let effective = effective_cells(rules, axiom, max_steps);
+	let mut candidates = Vec::new();
+	for (rule, effective) in effective.iter().enumerate() {
+		for (i, &effective) in effective.iter().enumerate() {
+			if !effective && rules[rule].contents()[i] != Cell::Empty {
+				candidates.push((rule, i));
+			}
+		}
+	}
+
+	let mut trimmed = rules.clone();
+	for &(rule, i) in &candidates {
+		trimmed[rule].contents_mut()[i] = Cell::Empty;
+	}
+	// removing every candidate at once can change the order of the stem queue, then they're tried one by one
+	if rule_difference(&grow(&trimmed, axiom, max_steps), &expected) != 0 {
+		trimmed = rules.clone();
+		for &(rule, i) in &candidates {
+			let cell = trimmed[rule].contents()[i];
+			trimmed[rule].contents_mut()[i] = Cell::Empty;
+			if rule_difference(&grow(&trimmed, axiom, max_steps), &expected) != 0 {
+				trimmed[rule].contents_mut()[i] = cell;
+			}
+		}
+	}
+
+	merge_identical(&mut trimmed, axiom);
+	trimmed.canonicalize_with(axiom);
+
+	let (result, trimmed_stopped) = grow_until_stopped(&trimmed, axiom, max_steps);
+	if !same_body(&result, &expected) || (stopped && !trimmed_stopped) {
+		(*rules, *axiom) = original;
+		return Simplification { steps: max_steps, stopped, ..Default::default() };
+	}
+
+	let simplification = Simplification {
+		removed_cells: cell_count(rules) - cell_count(&trimmed),
+		removed_rules: rules.len() - trimmed.len(),
+		steps: max_steps,
+		stopped,
+	};
+	*rules = trimmed;
+	simplification
+}
+
+fn grow(rules: &[Grid], axiom: &Grid, max_steps: usize) -> Grid {
+	grow_until_stopped(rules, axiom, max_steps).0
+}
+
+// the state and whether there are no stems left to grow
+fn grow_until_stopped(rules: &[Grid], axiom: &Grid, max_steps: usize) -> (Grid, bool) {
+	let mut system = LSystem::new(axiom.clone(), rules.to_vec());
+	for _ in 0..max_steps {
+		if !system.try_step() { break; }
+	}
+	(system.state().clone(), system.queue().is_empty())
+}
+
+// points stems of rules with the same cells as an earlier rule to that one, leaving the copies unreachable
+fn merge_identical(rules: &mut [Grid], axiom: &mut Grid) {
+	let targets: Vec<u8> = (0..rules.len())
+		.map(|j| (0..j).find(|&i| rule_difference(&rules[i], &rules[j]) == 0).unwrap_or(j) as u8)
+		.collect();
+
+	for grid in rules.iter_mut().chain(std::iter::once(axiom)) {
+		for cell in grid.contents_mut() {
+			if let Cell::Stem(n, _) = cell {
+				*n = targets[*n as usize];
+			}
+		}
+	}
+}
+
+// equal cells, stems only need to point the same way
+fn same_body(a: &Grid, b: &Grid) -> bool {
+	let same = |x: Cell, y: Cell| match (x, y) {
+		(Cell::Stem(_, a), Cell::Stem(_, b)) => a == b,
+		(x, y) => x == y,
+	};
+	a.into_iter().all(|(pos, cell)| same(cell, b.at(pos))) && b.into_iter().all(|(pos, cell)| same(cell, a.at(pos)))
+}
@@ -1,2 +1,6 @@
 pub mod l_system;
-pub mod genetic_algorithm;
\ No newline at end of file
+pub mod genetic_algorithm;
+pub mod gls_file;
+pub mod render;
+pub mod import;
+pub mod ls_evolve;
\ No newline at end of file
@@ -0,0 +1,462 @@
+use std::{collections::{HashMap, hash_map::DefaultHasher}, hash::Hasher};
+
+use rand::Rng;
+
+use crate::l_system::{LSystem, is_valid, is_valid_for};
+use crate::genetic_algorithm::{evolve::Evolve, map_elites::Axis, checkpoint::Persist, mutation::choose_weighted};
+use crate::gls_file::{Reader, GRID_HEADER};
+use crate::l_system::cell::{Cell, Direction};
+use crate::l_system::grid::Grid;
+use crate::l_system::ruleset::{Ruleset, Symmetry, MAX_RULES};
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EvolveParams {
+	pub goal: Grid,
+	pub max_steps: u16,
+
+	pub same_weight: f32,
+	pub different_weight: f32,
+	pub size_weight: f32,
+	pub size_pow: f32,
+
+	pub symmetry: Symmetry, // the rules are made symmetric before growing, and stay so
+}
+
+impl EvolveParams {
+	pub fn new(goal: Grid) -> Self {
+		Self {
+			goal,
+			max_steps: 25,
+
+			same_weight: 1.0,
+			different_weight: -10.0,
+			size_weight: -0.5,
+			size_pow: 1.5,
+
+			symmetry: Symmetry::None,
+		}
+	}
+}
+
+
+// names of the values returned by `LS::objectives`
+pub const OBJECTIVES: [&str; 3] = ["Matching Cells", "Mismatched Cells", "Rule Size"];
+
+// names of the values returned by `LS::descriptors`
+pub const DESCRIPTORS: [&str; 4] = ["Aspect Ratio (log2)", "Cell Count", "Rule Count", "Symmetry"];
+
+// archive axes for the descriptors, covering the usual range of each
+pub fn descriptor_axis(descriptor: usize, bins: usize) -> Axis {
+	let (min, max) = match descriptor {
+		0 => (-3.0, 3.0),
+		1 => (0.0, 200.0),
+		2 => (1.0, 11.0),
+		_ => (0.0, 1.0),
+	};
+	Axis { descriptor, min, max, bins }
+}
+
+// number of genotypes among grown agents. Agents are the same genotype when `equivalent` would say so,
+// that is when their canonical genomes or their grown states are equal, found by hashing both instead of comparing pairs
+pub fn unique_genotypes(agents: &[(LS, f32)]) -> usize {
+	fn root(parents: &mut [usize], mut i: usize) -> usize {
+		while parents[i] != i {
+			parents[i] = parents[parents[i]];
+			i = parents[i];
+		}
+		i
+	}
+
+	let mut parents: Vec<usize> = (0..agents.len()).collect();
+	let mut firsts = [HashMap::new(), HashMap::new()];
+	for (i, (agent, _)) in agents.iter().enumerate() {
+		let mut state = DefaultHasher::new();
+		agent.0.state().hash_contracted(&mut state);
+
+		for (first, hash) in firsts.iter_mut().zip([agent.genome_hash().unwrap(), state.finish()]) {
+			let other = *first.entry(hash).or_insert(i);
+			let (a, b) = (root(&mut parents, i), root(&mut parents, other));
+			parents[a] = b;
+		}
+	}
+
+	(0..agents.len()).filter(|&i| root(&mut parents, i) == i).count()
+}
+
+// mutation operators of `new_mutated_by` with their default weights. The first six keep the odds
+// of the old fixed choice at a mutation factor of 0.5 between them, the later ones are rarer.
+// Other factors shift the odds like the old choice did, see `LS::mutation_bias`
+pub const MUTATIONS: [(&str, f32); 15] = [
+	("Delete Rule", 1.0),
+	("Add Rule", 1.0),
+	("Expand Rule", 1.0),
+	("Contract Rule", 1.0),
+	("Separate Rule", 1.0),
+	("Mutate Cells", 7.6),
+	("Rotate Rule", 0.3),
+	("Mirror Rule", 0.3),
+	("Shift Origin", 0.3),
+	("Retarget Stem", 0.3),
+	("Grow Stem", 0.3),
+	("Merge Rules", 0.3),
+	("Duplicate Rule", 0.3),
+	("Mutate Axiom", 0.3),
+	("Change Step Share", 0.3),
+];
+// what operators that can't be applied fall back to
+const MUTATE_CELLS: usize = 5;
+
+// how far from the origin the axiom mutation places cells, so the axiom stays a small grid
+const AXIOM_RADIUS: i32 = 1;
+const MIN_STEP_SHARE: f32 = 0.05;
+
+// the part of a genome besides its rules: what it grows from and for how long
+#[derive(Clone)]
+pub struct Growth {
+	pub axiom: Grid,
+	pub step_share: f32, // of the run's max steps, in MIN_STEP_SHARE..=1
+}
+
+impl Growth {
+	pub fn new(axiom: Grid) -> Self {
+		Self { axiom, step_share: 1.0 }
+	}
+
+	// the steps grown for when the run allows `max_steps`, at least one if it allows any
+	pub fn steps(&self, max_steps: u16) -> u16 {
+		(max_steps as f32 * self.step_share).ceil() as u16
+	}
+}
+
+impl Default for Growth {
+	fn default() -> Self {
+		Self::new(Grid::single(Cell::Stem(0, Direction::UP)))
+	}
+}
+
+// keeps the axiom stems pointing at existing rules after the rules changed, then canonicalizes both
+fn canonicalize_genome(rules: &mut Vec<Grid>, axiom: &mut Grid) {
+	let len = rules.len();
+	for cell in axiom.contents_mut() {
+		if let Cell::Stem(n, _) = cell {
+			*n = (*n as usize % len) as u8;
+		}
+	}
+	rules.canonicalize_with(axiom);
+	axiom.contract_empty();
+}
+
+// sets a random cell near the origin of the axiom. Axioms left without a stem wouldn't grow, so those aren't kept
+fn mutate_axiom<R: Rng>(axiom: &mut Grid, stem_types: u8, rng: &mut R) -> bool {
+	let pos = [rng.gen_range(-AXIOM_RADIUS..=AXIOM_RADIUS), rng.gen_range(-AXIOM_RADIUS..=AXIOM_RADIUS)];
+	let mut mutated = axiom.clone();
+	mutated.insert_cell(Cell::random(rng, stem_types), pos);
+
+	if !mutated.contents().iter().any(|cell| matches!(cell, Cell::Stem(_, _))) {
+		return false;
+	}
+	*axiom = mutated;
+	true
+}
+
+#[derive(Clone)]
+pub struct LS(pub LSystem, pub Growth);
+
+impl LS {
+	pub fn new(rules: Vec<Grid>) -> Self {
+		Self::with_growth(rules, Growth::default())
+	}
+
+	pub fn with_growth(rules: Vec<Grid>, growth: Growth) -> Self {
+		Self(LSystem::new(growth.axiom.clone(), rules), growth)
+	}
+
+	// grows the system and returns the matching and mismatched cell counts and the total rule size
+	fn measure(&mut self, params: &EvolveParams) -> (f32, f32, f32) {
+		if params.symmetry != Symmetry::None {
+			let mut rules = self.0.rules().to_vec();
+			rules.symmetrize(params.symmetry);
+			let mut axiom = vec![self.1.axiom.clone()];
+			axiom.symmetrize(params.symmetry);
+
+			// an axiom that lost every stem wouldn't grow at all, it's kept as it is instead
+			let mut axiom = axiom.pop().unwrap();
+			if !axiom.contents().iter().any(|cell| matches!(cell, Cell::Stem(_, _))) {
+				axiom = self.1.axiom.clone();
+			}
+			let mut growth = Growth { axiom, ..self.1 };
+			canonicalize_genome(&mut rules, &mut growth.axiom);
+			*self = LS::with_growth(rules, growth);
+		}
+
+		for _ in 0..self.1.steps(params.max_steps) {
+			if !self.0.try_step() { break; }
+		}
+
+		let (same, different) = params.goal.score_simmilarity(self.0.state());
+		let mut size = 0.0;
+
+		for rule in self.0.rules() {
+			size += (rule.contents().len() as f32).powf(params.size_pow);
+		}
+
+		(same as f32, different as f32, size)
+	}
+}
+
+impl Evolve<EvolveParams> for LS {
+    fn new_random<R: Rng>(rng: &mut R) -> Self {
+		let stem_types = rng.gen_range(1..=5u8);
+		let mut rules = Vec::with_capacity(stem_types as usize);
+
+		for _ in 0..stem_types {
+			rules.push(Grid::random(rng, stem_types));
+		}
+		let mut growth = Growth::new(Grid::single(Cell::Stem(0, Direction::random(rng))));
+		// canonical like mutated genomes, so genomes with equal hashes have equal rule sizes
+		canonicalize_genome(&mut rules, &mut growth.axiom);
+
+        Self::with_growth(rules, growth)
+    }
+
+    fn reset(&mut self) {
+        self.0.set_state(self.1.axiom.clone());
+    }
+
+    fn new_mutated<R: Rng>(other: &Self, factor: f32, rng: &mut R) -> Self {
+		let weights: Vec<f32> = MUTATIONS.iter().enumerate().map(|(i, m)| m.1 * Self::mutation_bias(i, factor)).collect();
+		let operator = choose_weighted(&weights, rng);
+		Self::new_mutated_by(other, operator, factor, rng).0
+    }
+
+	fn mutations() -> Vec<(&'static str, f32)> {
+		MUTATIONS.to_vec()
+	}
+
+	// cell mutations get rarer as the factor grows, from twice their weight at 0 to almost never at 1
+	fn mutation_bias(operator: usize, factor: f32) -> f32 {
+		if operator != MUTATE_CELLS { return 1.0; }
+		((15.0 - 14.8 * factor) / 7.6).max(0.0)
+	}
+
+	fn new_mutated_by<R: Rng>(other: &Self, operator: usize, factor: f32, rng: &mut R) -> (Self, usize) {
+		let mut rules = Vec::from(other.0.rules());
+		let mut growth = other.1.clone();
+
+		let cell_factor = factor as f64 * 0.5 + 0.01;
+		let applied = match operator {
+			// the last rules can't be deleted, and none are added past `MAX_RULES`
+			0 => rules.len() > 2 && { rules.delete_rule(rng); true },
+			1 => rules.len() < MAX_RULES && { rules.add_rule(rng); true },
+			2 => { rules.expand_rule(rng); true },
+			3 => { rules.contract_rule(rng); true },
+			4 => rules.len() < MAX_RULES && { rules.separate_rule(rng); true },
+			6 => { rules.rotate_rule(rng); true },
+			7 => { rules.mirror_rule(rng); true },
+			8 => { rules.shift_rule_origin(rng); true },
+			9 => rules.retarget_stem(rng),
+			10 => rules.grow_stem(rng),
+			11 => rules.merge_rules(rng),
+			12 => rules.duplicate_rule(rng, cell_factor),
+			13 => mutate_axiom(&mut growth.axiom, rules.len() as u8, rng),
+			14 => {
+				growth.step_share = (growth.step_share * rng.gen_range(0.5..1.5)).clamp(MIN_STEP_SHARE, 1.0);
+				true
+			},
+			_ => false,
+		};
+		let operator = if applied { operator } else {
+			rules.mutate_cells(rng, cell_factor);
+			MUTATE_CELLS
+		};
+
+		canonicalize_genome(&mut rules, &mut growth.axiom);
+
+		(LS::with_growth(rules, growth), operator)
+	}
+
+    fn crossover<R: Rng>(a: &Self, b: &Self, rng: &mut R) -> Option<Self> {
+		let mut rules = Vec::from(a.0.rules());
+
+		if rng.gen_bool(0.5) {
+			rules.crossover_rules(b.0.rules(), rng);
+		}
+		else {
+			rules.crossover_cells(b.0.rules(), rng);
+		}
+		let mut growth = Growth {
+			axiom: if rng.gen_bool(0.5) { a.1.axiom.clone() } else { b.1.axiom.clone() },
+			step_share: if rng.gen_bool(0.5) { a.1.step_share } else { b.1.step_share },
+		};
+
+		canonicalize_genome(&mut rules, &mut growth.axiom);
+
+		Some(LS::with_growth(rules, growth))
+	}
+
+    fn fitness(&mut self, params: &EvolveParams) -> f32 {
+		let (same, different, size) = self.measure(params);
+		same * params.same_weight + different * params.different_weight + size * params.size_weight
+	}
+
+	// the weights are left out, only the size power still applies
+	fn objectives(&mut self, params: &EvolveParams) -> Vec<f32> {
+		let (same, different, size) = self.measure(params);
+		vec![same, -different, -size]
+	}
+
+	// measured on the bounding box of the grown body. Symmetry is the share of cells mirrored left to right
+	fn descriptors(&self, _params: &EvolveParams) -> Vec<f32> {
+		let state = self.0.state();
+
+		let mut min = [u32::MAX; 2];
+		let mut max = [0; 2];
+		for y in 0..state.height() {
+			for x in 0..state.width() {
+				if !state.at_raw([x, y]).same_type(&Cell::Empty) {
+					min = [min[0].min(x), min[1].min(y)];
+					max = [max[0].max(x), max[1].max(y)];
+				}
+			}
+		}
+		if min[0] > max[0] {
+			return vec![0.0, 0.0, self.0.rules().len() as f32, 0.0];
+		}
+
+		let mut cells = 0;
+		let mut mirrored = 0;
+		for y in min[1]..=max[1] {
+			for x in min[0]..=max[0] {
+				if state.at_raw([x, y]).same_type(&Cell::Empty) { continue; }
+				cells += 1;
+				if !state.at_raw([min[0] + max[0] - x, y]).same_type(&Cell::Empty) {
+					mirrored += 1;
+				}
+			}
+		}
+
+		let aspect = (max[0] - min[0] + 1) as f32 / (max[1] - min[1] + 1) as f32;
+		vec![aspect.log2(), cells as f32, self.0.rules().len() as f32, mirrored as f32 / cells as f32]
+	}
+
+	// cells that differ between the axioms and between rules of the same index, rules only one side has count fully
+	fn genotype_distance(&self, other: &Self) -> f32 {
+		fn differing_cells(a: &Grid, b: &Grid) -> usize {
+			a.into_iter().filter(|&(pos, cell)| cell != b.at(pos)).count() +
+			b.into_iter().filter(|&(pos, cell)| !a.contains(pos) && cell != Cell::Empty).count()
+		}
+
+		let (a, b) = (self.0.rules(), other.0.rules());
+		let mut distance = differing_cells(&self.1.axiom, &other.1.axiom);
+
+		for i in 0..a.len().max(b.len()) {
+			match (a.get(i), b.get(i)) {
+				(Some(a), Some(b)) => distance += differing_cells(a, b),
+				(Some(rule), None) | (None, Some(rule)) => {
+					distance += rule.contents().iter().filter(|c| !c.same_type(&Cell::Empty)).count();
+				},
+				(None, None) => {},
+			}
+		}
+
+		distance as f32
+	}
+
+	fn rule_count(&self) -> usize {
+		self.0.rules().len()
+	}
+
+	fn genome_hash(&self) -> Option<u64> {
+		let mut hasher = DefaultHasher::new();
+		hasher.write_u64(self.0.rules().to_vec().canonical_hash_with(&self.1.axiom));
+		hasher.write_u32(self.1.step_share.to_bits());
+		Some(hasher.finish())
+	}
+
+	// coarse occupancy around the origin: the share of filled cells in each block of a window around it
+	fn behaviour(&self, _params: &EvolveParams) -> Vec<f32> {
+		const BLOCKS: i32 = 8;
+		const BLOCK_SIZE: i32 = 4;
+		let half = BLOCKS * BLOCK_SIZE / 2;
+
+		let state = self.0.state();
+		let mut behaviour = vec![0.0; (BLOCKS * BLOCKS) as usize];
+		for y in -half..half {
+			for x in -half..half {
+				if !state.at([x, y]).same_type(&Cell::Empty) {
+					let block = (y + half) / BLOCK_SIZE * BLOCKS + (x + half) / BLOCK_SIZE;
+					behaviour[block as usize] += 1.0 / (BLOCK_SIZE * BLOCK_SIZE) as f32;
+				}
+			}
+		}
+
+		behaviour
+	}
+}
+
+impl Persist for EvolveParams {
+	fn persist(&self) -> Vec<u8> {
+		let mut data = self.goal.serialize();
+		data.extend_from_slice(&self.max_steps.to_be_bytes());
+		for weight in [self.same_weight, self.different_weight, self.size_weight, self.size_pow] {
+			data.extend_from_slice(&weight.to_be_bytes());
+		}
+		data.push(self.symmetry.to_byte());
+		data
+	}
+
+	fn restore(data: &[u8]) -> Result<(Self, usize), String> {
+		let mut reader = Reader::new(data);
+		let params = Self {
+			goal: reader.grid()?,
+			max_steps: reader.u16()?,
+			same_weight: reader.f32()?,
+			different_weight: reader.f32()?,
+			size_weight: reader.f32()?,
+			size_pow: reader.f32()?,
+			// runs saved before symmetry existed end here
+			symmetry: if reader.remaining() > 0 { Symmetry::from_byte(reader.u8()?) } else { Symmetry::None },
+		};
+		Ok((params, data.len() - reader.remaining()))
+	}
+}
+
+impl Persist for LS {
+	fn persist(&self) -> Vec<u8> {
+		let mut data = (self.0.rules().len() as u32).to_be_bytes().to_vec();
+		for rule in self.0.rules() {
+			data.extend(rule.serialize());
+		}
+		data.extend(self.1.axiom.serialize());
+		data.extend_from_slice(&self.1.step_share.to_be_bytes());
+		data
+	}
+
+	fn restore(data: &[u8]) -> Result<(Self, usize), String> {
+		let mut reader = Reader::new(data);
+		let rules = (0..reader.count(GRID_HEADER)?).map(|_| reader.grid()).collect::<Result<_, _>>()?;
+		let growth = Growth { axiom: reader.grid()?, step_share: reader.f32()? };
+		Ok((checked_genome(rules, growth)?, data.len() - reader.remaining()))
+	}
+
+	// version 1 stored only the rules, those genomes grow from the usual axiom for all steps
+	fn restore_version(data: &[u8], version: u16) -> Result<(Self, usize), String> {
+		if version > 1 { return Self::restore(data); }
+
+		let mut reader = Reader::new(data);
+		let rules = (0..reader.count(GRID_HEADER)?).map(|_| reader.grid()).collect::<Result<_, _>>()?;
+		Ok((checked_genome(rules, Growth::default())?, data.len() - reader.remaining()))
+	}
+}
+
+// genomes read from a file have to grow: at least one rule, no more than stems can name and every stem naming one of them
+fn checked_genome(rules: Vec<Grid>, growth: Growth) -> Result<LS, String> {
+	if rules.is_empty() || rules.len() > MAX_RULES {
+		return Err(format!("Agent with {} rules", rules.len()));
+	}
+	if !is_valid(&rules) || !is_valid_for(&growth.axiom, rules.len()) {
+		return Err("Agent with stems referring to missing rules".into());
+	}
+	Ok(LS::with_growth(rules, growth))
+}
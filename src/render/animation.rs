@@ -0,0 +1,241 @@
+use std::{fs::File, io::{BufWriter, Write}, path::Path};
+
+use crate::l_system::{LSystem, grid::Grid, cell::{Cell, Direction}};
+
+use super::{Image, RenderSettings, Viewport, draw_cell, draw_grid, draw_grid_lines, draw_grid_axes};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AnimationFormat {
+	Gif,
+	Apng,
+}
+
+impl AnimationFormat {
+	pub fn extension(&self) -> &'static str {
+		match self {
+			AnimationFormat::Gif => "gif",
+			AnimationFormat::Apng => "png",
+		}
+	}
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct AnimationSettings {
+	pub render: RenderSettings,
+	pub steps: u32,
+	pub frame_delay: f32, // seconds each frame is shown for
+	pub tween_frames: u32, // frames per step animated like the Grow tab, 0 for a single frame per step
+	pub final_frames: u32, // how many times the last frame is repeated before looping
+}
+
+impl Default for AnimationSettings {
+	fn default() -> Self {
+		Self {
+			render: RenderSettings::default(),
+			steps: 50,
+			frame_delay: 0.1,
+			tween_frames: 0,
+			final_frames: 10,
+		}
+	}
+}
+
+// frames of the growth of a copy of the system, rendered one at a time as they're taken so only one is held.
+// All frames share the viewport of the final state
+pub struct GrowthFrames<'a> {
+	settings: &'a AnimationSettings,
+	viewport: Viewport,
+	blank: Image, // background every frame is drawn over
+	system: LSystem,
+	// state before the step being animated, with the rule, position and direction of the stem that grew it
+	step: Option<(Grid, u8, [i32; 2], Direction)>,
+	tween: u32, // frames of the current step rendered so far
+	steps_left: u32,
+	started: bool,
+	last: Option<Image>, // the final state, repeated at the end
+	remaining: usize,
+}
+
+// fails when the frames would be too large to allocate
+pub fn growth_frames<'a>(system: &LSystem, settings: &'a AnimationSettings) -> Result<GrowthFrames<'a>, String> {
+	let mut final_system = system.clone();
+	let mut steps = 0;
+	while steps < settings.steps && final_system.try_step() {
+		steps += 1;
+	}
+
+	let viewport = Viewport::of(final_system.state());
+	let blank = Image::for_viewport(&viewport, &settings.render)?;
+
+	Ok(GrowthFrames {
+		settings,
+		viewport,
+		blank,
+		system: system.clone(),
+		step: None,
+		tween: 0,
+		steps_left: steps,
+		started: false,
+		last: None,
+		remaining: 1 + steps as usize * settings.tween_frames.max(1) as usize + settings.final_frames as usize,
+	})
+}
+
+impl Iterator for GrowthFrames<'_> {
+	type Item = Image;
+
+	fn next(&mut self) -> Option<Image> {
+		if self.remaining == 0 { return None; }
+		self.remaining -= 1;
+
+		let (viewport, render, blank) = (&self.viewport, &self.settings.render, &self.blank);
+		if let Some(last) = &self.last {
+			return Some(last.clone());
+		}
+
+		let frame = if !self.started {
+			self.started = true;
+			render_frame(blank, viewport, render, |image| draw_grid(image, viewport, render, self.system.state()))
+		}
+		else {
+			if self.tween == 0 {
+				let prev = self.system.state().clone();
+				let pos = *self.system.queue().front().unwrap();
+				let Cell::Stem(rule, dir) = prev.at(pos) else { panic!() };
+
+				self.system.try_step();
+				self.steps_left -= 1;
+				self.step = Some((prev, rule, pos, dir));
+			}
+
+			if self.settings.tween_frames == 0 {
+				render_frame(blank, viewport, render, |image| draw_grid(image, viewport, render, self.system.state()))
+			}
+			else {
+				self.tween += 1;
+				let t = self.tween as f32 / self.settings.tween_frames as f32;
+				let (prev, rule, pos, dir) = self.step.as_ref().unwrap();
+				let frame = render_frame(blank, viewport, render, |image| {
+					draw_grid_animated(image, viewport, render, self.system.state(), prev, &self.system.rules()[*rule as usize], *pos, *dir, t);
+				});
+				if self.tween == self.settings.tween_frames {
+					self.tween = 0;
+				}
+				frame
+			}
+		};
+
+		if self.steps_left == 0 && self.tween == 0 && self.remaining > 0 {
+			self.last = Some(frame.clone());
+		}
+		Some(frame)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.remaining, Some(self.remaining))
+	}
+}
+
+impl ExactSizeIterator for GrowthFrames<'_> {}
+
+fn render_frame<F: FnOnce(&mut Image)>(blank: &Image, viewport: &Viewport, settings: &RenderSettings, draw: F) -> Image {
+	let mut image = blank.clone();
+
+	if settings.grid_lines {
+		draw_grid_lines(&mut image, viewport, settings);
+	}
+	draw(&mut image);
+	if settings.axes {
+		draw_grid_axes(&mut image, viewport, settings);
+	}
+
+	image
+}
+
+// software counterpart of the Grow tab animation: cells placed by `rule` fly out of the stem at `from`
+#[allow(clippy::too_many_arguments)]
+pub fn draw_grid_animated(image: &mut Image, viewport: &Viewport, settings: &RenderSettings, grid: &Grid, prev_grid: &Grid, rule: &Grid, from: [i32; 2], from_dir: Direction, t: f32) {
+	let t_fast = (t*1.5).min(1.0);
+
+	let from_x = from[0] as f32;
+	let from_y = from[1] as f32;
+
+	for ([x, y], cell) in grid {
+		let old_cell = prev_grid.at([x, y]);
+		let changed = old_cell.same_type(&Cell::Empty) && !cell.same_type(&Cell::Empty);
+		let placed = !rule.at(from_dir.unrotate_coords(grid.pos_to_other_pos([x, y], from))).same_type(&Cell::Empty);
+
+		if !changed && placed {
+			draw_cell(image, viewport, settings, old_cell, x as f32, y as f32, 1.0-t_fast);
+		}
+		if !changed && !placed {
+			draw_cell(image, viewport, settings, cell, x as f32, y as f32, 1.0);
+		}
+	}
+	for ([x, y], cell) in grid {
+		let placed = !rule.at(from_dir.unrotate_coords(grid.pos_to_other_pos([x, y], from))).same_type(&Cell::Empty);
+
+		if placed {
+			let xf = from_x + (x as f32 - from_x) * smoothstep(t_fast);
+			let yf = from_y + (y as f32 - from_y) * smoothstep(t_fast);
+			draw_cell(image, viewport, settings, cell, xf, yf, ease_out_back(t));
+		}
+	}
+}
+
+pub fn smoothstep(x: f32) -> f32 {
+	3.0*x*x - 2.0*x*x*x
+}
+
+pub fn ease_out_back(x: f32) -> f32 {
+	2.70158*x*x*x - 6.40316*x*x + 4.70158*x
+}
+
+pub fn write_gif<W: Write, I: IntoIterator<Item = Image>>(frames: I, frame_delay: f32, writer: W) -> Result<(), String> {
+	let mut frames = frames.into_iter().peekable();
+	let first = frames.peek().ok_or(String::from("No frames to encode"))?;
+	let (Ok(width), Ok(height)) = (u16::try_from(first.width()), u16::try_from(first.height())) else {
+		return Err(format!("{}x{} pixels is too large for a GIF, at most {} are allowed each way", first.width(), first.height(), u16::MAX));
+	};
+
+	let mut encoder = gif::Encoder::new(writer, width, height, &[]).map_err(|e| e.to_string())?;
+	encoder.set_repeat(gif::Repeat::Infinite).map_err(|e| e.to_string())?;
+
+	for image in frames {
+		let mut pixels = image.pixels;
+		let mut frame = gif::Frame::from_rgba_speed(width, height, &mut pixels, 10);
+		frame.delay = (frame_delay * 100.0).round() as u16;
+		encoder.write_frame(&frame).map_err(|e| e.to_string())?;
+	}
+
+	Ok(())
+}
+
+// the frame count has to be known before the first frame is written
+pub fn write_apng<W: Write, I>(frames: I, frame_delay: f32, writer: W) -> Result<(), String> where I: IntoIterator<Item = Image>, I::IntoIter: ExactSizeIterator {
+	let mut frames = frames.into_iter().peekable();
+	let count = u32::try_from(frames.len()).map_err(|_| String::from("Too many frames for an APNG"))?;
+	let first = frames.peek().ok_or(String::from("No frames to encode"))?;
+
+	let mut encoder = png::Encoder::new(writer, first.width(), first.height());
+	encoder.set_color(png::ColorType::Rgba);
+	encoder.set_depth(png::BitDepth::Eight);
+	encoder.set_animated(count, 0).map_err(|e| e.to_string())?;
+	encoder.set_frame_delay((frame_delay * 1000.0).round() as u16, 1000).map_err(|e| e.to_string())?;
+
+	let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+	for image in frames {
+		writer.write_image_data(image.pixels()).map_err(|e| e.to_string())?;
+	}
+	writer.finish().map_err(|e| e.to_string())
+}
+
+pub fn save_animation<P: AsRef<Path>>(system: &LSystem, settings: &AnimationSettings, format: AnimationFormat, path: P) -> Result<(), String> {
+	let frames = growth_frames(system, settings)?;
+	let file = BufWriter::new(File::create(path).map_err(|e| e.to_string())?);
+
+	match format {
+		AnimationFormat::Gif => write_gif(frames, settings.frame_delay, file),
+		AnimationFormat::Apng => write_apng(frames, settings.frame_delay, file),
+	}
+}
@@ -0,0 +1,386 @@
+pub mod animation;
+
+use std::{f32::consts::PI, fmt::Write as _, fs::File, io::{BufWriter, Write}, path::Path};
+
+use crate::l_system::{grid::Grid, cell::Cell};
+
+pub const GRID_COL: [f32; 4] = [58.0 / 255.0, 58.0 / 255.0, 58.0 / 255.0, 1.0];
+pub const ARROW_COL: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+pub const Y_AXIS_COL: [f32; 4] = [0.0, 0.4, 0.0, 1.0];
+pub const X_AXIS_COL: [f32; 4] = [0.4, 0.0, 0.0, 1.0];
+
+// corners of the stem arrow in cell-local coordinates (y down), relative to the cell centre, for a radius of 1
+const ARROW: [[f32; 2]; 3] = [[-0.2, -0.3], [0.0, -0.45], [0.2, -0.3]];
+
+pub fn col_from_hsv(hue: f32, saturation: f32, value: f32) -> [f32; 4] {
+
+	let hue = hue + hue.floor();
+	let hue = hue % 1.0;
+	let saturation = saturation.clamp(0.0, 1.0);
+	let value = value.clamp(0.0, 1.0);
+
+    let h = (hue * 6.0) as i32;
+    let f = hue * 6.0 - h as f32;
+    let p = value * (1.0 - saturation);
+    let q = value * (1.0 - f * saturation);
+    let t = value * (1.0 - (1.0 - f) * saturation);
+
+    match h {
+      0 => [value, t, p, 1.0],
+      1 => [q, value, p, 1.0],
+      2 => [p, value, t, 1.0],
+      3 => [p, q, value, 1.0],
+      4 => [t, p, value, 1.0],
+      5 => [value, p, q, 1.0],
+	  _ => unreachable!()
+    }
+}
+
+pub fn stem_cell_col(stem: u8) -> [f32; 4] {
+	col_from_hsv(0.678 / PI * stem as f32, 0.6, 1.0)
+}
+
+pub fn stem_cell_border_col(stem: u8) -> [f32; 4] {
+	col_from_hsv(0.678 / PI * stem as f32, 0.6, 0.5)
+}
+
+pub fn cell_col(cell: &Cell) -> [f32; 4] {
+	match cell {
+		Cell::Stem(n, _) => stem_cell_col(*n),
+		Cell::Passive => [0.5, 0.5, 0.5, 1.0],
+		Cell::Empty => [0.0, 0.0, 0.0, 1.0],
+	}
+}
+
+pub fn cell_border_col(cell: &Cell) -> [f32; 4] {
+	match cell {
+		Cell::Stem(n, _) => stem_cell_border_col(*n),
+		Cell::Passive => [0.25, 0.25, 0.25, 1.0],
+		Cell::Empty => [0.0, 0.0, 0.0, 1.0],
+	}
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RenderSettings {
+	pub cell_size: u32, // in pixels for raster output, in user units for svg
+	pub grid_lines: bool,
+	pub axes: bool,
+	pub stem_arrows: bool,
+	pub background: [f32; 4],
+}
+
+impl Default for RenderSettings {
+	fn default() -> Self {
+		Self {
+			cell_size: 16,
+			grid_lines: false,
+			axes: false,
+			stem_arrows: true,
+			background: [0.0, 0.0, 0.0, 1.0],
+		}
+	}
+}
+
+// the part of the grid plane that gets rendered, in grid coordinates
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+	pub left: i32,
+	pub top: i32,
+	pub width: u32,
+	pub height: u32,
+}
+
+impl Viewport {
+	pub fn of(grid: &Grid) -> Self {
+		Self {
+			left: -(grid.shift()[0] as i32),
+			top: grid.height() as i32 - grid.shift()[1] as i32 - 1,
+			width: grid.width(),
+			height: grid.height(),
+		}
+	}
+
+	// top-left corner of the cell at grid position (x, y), in cells from the top-left of the viewport
+	fn cell_corner(&self, x: f32, y: f32) -> [f32; 2] {
+		[x - self.left as f32, self.top as f32 - y]
+	}
+}
+
+// 8-bit RGBA image, rows top to bottom
+#[derive(Clone)]
+pub struct Image {
+	width: u32,
+	height: u32,
+	pixels: Vec<u8>,
+}
+
+impl Image {
+	pub fn new(width: u32, height: u32, col: [f32; 4]) -> Result<Self, String> {
+		let too_large = || format!("An image of {width}x{height} pixels is too large");
+		let length = byte_count(width, height).ok_or_else(too_large)?;
+
+		let col = col_to_bytes(col);
+		let mut pixels = Vec::new();
+		pixels.try_reserve_exact(length).map_err(|_| too_large())?;
+		for _ in 0..length / 4 {
+			pixels.extend_from_slice(&col);
+		}
+
+		Ok(Self {
+			width,
+			height,
+			pixels,
+		})
+	}
+
+	// blank image the size of the viewport at the settings' cell size
+	pub fn for_viewport(viewport: &Viewport, settings: &RenderSettings) -> Result<Self, String> {
+		let (Some(width), Some(height)) = (viewport.width.checked_mul(settings.cell_size), viewport.height.checked_mul(settings.cell_size)) else {
+			return Err(format!("{}x{} cells of {} pixels make too large an image", viewport.width, viewport.height, settings.cell_size));
+		};
+		Self::new(width, height, settings.background)
+	}
+
+	pub fn from_rgba(width: u32, height: u32, pixels: Vec<u8>) -> Self {
+		assert!(byte_count(width, height) == Some(pixels.len()));
+		Self {
+			width,
+			height,
+			pixels,
+		}
+	}
+
+	pub fn width(&self) -> u32 {
+		self.width
+	}
+
+	pub fn height(&self) -> u32 {
+		self.height
+	}
+
+	pub fn pixels(&self) -> &[u8] {
+		&self.pixels
+	}
+
+	pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+		let i = (x as usize + y as usize * self.width as usize) * 4;
+		[self.pixels[i], self.pixels[i + 1], self.pixels[i + 2], self.pixels[i + 3]]
+	}
+
+	pub fn set_pixel(&mut self, x: u32, y: u32, col: [u8; 4]) {
+		let i = (x as usize + y as usize * self.width as usize) * 4;
+		self.pixels[i..i + 4].copy_from_slice(&col);
+	}
+
+	// fills every pixel whose centre lies inside the rectangle, coordinates in pixels
+	pub fn fill_rect(&mut self, min: [f32; 2], max: [f32; 2], col: [f32; 4]) {
+		let col = col_to_bytes(col);
+		let [x0, x1] = [pixel_span(min[0], self.width), pixel_span(max[0], self.width)];
+		let [y0, y1] = [pixel_span(min[1], self.height), pixel_span(max[1], self.height)];
+
+		for y in y0..y1 {
+			for x in x0..x1 {
+				self.set_pixel(x, y, col);
+			}
+		}
+	}
+
+	// fills every pixel whose centre lies inside the triangle, coordinates in pixels
+	pub fn fill_triangle(&mut self, v: [[f32; 2]; 3], col: [f32; 4]) {
+		let col = col_to_bytes(col);
+		let min_x = pixel_span(v[0][0].min(v[1][0]).min(v[2][0]), self.width);
+		let max_x = pixel_span(v[0][0].max(v[1][0]).max(v[2][0]), self.width);
+		let min_y = pixel_span(v[0][1].min(v[1][1]).min(v[2][1]), self.height);
+		let max_y = pixel_span(v[0][1].max(v[1][1]).max(v[2][1]), self.height);
+
+		let edge = |a: [f32; 2], b: [f32; 2], p: [f32; 2]| (b[0] - a[0]) * (p[1] - a[1]) - (b[1] - a[1]) * (p[0] - a[0]);
+		let area = edge(v[0], v[1], v[2]);
+		if area == 0.0 { return; }
+
+		for y in min_y..max_y {
+			for x in min_x..max_x {
+				let p = [x as f32 + 0.5, y as f32 + 0.5];
+				let w0 = edge(v[1], v[2], p) * area;
+				let w1 = edge(v[2], v[0], p) * area;
+				let w2 = edge(v[0], v[1], p) * area;
+				if w0 >= 0.0 && w1 >= 0.0 && w2 >= 0.0 {
+					self.set_pixel(x, y, col);
+				}
+			}
+		}
+	}
+
+	pub fn write_png<W: Write>(&self, writer: W) -> Result<(), String> {
+		let mut encoder = png::Encoder::new(writer, self.width, self.height);
+		encoder.set_color(png::ColorType::Rgba);
+		encoder.set_depth(png::BitDepth::Eight);
+
+		let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+		writer.write_image_data(&self.pixels).map_err(|e| e.to_string())
+	}
+
+	pub fn save_png<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+		let file = File::create(path).map_err(|e| e.to_string())?;
+		self.write_png(BufWriter::new(file))
+	}
+}
+
+// bytes of an RGBA image of that size, None if they can't be addressed
+fn byte_count(width: u32, height: u32) -> Option<usize> {
+	(width as usize).checked_mul(height as usize)?.checked_mul(4)
+}
+
+// index of the first pixel whose centre is at or after the coordinate
+fn pixel_span(coord: f32, size: u32) -> u32 {
+	((coord - 0.5).ceil().max(0.0) as u32).min(size)
+}
+
+fn col_to_bytes(col: [f32; 4]) -> [u8; 4] {
+	[
+		(255.0 * col[0].clamp(0.0, 1.0)) as u8,
+		(255.0 * col[1].clamp(0.0, 1.0)) as u8,
+		(255.0 * col[2].clamp(0.0, 1.0)) as u8,
+		(255.0 * col[3].clamp(0.0, 1.0)) as u8,
+	]
+}
+
+pub fn rasterize(grid: &Grid, settings: &RenderSettings) -> Result<Image, String> {
+	let viewport = Viewport::of(grid);
+	let mut image = Image::for_viewport(&viewport, settings)?;
+
+	if settings.grid_lines {
+		draw_grid_lines(&mut image, &viewport, settings);
+	}
+	draw_grid(&mut image, &viewport, settings, grid);
+	if settings.axes {
+		draw_grid_axes(&mut image, &viewport, settings);
+	}
+
+	Ok(image)
+}
+
+pub fn draw_grid(image: &mut Image, viewport: &Viewport, settings: &RenderSettings, grid: &Grid) {
+	for ([x, y], cell) in grid {
+		draw_cell(image, viewport, settings, cell, x as f32, y as f32, 1.0);
+	}
+}
+
+// same layout as the on-screen cells: a border square with a smaller square of the cell colour inside
+pub fn draw_cell(image: &mut Image, viewport: &Viewport, settings: &RenderSettings, cell: Cell, x: f32, y: f32, radius: f32) {
+	if let Cell::Empty = cell { return; }
+
+	let scale = settings.cell_size as f32;
+	let [cx, cy] = viewport.cell_corner(x, y);
+
+	let margin = (1.0 - radius) * 0.5;
+	let inner_radius = radius * 0.8;
+	let inner_margin = (1.0 - inner_radius) * 0.5;
+
+	image.fill_rect(
+		[(cx + margin) * scale, (cy + margin) * scale],
+		[(cx + margin + radius) * scale, (cy + margin + radius) * scale],
+		cell_border_col(&cell),
+	);
+	image.fill_rect(
+		[(cx + inner_margin) * scale, (cy + inner_margin) * scale],
+		[(cx + inner_margin + inner_radius) * scale, (cy + inner_margin + inner_radius) * scale],
+		cell_col(&cell),
+	);
+
+	if let Cell::Stem(_, dir) = cell {
+		if settings.stem_arrows {
+			let v = ARROW.map(|v| {
+				let [vx, vy] = dir.unrotate_coords(v);
+				[(cx + 0.5 + vx * radius) * scale, (cy + 0.5 + vy * radius) * scale]
+			});
+			image.fill_triangle(v, ARROW_COL);
+		}
+	}
+}
+
+pub fn draw_grid_lines(image: &mut Image, viewport: &Viewport, settings: &RenderSettings) {
+	let scale = settings.cell_size as f32;
+	let (width, height) = (image.width() as f32, image.height() as f32);
+
+	for x in 0..=viewport.width {
+		let x = (x as f32 * scale).min(width - 1.0);
+		image.fill_rect([x, 0.0], [x + 1.0, height], GRID_COL);
+	}
+	for y in 0..=viewport.height {
+		let y = (y as f32 * scale).min(height - 1.0);
+		image.fill_rect([0.0, y], [width, y + 1.0], GRID_COL);
+	}
+}
+
+pub fn draw_grid_axes(image: &mut Image, viewport: &Viewport, settings: &RenderSettings) {
+	let scale = settings.cell_size as f32;
+	let [x, y] = viewport.cell_corner(0.5, 0.5);
+	let [x, y] = [x * scale, (y + 1.0) * scale];
+
+	image.fill_rect([x - 1.0, 0.0], [x + 1.0, image.height() as f32], Y_AXIS_COL);
+	image.fill_rect([0.0, y - 1.0], [image.width() as f32, y + 1.0], X_AXIS_COL);
+}
+
+
+pub fn grid_to_svg(grid: &Grid, settings: &RenderSettings) -> String {
+	let viewport = Viewport::of(grid);
+	let scale = settings.cell_size;
+	let mut svg = String::new();
+
+	// writing into a String cannot fail
+	writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">"#,
+		viewport.width * scale, viewport.height * scale, viewport.width, viewport.height).unwrap();
+	writeln!(svg, r#"<rect width="100%" height="100%" {}/>"#, svg_fill(settings.background)).unwrap();
+
+	let line_width = 1.0 / scale as f32;
+
+	if settings.grid_lines {
+		for x in 0..=viewport.width {
+			writeln!(svg, r#"<line x1="{x}" y1="0" x2="{x}" y2="{}" stroke="{}" stroke-width="{line_width}"/>"#, viewport.height, svg_col(GRID_COL)).unwrap();
+		}
+		for y in 0..=viewport.height {
+			writeln!(svg, r#"<line x1="0" y1="{y}" x2="{}" y2="{y}" stroke="{}" stroke-width="{line_width}"/>"#, viewport.width, svg_col(GRID_COL)).unwrap();
+		}
+	}
+
+	for ([x, y], cell) in grid {
+		if let Cell::Empty = cell { continue; }
+		let [cx, cy] = viewport.cell_corner(x as f32, y as f32);
+
+		writeln!(svg, r#"<rect x="{cx}" y="{cy}" width="1" height="1" {}/>"#, svg_fill(cell_border_col(&cell))).unwrap();
+		writeln!(svg, r#"<rect x="{}" y="{}" width="0.8" height="0.8" {}/>"#, cx + 0.1, cy + 0.1, svg_fill(cell_col(&cell))).unwrap();
+
+		if let Cell::Stem(_, dir) = cell {
+			if settings.stem_arrows {
+				let points = ARROW.map(|v| {
+					let [vx, vy] = dir.unrotate_coords(v);
+					format!("{},{}", cx + 0.5 + vx, cy + 0.5 + vy)
+				});
+				writeln!(svg, r#"<polygon points="{}" {}/>"#, points.join(" "), svg_fill(ARROW_COL)).unwrap();
+			}
+		}
+	}
+
+	if settings.axes {
+		let [x, y] = viewport.cell_corner(0.5, 0.5);
+		let y = y + 1.0;
+		writeln!(svg, r#"<line x1="{x}" y1="0" x2="{x}" y2="{}" stroke="{}" stroke-width="{}"/>"#, viewport.height, svg_col(Y_AXIS_COL), line_width * 2.0).unwrap();
+		writeln!(svg, r#"<line x1="0" y1="{y}" x2="{}" y2="{y}" stroke="{}" stroke-width="{}"/>"#, viewport.width, svg_col(X_AXIS_COL), line_width * 2.0).unwrap();
+	}
+
+	svg.push_str("</svg>\n");
+	svg
+}
+
+pub fn save_svg<P: AsRef<Path>>(grid: &Grid, settings: &RenderSettings, path: P) -> Result<(), String> {
+	std::fs::write(path, grid_to_svg(grid, settings)).map_err(|e| e.to_string())
+}
+
+fn svg_col(col: [f32; 4]) -> String {
+	let [r, g, b, _] = col_to_bytes(col);
+	format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+fn svg_fill(col: [f32; 4]) -> String {
+	format!(r#"fill="{}" fill-opacity="{}""#, svg_col(col), col[3])
+}
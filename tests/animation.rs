@@ -0,0 +1,73 @@
+use soft_evolution::{
+	l_system::{LSystem, grid::Grid, cell::{Cell, Direction}},
+	render::{Image, RenderSettings, animation::{AnimationSettings, growth_frames, write_gif, write_apng}},
+};
+
+fn sample() -> LSystem {
+	LSystem::new(Grid::single(Cell::Stem(0, Direction::UP)), vec![Grid::from_rows(&["0^", "W"], [0, 0]).unwrap()])
+}
+
+fn settings(steps: u32, tween_frames: u32) -> AnimationSettings {
+	AnimationSettings {
+		render: RenderSettings { cell_size: 2, ..Default::default() },
+		steps,
+		tween_frames,
+		final_frames: 3,
+		..Default::default()
+	}
+}
+
+#[test]
+fn frames_are_counted_before_rendering() {
+	for (steps, tween_frames) in [(0, 0), (4, 0), (4, 5)] {
+		let settings = settings(steps, tween_frames);
+		let frames = growth_frames(&sample(), &settings).unwrap();
+		let expected = 1 + steps as usize * tween_frames.max(1) as usize + 3;
+		assert_eq!(frames.len(), expected);
+
+		let frames: Vec<Image> = frames.collect();
+		assert_eq!(frames.len(), expected);
+		// every frame shares the final viewport, the last ones repeat the grown state
+		assert!(frames.iter().all(|frame| (frame.width(), frame.height()) == (2, 2 * (steps + 1))));
+		for frame in &frames[expected - 4..] {
+			assert_eq!(frame.pixels(), frames[expected - 1].pixels());
+		}
+	}
+
+	// systems that stop growing early have fewer frames
+	let mut stopping = LSystem::new(Grid::single(Cell::Stem(0, Direction::UP)), vec![Grid::single(Cell::Passive)]);
+	assert_eq!(growth_frames(&stopping, &settings(10, 2)).unwrap().count(), 1 + 2 + 3);
+	stopping.try_step();
+	assert_eq!(growth_frames(&stopping, &settings(10, 2)).unwrap().count(), 1 + 3);
+}
+
+#[test]
+fn encoders_write_every_frame() {
+	let settings = settings(4, 2);
+
+	let mut gif_data = Vec::new();
+	write_gif(growth_frames(&sample(), &settings).unwrap(), 0.1, &mut gif_data).unwrap();
+	let mut decoder = gif::DecodeOptions::new().read_info(&gif_data[..]).unwrap();
+	let mut count = 0;
+	while decoder.read_next_frame().unwrap().is_some() {
+		count += 1;
+	}
+	assert_eq!(count, 1 + 4 * 2 + 3);
+
+	let mut png_data = Vec::new();
+	write_apng(growth_frames(&sample(), &settings).unwrap(), 0.1, &mut png_data).unwrap();
+	let reader = png::Decoder::new(&png_data[..]).read_info().unwrap();
+	assert_eq!(reader.info().animation_control().unwrap().num_frames, 1 + 4 * 2 + 3);
+}
+
+#[test]
+fn oversized_gifs_are_refused() {
+	let wide = Image::new(u16::MAX as u32 + 1, 1, [0.0, 0.0, 0.0, 1.0]).unwrap();
+	assert!(write_gif(vec![wide], 0.1, Vec::new()).is_err());
+	let tall = Image::new(1, u16::MAX as u32 + 1, [0.0, 0.0, 0.0, 1.0]).unwrap();
+	assert!(write_gif(vec![tall], 0.1, Vec::new()).is_err());
+	assert!(write_gif(Vec::new(), 0.1, Vec::new()).is_err());
+
+	let huge = AnimationSettings { render: RenderSettings { cell_size: u32::MAX, ..Default::default() }, ..settings(4, 0) };
+	assert!(growth_frames(&sample(), &huge).is_err());
+}
@@ -0,0 +1,84 @@
+use rand::{SeedableRng, Rng};
+use rand_chacha::ChaCha8Rng;
+
+use soft_evolution::{
+	genetic_algorithm::{evolve::Evolve, checkpoint::Persist},
+	l_system::{LSystem, grid::Grid, cell::Cell, is_valid},
+	ls_evolve::{LS, Growth, EvolveParams, MUTATIONS},
+};
+
+fn axiom_valid(ls: &LS) -> bool {
+	ls.1.axiom.contents().iter().all(|cell| match cell {
+		Cell::Stem(n, _) => (*n as usize) < ls.0.rules().len(),
+		_ => true,
+	})
+}
+
+#[test]
+fn offspring_keep_a_small_valid_axiom() {
+	let mut rng = ChaCha8Rng::seed_from_u64(0);
+	let mut agents: Vec<LS> = (0..20).map(|_| LS::new_random(&mut rng)).collect();
+
+	for _ in 0..2000 {
+		let parent = &agents[rng.gen_range(0..agents.len())];
+		let child = if rng.gen_bool(0.2) {
+			let other = &agents[rng.gen_range(0..agents.len())];
+			LS::crossover(parent, other, &mut rng).unwrap()
+		}
+		else {
+			LS::new_mutated_by(parent, rng.gen_range(0..MUTATIONS.len()), 0.5, &mut rng).0
+		};
+
+		assert!(is_valid(child.0.rules()));
+		assert!(axiom_valid(&child));
+		assert!(child.1.axiom.contents().iter().any(|cell| matches!(cell, Cell::Stem(_, _))));
+		assert!(child.1.axiom.width() <= 3 && child.1.axiom.height() <= 3);
+		assert!((0.05..=1.0).contains(&child.1.step_share));
+
+		let i = rng.gen_range(0..agents.len());
+		agents[i] = child;
+	}
+
+	// the axiom does evolve
+	assert!(agents.iter().any(|agent| agent.1.axiom.contents().len() > 1));
+}
+
+#[test]
+fn agents_grow_from_their_axiom_for_their_share_of_steps() {
+	let rules = vec![Grid::from_rows(&["W0^"], [0, 0]).unwrap()];
+	let axiom = Grid::from_rows(&["0<W0>"], [2, 0]).unwrap();
+	let mut growth = Growth::new(axiom.clone());
+	growth.step_share = 0.2;
+	assert_eq!(growth.steps(25), 5);
+
+	let mut agent = LS::with_growth(rules.clone(), growth);
+	agent.fitness(&EvolveParams::new(Grid::from_rows(&["W"], [0, 0]).unwrap()));
+
+	let mut system = LSystem::new(axiom.clone(), rules);
+	for _ in 0..5 {
+		system.try_step();
+	}
+	assert_eq!(agent.0.state().to_rows(), system.state().to_rows());
+
+	agent.reset();
+	assert_eq!(agent.0.state().to_rows(), axiom.to_rows());
+}
+
+#[test]
+fn growth_is_part_of_the_genome() {
+	let rules = vec![Grid::from_rows(&["W0^"], [0, 0]).unwrap()];
+	let mut growth = Growth::new(Grid::from_rows(&["0<W0>"], [2, 0]).unwrap());
+	growth.step_share = 0.5;
+	let agent = LS::with_growth(rules.clone(), growth);
+
+	let (restored, length) = LS::restore(&agent.persist()).unwrap();
+	assert_eq!(length, agent.persist().len());
+	assert_eq!(restored.1.axiom.to_rows(), agent.1.axiom.to_rows());
+	assert_eq!(restored.1.step_share, 0.5);
+	assert_eq!(restored.genome_hash(), agent.genome_hash());
+
+	assert_ne!(LS::new(rules.clone()).genome_hash(), agent.genome_hash());
+	let mut shorter = agent.clone();
+	shorter.1.step_share = 0.25;
+	assert_ne!(shorter.genome_hash(), agent.genome_hash());
+}
@@ -0,0 +1,44 @@
+use soft_evolution::{
+	genetic_algorithm::GeneticAlgorithm,
+	l_system::grid::Grid,
+	ls_evolve::{LS, EvolveParams},
+};
+
+fn run(cache_limit: usize, multi_objective: bool) -> GeneticAlgorithm<LS, EvolveParams> {
+	let goal = Grid::from_string(".W.\nWWW\n.W.", [1, 1]).unwrap();
+	let mut gen_alg = GeneticAlgorithm::<LS, EvolveParams>::new_seeded(100, 50, 0.5, EvolveParams::new(goal), 4);
+	gen_alg.fitness_cache.limit = cache_limit;
+	gen_alg.multi_objective = multi_objective;
+	gen_alg.novelty_weight = 0.3;
+	gen_alg.fitness_cache.reset_hit_rate();
+	gen_alg.perform_generations(8);
+	gen_alg
+}
+
+#[test]
+fn cache_doesnt_change_the_run() {
+	for multi_objective in [false, true] {
+		let cached = run(10000, multi_objective);
+		let uncached = run(0, multi_objective);
+
+		assert!(cached.fitness_cache.hit_rate() > 0.0);
+		assert_eq!(uncached.fitness_cache.hit_rate(), 0.0);
+		assert!(uncached.fitness_cache.is_empty());
+
+		for ((a, fa), (b, fb)) in cached.agents().iter().zip(uncached.agents()) {
+			assert_eq!(fa, fb);
+			assert_eq!(a.0.state().to_rows(), b.0.state().to_rows());
+		}
+		assert_eq!(cached.objectives(), uncached.objectives());
+		assert_eq!(cached.novelty(), uncached.novelty());
+	}
+}
+
+#[test]
+fn cache_is_bounded_and_cleared_with_the_params() {
+	let mut gen_alg = run(30, false);
+	assert_eq!(gen_alg.fitness_cache.len(), 30);
+
+	gen_alg.params_mut().max_steps += 1;
+	assert!(gen_alg.fitness_cache.is_empty());
+}
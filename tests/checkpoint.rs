@@ -0,0 +1,202 @@
+use soft_evolution::{
+	genetic_algorithm::{islands::Islands, checkpoint::{Persist, FORMAT_VERSION}, selection::Selection},
+	l_system::{grid::Grid, cell::{Cell, Direction}},
+	ls_evolve::{LS, EvolveParams, MUTATIONS},
+};
+
+fn population(islands: &Islands<LS, EvolveParams>) -> Vec<(Vec<u8>, f32)> {
+	islands.islands().iter().flat_map(|i| i.agents().iter().map(|(agent, fitness)| (agent.persist(), *fitness))).collect()
+}
+
+#[test]
+fn resumed_run_continues_identically() {
+	let goal = Grid::from_string(".W.\nWWW\n.W.", [1, 1]).unwrap();
+	let mut islands = Islands::<LS, EvolveParams>::new_seeded(2, 60, 30, 0.5, EvolveParams::new(goal), 11);
+	islands.migration_interval = 2;
+	for island in islands.islands_mut() {
+		island.selection = Selection::Boltzmann { temperature: 2.0 };
+		island.novelty_weight = 0.3;
+		island.crossover_rate = 0.2;
+		island.mutations.adaptive = true;
+	}
+	islands.perform_generations(3);
+
+	let data = islands.serialize();
+	let mut resumed = Islands::<LS, EvolveParams>::deserialize(&data).unwrap();
+	assert_eq!(resumed.serialize(), data);
+	assert_eq!(resumed.generation_number(), 3);
+	assert_eq!(resumed.islands()[0].history().len(), 4);
+
+	islands.perform_generations(4);
+	resumed.perform_generations(4);
+	assert_eq!(population(&islands), population(&resumed));
+}
+
+#[test]
+fn rules_files_are_not_runs() {
+	let data = soft_evolution::gls_file::GlsFile::new(vec![Grid::from_string("W", [0, 0]).unwrap()]).serialize();
+	assert!(Islands::<LS, EvolveParams>::deserialize(&data).is_err());
+}
+
+fn sections(mut data: &[u8]) -> Vec<([u8; 4], Vec<u8>)> {
+	let mut sections = Vec::new();
+	while !data.is_empty() {
+		let length = u32::from_be_bytes(data[4..8].try_into().unwrap()) as usize;
+		sections.push((data[..4].try_into().unwrap(), data[8..8 + length].to_vec()));
+		data = &data[8 + length..];
+	}
+	sections
+}
+
+fn join(sections: &[([u8; 4], Vec<u8>)]) -> Vec<u8> {
+	sections.iter().flat_map(|(tag, section)| [&tag[..], &(section.len() as u32).to_be_bytes(), section].concat()).collect()
+}
+
+// the same run as version 1 wrote it: genomes of rules only and the first 13 mutation operators
+fn as_version_1(data: &[u8]) -> Vec<u8> {
+	let mut islands = sections(&data[6..]);
+	for (_, island) in islands.iter_mut().filter(|(tag, _)| tag == b"ISLE") {
+		let mut parts = sections(island);
+		for (tag, part) in &mut parts {
+			match &*tag {
+				b"AGNT" => {
+					let mut agents = part[..4].to_vec();
+					let mut rest = &part[4..];
+					while !rest.is_empty() {
+						let (agent, length) = LS::restore(&rest[4..]).unwrap();
+						agents.extend_from_slice(&rest[..4]);
+						agents.extend((agent.0.rules().len() as u32).to_be_bytes());
+						agents.extend(agent.0.rules().iter().flat_map(Grid::serialize));
+						rest = &rest[4 + length..];
+					}
+					*part = agents;
+				},
+				b"MUTS" => {
+					let mut mutations = vec![part[0]];
+					mutations.extend(13u32.to_be_bytes());
+					mutations.extend_from_slice(&part[5..5 + 13 * 24]);
+					*part = mutations;
+				},
+				_ => {},
+			}
+		}
+		*island = join(&parts);
+	}
+	[&data[..4], &1u16.to_be_bytes(), &join(&islands)].concat()
+}
+
+#[test]
+fn version_1_runs_grow_from_the_usual_axiom() {
+	let goal = Grid::from_string(".W.\nWWW\n.W.", [1, 1]).unwrap();
+	let mut islands = Islands::<LS, EvolveParams>::new_seeded(2, 40, 20, 0.5, EvolveParams::new(goal), 12);
+	islands.islands_mut()[0].mutations.weights[3] = 2.5;
+	islands.perform_generations(2);
+
+	let data = as_version_1(&islands.serialize());
+	let mut resumed = Islands::<LS, EvolveParams>::deserialize(&data).unwrap();
+	assert_eq!(resumed.generation_number(), 2);
+
+	let usual = Grid::single(Cell::Stem(0, Direction::UP)).to_rows();
+	for (old, new) in islands.islands().iter().zip(resumed.islands()) {
+		for ((a, _), (b, _)) in old.agents().iter().zip(new.agents()) {
+			assert_eq!(b.0.rules().iter().map(Grid::to_rows).collect::<Vec<_>>(), a.0.rules().iter().map(Grid::to_rows).collect::<Vec<_>>());
+			assert_eq!(b.1.axiom.to_rows(), usual);
+			assert_eq!(b.1.step_share, 1.0);
+		}
+		// the operators added since keep their defaults
+		assert_eq!(new.mutations.weights[..13], old.mutations.weights[..13]);
+		assert_eq!(new.mutations.weights[13..], MUTATIONS[13..].iter().map(|m| m.1).collect::<Vec<_>>());
+	}
+
+	resumed.perform_generations(2);
+	assert_eq!(resumed.generation_number(), 4);
+
+	// newer versions than this one, or a version 2 run missing operators, are refused
+	let mut newer = islands.serialize();
+	newer[4..6].copy_from_slice(&(FORMAT_VERSION + 1).to_be_bytes());
+	assert!(Islands::<LS, EvolveParams>::deserialize(&newer).is_err());
+	let mut missing = as_version_1(&islands.serialize());
+	missing[4..6].copy_from_slice(&FORMAT_VERSION.to_be_bytes());
+	assert!(Islands::<LS, EvolveParams>::deserialize(&missing).is_err());
+}
+
+#[test]
+fn genomes_that_cant_grow_are_refused() {
+	let rule = Grid::from_rows(&["W0^"], [0, 0]).unwrap();
+	let dangling = Grid::from_rows(&["W9^"], [0, 0]).unwrap();
+	let axiom = Grid::single(Cell::Stem(0, Direction::UP));
+	let genome = |rules: &[&Grid], axiom: &Grid| {
+		let mut data = (rules.len() as u32).to_be_bytes().to_vec();
+		data.extend(rules.iter().flat_map(|rule| rule.serialize()));
+		data.extend(axiom.serialize());
+		data.extend(1.0f32.to_be_bytes());
+		data
+	};
+
+	assert!(LS::restore(&genome(&[&rule], &axiom)).is_ok());
+	assert!(LS::restore(&genome(&[], &axiom)).is_err());
+	assert!(LS::restore(&genome(&[&rule, &dangling], &axiom)).is_err());
+	assert!(LS::restore(&genome(&[&rule], &Grid::single(Cell::Stem(1, Direction::UP)))).is_err());
+
+	// version 1 genomes are the rules alone
+	let rules_only = |rules: &[&Grid]| [&(rules.len() as u32).to_be_bytes()[..], &rules.iter().flat_map(|rule| rule.serialize()).collect::<Vec<_>>()].concat();
+	assert!(LS::restore_version(&rules_only(&[&rule]), 1).is_ok());
+	assert!(LS::restore_version(&rules_only(&[]), 1).is_err());
+	assert!(LS::restore_version(&rules_only(&[&dangling]), 1).is_err());
+}
+
+// the run with a section of every island replaced
+fn with_section(data: &[u8], name: &[u8; 4], section: &[u8]) -> Vec<u8> {
+	let mut islands = sections(&data[6..]);
+	for (_, island) in islands.iter_mut().filter(|(tag, _)| tag == b"ISLE") {
+		let mut parts = sections(island);
+		parts.retain(|(tag, _)| tag != name);
+		parts.push((*name, section.to_vec()));
+		*island = join(&parts);
+	}
+	[&data[..6], &join(&islands)].concat()
+}
+
+#[test]
+fn broken_runs_are_errors() {
+	let goal = Grid::from_string(".W.\nWWW\n.W.", [1, 1]).unwrap();
+	let mut islands = Islands::<LS, EvolveParams>::new_seeded(1, 20, 10, 0.5, EvolveParams::new(goal), 13);
+	islands.islands_mut()[0].multi_objective = true;
+	islands.islands_mut()[0].novelty_weight = 0.5;
+	islands.perform_generations(1);
+	let data = islands.serialize();
+	assert!(Islands::<LS, EvolveParams>::deserialize(&data).is_ok());
+	let refused = |name: &[u8; 4], section: &[u8]| Islands::<LS, EvolveParams>::deserialize(&with_section(&data, name, section)).is_err();
+
+	// counts far past what the sections hold
+	assert!(refused(b"AGNT", &u32::MAX.to_be_bytes()));
+	assert!(refused(b"NOVS", &u32::MAX.to_be_bytes()));
+	assert!(refused(b"OBJS", &[&1u32.to_be_bytes()[..], &u32::MAX.to_be_bytes()].concat()));
+	assert!(refused(b"HIST", &u32::MAX.to_be_bytes()));
+
+	// an agent growing a rule it doesn't have, in a run keeping no scores that would have to match
+	let plain = Islands::<LS, EvolveParams>::new_seeded(1, 20, 10, 0.5, EvolveParams::new(Grid::single(Cell::Passive)), 13).serialize();
+	let agent = |rule: &str| {
+		let mut section = 1u32.to_be_bytes().to_vec();
+		section.extend(0.5f32.to_be_bytes());
+		section.extend(1u32.to_be_bytes());
+		section.extend(Grid::from_rows(&[rule], [0, 0]).unwrap().serialize());
+		section.extend(Grid::single(Cell::Stem(0, Direction::UP)).serialize());
+		section.extend(1.0f32.to_be_bytes());
+		with_section(&plain, b"AGNT", &section)
+	};
+	assert!(Islands::<LS, EvolveParams>::deserialize(&agent("W0^")).is_ok());
+	assert!(Islands::<LS, EvolveParams>::deserialize(&agent("W9^")).is_err());
+
+	// scores for fewer agents than there are
+	let scores = |count: u32, values: &[f32]| {
+		let mut section = count.to_be_bytes().to_vec();
+		section.extend(values.iter().flat_map(|value| value.to_be_bytes()));
+		section
+	};
+	assert!(refused(b"NOVS", &scores(2, &[0.1, 0.2])));
+	let mut objectives = 2u32.to_be_bytes().to_vec();
+	objectives.extend(scores(2, &[0.1, 0.2]));
+	objectives.extend(scores(2, &[0.3, 0.4]));
+	assert!(refused(b"OBJS", &objectives));
+}
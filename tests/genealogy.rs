@@ -0,0 +1,49 @@
+use soft_evolution::{
+	genetic_algorithm::GeneticAlgorithm,
+	l_system::grid::Grid,
+	ls_evolve::{LS, EvolveParams},
+};
+
+fn params() -> EvolveParams {
+	EvolveParams::new(Grid::from_string(".W.\nWWW\n.W.", [1, 1]).unwrap())
+}
+
+#[test]
+fn lineages_lead_back_to_the_first_generation() {
+	let mut gen_alg = GeneticAlgorithm::<LS, EvolveParams>::new_seeded(100, 50, 0.5, params(), 6);
+	gen_alg.crossover_rate = 0.3;
+	gen_alg.perform_generations(10);
+
+	for (&id, (agent, _)) in gen_alg.ids().iter().zip(gen_alg.agents()) {
+		let lineage = gen_alg.genealogy().lineage(id);
+		assert_eq!(lineage[0].id, id);
+		assert_eq!(lineage[0].agent.0.rules().len(), agent.0.rules().len());
+		// ancestors keep their genome, not what it grew into
+		assert!(lineage.iter().all(|ancestor| ancestor.agent.0.state().to_rows() == ancestor.agent.1.axiom.to_rows()));
+
+		for pair in lineage.windows(2) {
+			assert!(pair[0].generation > pair[1].generation);
+			assert!(pair[0].parents.contains(&pair[1].id));
+			assert!(pair[0].parents.len() <= 2);
+		}
+		let root = lineage.last().unwrap();
+		assert_eq!((root.generation, root.origin), (0, "random"));
+		assert!(root.parents.is_empty());
+	}
+
+	// only ancestors of the living are kept
+	assert!(gen_alg.genealogy().len() < 100 * 11);
+}
+
+#[test]
+fn seeded_lineages_lead_back_to_the_seed() {
+	let seed = LS::new(vec![Grid::from_rows(&["W0^W", ".W."], [1, 0]).unwrap()]);
+	let mut gen_alg = GeneticAlgorithm::<LS, EvolveParams>::new_seeded(50, 25, 0.5, params(), 2);
+	gen_alg.reset_from_seed(&seed);
+	gen_alg.perform_generations(3);
+
+	for &id in gen_alg.ids() {
+		let root = *gen_alg.genealogy().lineage(id).last().unwrap();
+		assert_eq!((root.generation, root.origin), (0, "seed"));
+	}
+}
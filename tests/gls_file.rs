@@ -0,0 +1,158 @@
+use soft_evolution::{
+	l_system::{grid::Grid, cell::{Cell, Direction}},
+	gls_file::{GlsFile, GrowthSettings, EvolutionSettings, MAGIC, FORMAT_VERSION},
+};
+
+fn sample_rules() -> Vec<Grid> {
+	vec![
+		Grid::from_rows(&["W1<W", ".0^."], [1, 1]).unwrap(),
+		Grid::from_rows(&["WW"], [0, 0]).unwrap(),
+	]
+}
+
+fn full_file() -> GlsFile {
+	let mut file = GlsFile::new(sample_rules());
+	file.axiom = Grid::from_rows(&["0<W0>"], [2, 0]).unwrap();
+	file.goal = Some(Grid::from_rows(&["WWW", ".W."], [1, 0]).unwrap());
+	file.growth = Some(GrowthSettings { max_steps: 40 });
+	file.evolution = Some(EvolutionSettings {
+		generation_count: 200,
+		survivors_count: 20,
+		tournament_size: 3,
+		mutation_factor: 0.4,
+		same_weight: 1.0,
+		different_weight: -10.0,
+		size_weight: -0.5,
+		size_pow: 1.5,
+	});
+	file.metadata.name = "sample".into();
+	file.metadata.notes = "notes with ünïcode".into();
+	file.metadata.created = 1_700_000_000;
+	file
+}
+
+fn assert_same(a: &GlsFile, b: &GlsFile) {
+	assert_eq!(a.rules.len(), b.rules.len());
+	for (a, b) in a.rules.iter().zip(&b.rules) {
+		assert_eq!(a.to_rows(), b.to_rows());
+		assert_eq!(a.shift(), b.shift());
+	}
+	assert_eq!(a.axiom.to_rows(), b.axiom.to_rows());
+	assert_eq!(a.goal.as_ref().map(Grid::to_rows), b.goal.as_ref().map(Grid::to_rows));
+	assert_eq!(a.growth, b.growth);
+	assert_eq!(a.evolution, b.evolution);
+	assert_eq!(a.metadata, b.metadata);
+}
+
+#[test]
+fn container_round_trip_keeps_every_section() {
+	let file = full_file();
+	let data = file.serialize();
+	assert!(data.starts_with(&MAGIC));
+
+	let read = GlsFile::deserialize(&data).unwrap();
+	assert_eq!(read.version, FORMAT_VERSION);
+	assert_same(&read, &file);
+
+	// optional sections left out stay out
+	let bare = GlsFile::new(sample_rules());
+	let read = GlsFile::deserialize(&bare.serialize()).unwrap();
+	assert!(read.goal.is_none() && read.growth.is_none() && read.evolution.is_none());
+	assert_same(&read, &bare);
+}
+
+#[test]
+fn unknown_sections_are_skipped() {
+	let file = full_file();
+	let data = file.serialize();
+
+	let header = MAGIC.len() + 2;
+	let mut extended = data[..header].to_vec();
+	extended.extend_from_slice(b"NEWS");
+	extended.extend_from_slice(&5u32.to_be_bytes());
+	extended.extend_from_slice(&[1, 2, 3, 4, 5]);
+	extended.extend_from_slice(&data[header..]);
+	extended.extend_from_slice(b"LAST");
+	extended.extend_from_slice(&0u32.to_be_bytes());
+
+	assert_same(&GlsFile::deserialize(&extended).unwrap(), &file);
+}
+
+#[test]
+fn headerless_files_fall_back_to_bare_rules() {
+	let rules = sample_rules();
+	let data: Vec<u8> = rules.iter().flat_map(|rule| rule.serialize()).collect();
+
+	let file = GlsFile::deserialize(&data).unwrap();
+	assert_eq!(file.version, 0);
+	assert_same(&file, &GlsFile { version: 0, ..GlsFile::new(rules) });
+	assert_eq!(file.axiom.to_rows(), Grid::single(Cell::Stem(0, Direction::UP)).to_rows());
+}
+
+#[test]
+fn broken_files_are_errors() {
+	let data = full_file().serialize();
+
+	assert!(GlsFile::deserialize(&data[..data.len() - 3]).is_err());
+	assert!(GlsFile::deserialize(&data[..MAGIC.len() + 1]).is_err());
+	assert!(GlsFile::deserialize(&[]).is_err());
+
+	let mut newer = data.clone();
+	newer[MAGIC.len()..MAGIC.len() + 2].copy_from_slice(&(FORMAT_VERSION + 1).to_be_bytes());
+	assert!(GlsFile::deserialize(&newer).is_err());
+
+	// a section claiming more bytes than the file has
+	let mut oversized = data[..MAGIC.len() + 2].to_vec();
+	oversized.extend_from_slice(b"RULE");
+	oversized.extend_from_slice(&u32::MAX.to_be_bytes());
+	assert!(GlsFile::deserialize(&oversized).is_err());
+
+	// a file without any rules
+	let mut empty = data[..MAGIC.len() + 2].to_vec();
+	empty.extend_from_slice(b"RULE");
+	empty.extend_from_slice(&4u32.to_be_bytes());
+	empty.extend_from_slice(&0u32.to_be_bytes());
+	assert!(GlsFile::deserialize(&empty).is_err());
+}
+
+#[test]
+fn stems_pointing_past_the_rules_are_errors() {
+	// a rule growing rule 2 when there are only two
+	let mut file = full_file();
+	file.rules[1] = Grid::from_rows(&["W2^"], [0, 0]).unwrap();
+	assert!(GlsFile::deserialize(&file.serialize()).is_err());
+
+	let data: Vec<u8> = file.rules.iter().flat_map(|rule| rule.serialize()).collect();
+	assert!(GlsFile::deserialize(&data).is_err());
+
+	// an axiom growing a missing rule
+	let mut file = full_file();
+	file.axiom = Grid::from_rows(&["0<W5>"], [2, 0]).unwrap();
+	assert!(GlsFile::deserialize(&file.serialize()).is_err());
+}
+
+// saves from before files were checked, with stems growing rules that were deleted since
+const BROKEN_SAVES: [&str; 2] = ["bazgroly.gls", "test2.gls"];
+
+#[test]
+fn saved_files_load() {
+	let mut count = 0;
+	for entry in std::fs::read_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/gls_evolve/saves")).unwrap() {
+		let path = entry.unwrap().path();
+		if path.extension().is_none_or(|extension| extension != "gls") { continue; }
+
+		let data = std::fs::read(&path).unwrap();
+		if BROKEN_SAVES.iter().any(|name| path.ends_with(name)) {
+			assert!(GlsFile::deserialize(&data).is_err(), "{}", path.display());
+			continue;
+		}
+
+		let file = GlsFile::deserialize(&data).unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+		assert!(!file.rules.is_empty());
+
+		// and survive being saved in the current format
+		assert_same(&GlsFile::deserialize(&file.serialize()).unwrap(), &GlsFile { version: FORMAT_VERSION, ..file });
+		count += 1;
+	}
+	assert!(count > 0);
+}
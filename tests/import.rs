@@ -0,0 +1,173 @@
+use soft_evolution::{
+	l_system::cell::{Cell, Direction},
+	render::Image,
+	import::{ImportSettings, ColourMapping, decode_png, decode_bmp, image_to_grid},
+};
+
+const BLACK: [u8; 4] = [0, 0, 0, 255];
+const WHITE: [u8; 4] = [255, 255, 255, 255];
+const RED: [u8; 4] = [200, 0, 0, 255];
+const CLEAR: [u8; 4] = [0, 0, 0, 0];
+
+fn image(width: u32, pixels: &[[u8; 4]]) -> Image {
+	Image::from_rgba(width, pixels.len() as u32 / width, pixels.concat())
+}
+
+// uncompressed bitmap with a 40 byte info header, 8 bit ones get a palette of the distinct colours
+fn bmp(image: &Image, bits: u16, top_down: bool) -> Vec<u8> {
+	let (width, height) = (image.width(), image.height());
+	let pixels: Vec<[u8; 4]> = image.pixels().chunks_exact(4).map(|p| [p[0], p[1], p[2], p[3]]).collect();
+	let mut palette: Vec<[u8; 4]> = Vec::new();
+	if bits == 8 {
+		for p in &pixels {
+			if !palette.contains(p) { palette.push(*p); }
+		}
+	}
+
+	let row_size = (bits as u32 * width).div_ceil(32) * 4;
+	let offset = 14 + 40 + palette.len() as u32 * 4;
+	let mut data = Vec::new();
+	data.extend_from_slice(b"BM");
+	data.extend_from_slice(&(offset + row_size * height).to_le_bytes());
+	data.extend_from_slice(&[0; 4]);
+	data.extend_from_slice(&offset.to_le_bytes());
+	data.extend_from_slice(&40u32.to_le_bytes());
+	data.extend_from_slice(&(width as i32).to_le_bytes());
+	data.extend_from_slice(&(if top_down { -(height as i32) } else { height as i32 }).to_le_bytes());
+	data.extend_from_slice(&1u16.to_le_bytes());
+	data.extend_from_slice(&bits.to_le_bytes());
+	data.extend_from_slice(&[0; 16]);
+	data.extend_from_slice(&(palette.len() as u32).to_le_bytes());
+	data.extend_from_slice(&[0; 4]);
+	for [r, g, b, _] in &palette {
+		data.extend_from_slice(&[*b, *g, *r, 0]);
+	}
+
+	for y in 0..height {
+		let y = if top_down { y } else { height - 1 - y };
+		let mut row = Vec::new();
+		for x in 0..width {
+			let [r, g, b, a] = pixels[(x + y * width) as usize];
+			match bits {
+				8 => row.push(palette.iter().position(|p| *p == [r, g, b, a]).unwrap() as u8),
+				24 => row.extend_from_slice(&[b, g, r]),
+				_ => row.extend_from_slice(&[b, g, r, a]),
+			}
+		}
+		row.resize(row_size as usize, 0);
+		data.extend(row);
+	}
+	data
+}
+
+fn set_u32(data: &mut [u8], at: usize, value: u32) {
+	data[at..at + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+#[test]
+fn png_round_trip() {
+	let original = image(3, &[BLACK, WHITE, RED, CLEAR, RED, BLACK]);
+	let mut data = Vec::new();
+	original.write_png(&mut data).unwrap();
+
+	let decoded = decode_png(&data).unwrap();
+	assert_eq!((decoded.width(), decoded.height()), (3, 2));
+	assert_eq!(decoded.pixels(), original.pixels());
+
+	assert!(decode_png(&data[..data.len() / 2]).is_err());
+	assert!(decode_png(b"\x89PNG").is_err());
+}
+
+#[test]
+fn bmp_round_trip() {
+	let opaque = image(3, &[BLACK, WHITE, RED, WHITE, RED, BLACK]);
+	let translucent = image(3, &[BLACK, WHITE, RED, CLEAR, RED, BLACK]);
+
+	for (original, bits) in [(&opaque, 8), (&opaque, 24), (&translucent, 32)] {
+		for top_down in [false, true] {
+			let decoded = decode_bmp(&bmp(original, bits, top_down)).unwrap();
+			assert_eq!((decoded.width(), decoded.height()), (3, 2));
+			assert_eq!(decoded.pixels(), original.pixels(), "{bits} bit, top down {top_down}");
+		}
+	}
+}
+
+#[test]
+fn truncated_bitmaps_are_errors() {
+	let data = bmp(&image(3, &[BLACK, WHITE, RED, WHITE, RED, BLACK]), 24, false);
+
+	for length in [0, 10, 20, 30, 54, data.len() - 1] {
+		assert!(decode_bmp(&data[..length]).is_err(), "{length} bytes");
+	}
+
+	// a palette index past the palette
+	let mut data = bmp(&image(2, &[BLACK, WHITE]), 8, false);
+	let last = data.len() - 4;
+	data[last] = 9;
+	assert!(decode_bmp(&data).is_err());
+}
+
+#[test]
+fn oversized_bitmap_headers_are_errors() {
+	let data = bmp(&image(2, &[BLACK, WHITE, RED, BLACK]), 32, false);
+
+	for (width, height) in [(i32::MAX as u32, 1), (1, i32::MAX as u32), (i32::MAX as u32, i32::MAX as u32), (65536, (-65536i32) as u32)] {
+		let mut data = data.clone();
+		set_u32(&mut data, 18, width);
+		set_u32(&mut data, 22, height);
+		assert!(decode_bmp(&data).is_err(), "{width}x{height}");
+	}
+
+	let mut data = data.clone();
+	set_u32(&mut data, 10, u32::MAX);
+	assert!(decode_bmp(&data).is_err());
+
+	let mut data = bmp(&image(2, &[BLACK, WHITE, RED, BLACK]), 32, false);
+	data[28] = 16;
+	assert!(decode_bmp(&data).is_err());
+}
+
+#[test]
+fn dark_pixels_become_cells() {
+	let image = image(3, &[BLACK, WHITE, BLACK, WHITE, BLACK, CLEAR]);
+	let settings = ImportSettings { target_size: 0, ..Default::default() };
+
+	let grid = image_to_grid(&image, &settings);
+	assert_eq!(grid.to_rows(), vec!["W.W", ".W."]);
+	assert_eq!(grid.shift(), [1, 1]);
+
+	let inverted = image_to_grid(&image, &ImportSettings { invert: true, ..settings.clone() });
+	assert_eq!(inverted.to_rows(), vec![".W.", "W.."]);
+	// without the mask transparent pixels count as white
+	let unmasked = image_to_grid(&image, &ImportSettings { invert: true, alpha_mask: false, ..settings.clone() });
+	assert_eq!(unmasked.to_rows(), vec![".W.", "W.W"]);
+}
+
+#[test]
+fn mapped_colours_pick_the_closest_cell() {
+	let image = image(2, &[RED, BLACK]);
+	let stem = Cell::Stem(0, Direction::UP);
+	let settings = ImportSettings {
+		target_size: 0,
+		threshold: 0.9,
+		mapping: vec![
+			ColourMapping { colour: [255, 0, 0], cell: stem },
+			ColourMapping { colour: [0, 0, 0], cell: Cell::Passive },
+		],
+		..Default::default()
+	};
+
+	let grid = image_to_grid(&image, &settings);
+	assert_eq!(grid.at([-1, 0]), stem);
+	assert_eq!(grid.at([0, 0]), Cell::Passive);
+}
+
+#[test]
+fn large_images_are_scaled_to_the_target_size() {
+	// left half black, right half white
+	let pixels: Vec<[u8; 4]> = (0..40 * 20).map(|i| if i % 40 < 20 { BLACK } else { WHITE }).collect();
+	let grid = image_to_grid(&image(40, &pixels), &ImportSettings { target_size: 10, ..Default::default() });
+
+	assert_eq!((grid.width(), grid.height()), (10, 5));
+	assert!(grid.to_rows().iter().all(|row| row == "WWWWW....."));
+}
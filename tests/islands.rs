@@ -0,0 +1,21 @@
+use soft_evolution::{
+	genetic_algorithm::islands::Islands,
+	l_system::grid::Grid,
+	ls_evolve::{LS, EvolveParams},
+};
+
+#[test]
+fn migration_shares_the_best_agents() {
+	let goal = Grid::from_string(".W.\nWWW\n.W.", [1, 1]).unwrap();
+	let mut islands = Islands::<LS, EvolveParams>::new_seeded(3, 60, 30, 0.5, EvolveParams::new(goal), 3);
+	islands.migration_interval = 0;
+	islands.perform_generations(5);
+
+	let best = islands.best().1;
+	islands.migrate();
+
+	// in a ring every island received the best agents of another one, so the overall best reached the next island
+	let fitnesses: Vec<f32> = islands.islands().iter().map(|i| i.best().1).collect();
+	assert!(fitnesses.iter().filter(|&&f| f >= best).count() >= 2);
+	assert_eq!(islands.generation_number(), 5);
+}
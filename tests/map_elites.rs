@@ -0,0 +1,39 @@
+use soft_evolution::{
+	genetic_algorithm::map_elites::{Axis, MapElites},
+	l_system::grid::Grid,
+	ls_evolve::{LS, EvolveParams, descriptor_axis},
+};
+
+#[test]
+fn axis_clamps_to_outer_bins() {
+	let axis = Axis { descriptor: 0, min: 0.0, max: 10.0, bins: 5 };
+	assert_eq!(axis.bin(-3.0), 0);
+	assert_eq!(axis.bin(0.0), 0);
+	assert_eq!(axis.bin(4.5), 2);
+	assert_eq!(axis.bin(10.0), 4);
+	assert_eq!(axis.bin(50.0), 4);
+}
+
+#[test]
+fn elites_stay_in_their_niches() {
+	let goal = Grid::from_string(".W.\nWWW\n.W.", [1, 1]).unwrap();
+	let axes = [descriptor_axis(1, 8), descriptor_axis(2, 5)];
+	let mut archive = MapElites::<LS, EvolveParams>::new_seeded(axes, 50, 0.5, EvolveParams::new(goal), 7);
+
+	let initial = archive.coverage();
+	archive.perform_iterations(20);
+	assert!(archive.coverage() >= initial);
+
+	for y in 0..5 {
+		for x in 0..8 {
+			if let Some(elite) = archive.cell(x, y) {
+				assert_eq!(axes[0].bin(elite.descriptors[1]), x);
+				assert_eq!(axes[1].bin(elite.descriptors[2]), y);
+			}
+		}
+	}
+
+	// reshaping the archive keeps elites that still fit
+	archive.set_axes([descriptor_axis(1, 4), descriptor_axis(2, 5)]);
+	assert!(archive.elites().count() > 0);
+}
@@ -0,0 +1,103 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use soft_evolution::{
+	genetic_algorithm::{GeneticAlgorithm, evolve::Evolve, mutation::choose_weighted},
+	l_system::grid::Grid,
+	ls_evolve::{LS, EvolveParams},
+};
+
+fn params() -> EvolveParams {
+	EvolveParams::new(Grid::from_string(".W.\nWWW\n.W.", [1, 1]).unwrap())
+}
+
+#[test]
+fn zero_weights_are_never_chosen() {
+	let mut rng = ChaCha8Rng::seed_from_u64(0);
+	for _ in 0..1000 {
+		assert_ne!(choose_weighted(&[1.0, 0.0, 3.0], &mut rng), 1);
+	}
+	// no positive weight picks uniformly rather than panicking
+	assert!(choose_weighted(&[0.0, 0.0], &mut rng) < 2);
+}
+
+#[test]
+fn every_offspring_is_counted_once() {
+	let mut gen_alg = GeneticAlgorithm::<LS, EvolveParams>::new_seeded(60, 20, 0.5, params(), 3);
+	gen_alg.mutations.weights[0] = 0.0;
+	let mut uses = 0;
+	for _ in 0..5 {
+		gen_alg.perform_generation();
+		// the newborns are all still alive until the next selection
+		let born = gen_alg.ids().iter()
+			.filter(|&&id| gen_alg.genealogy().get(id).unwrap().generation == gen_alg.generation_number())
+			.count() as u64;
+		uses += born;
+		assert_eq!(gen_alg.mutations.stats().iter().map(|s| s.uses).sum::<u64>(), uses);
+	}
+
+	let stats = gen_alg.mutations.stats();
+	assert!(stats.iter().all(|s| s.improvements <= s.uses));
+	// operators only fall back to others when they can't apply, so an unweighted one is never used
+	assert_eq!(stats[0].uses, 0);
+
+	for &id in gen_alg.ids() {
+		let origin = gen_alg.genealogy().get(id).unwrap().origin;
+		assert!(origin == "random" || LS::mutations().iter().any(|(name, _)| *name == origin));
+	}
+}
+
+#[test]
+fn adaptive_weights_keep_their_total_and_every_operator() {
+	let mut gen_alg = GeneticAlgorithm::<LS, EvolveParams>::new_seeded(60, 20, 0.5, params(), 4);
+	gen_alg.mutations.adaptive = true;
+	let total: f32 = gen_alg.mutations.weights.iter().sum();
+	gen_alg.perform_generations(8);
+
+	let weights = &gen_alg.mutations.weights;
+	assert!((weights.iter().sum::<f32>() - total).abs() < total * 1e-4);
+	assert!(weights.iter().all(|&w| w >= 0.02 * total * 0.999));
+
+	gen_alg.mutations.reset_stats();
+	assert!(gen_alg.mutations.stats().iter().all(|s| s.uses == 0));
+}
+
+#[test]
+fn mutation_factor_shifts_the_operator_odds() {
+	assert_eq!(LS::mutation_bias(5, 0.5), 1.0);
+	assert!(LS::mutation_bias(5, 0.0) > 1.5 && LS::mutation_bias(5, 1.0) < 0.05);
+	assert!((0..15).filter(|&i| i != 5).all(|i| LS::mutation_bias(i, 0.9) == 1.0));
+
+	let cell_share = |factor| {
+		let mut gen_alg = GeneticAlgorithm::<LS, EvolveParams>::new_seeded(100, 50, factor, params(), 5);
+		gen_alg.perform_generations(3);
+		let stats = gen_alg.mutations.stats();
+		stats[5].uses as f32 / stats.iter().map(|s| s.uses).sum::<u64>() as f32
+	};
+	assert!(cell_share(0.1) > 2.0 * cell_share(0.9));
+}
+
+#[test]
+fn improvements_ignore_novelty() {
+	let mut gen_alg = GeneticAlgorithm::<LS, EvolveParams>::new_seeded(60, 20, 0.5, params(), 6);
+	gen_alg.novelty_weight = 1.0;
+	gen_alg.perform_generations(2);
+
+	let raw_fitness = |id: u64, gen_alg: &GeneticAlgorithm<LS, EvolveParams>| {
+		let i = gen_alg.ids().iter().position(|&i| i == id).unwrap();
+		let mut agent = gen_alg.agents()[i].0.clone();
+		agent.reset();
+		agent.fitness(gen_alg.params())
+	};
+
+	let before = gen_alg.mutations.stats().iter().map(|s| s.improvements).sum::<u64>();
+	gen_alg.perform_generation();
+	let improvements = gen_alg.mutations.stats().iter().map(|s| s.improvements).sum::<u64>() - before;
+
+	// ranked by novelty alone, yet the improvements are counted by the fitness
+	let expected = gen_alg.ids().iter()
+		.map(|&id| gen_alg.genealogy().get(id).unwrap())
+		.filter(|ancestor| ancestor.generation == gen_alg.generation_number())
+		.filter(|ancestor| raw_fitness(ancestor.id, &gen_alg) > raw_fitness(ancestor.parents[0], &gen_alg))
+		.count() as u64;
+	assert_eq!(improvements, expected);
+}
@@ -0,0 +1,31 @@
+use soft_evolution::genetic_algorithm::novelty::{distance, NoveltyArchive};
+
+#[test]
+fn outliers_are_most_novel() {
+	assert_eq!(distance(&[0.0, 3.0], &[4.0, 0.0]), 5.0);
+
+	let behaviours = vec![vec![0.0, 0.0], vec![0.1, 0.0], vec![0.0, 0.1], vec![5.0, 5.0]];
+	let mut archive = NoveltyArchive::default();
+	archive.additions = 1;
+
+	let novelty = archive.score(&behaviours, 2, false);
+	let most_novel = (0..4).max_by(|&a, &b| novelty[a].total_cmp(&novelty[b])).unwrap();
+	assert_eq!(most_novel, 3);
+
+	// once archived, the same behaviour is no longer new
+	archive.extend(&behaviours, &novelty);
+	assert_eq!(archive.len(), 1);
+	let again = archive.score(&[vec![5.0, 5.0]], 1, false);
+	assert_eq!(again, vec![0.0]);
+}
+
+#[test]
+fn scoring_from_an_index_matches_scoring_all() {
+	let behaviours = vec![vec![0.0, 0.0], vec![1.0, 0.0], vec![0.0, 2.0], vec![5.0, 5.0]];
+	let mut archive = NoveltyArchive::default();
+	archive.extend(&[vec![3.0, 3.0]], &[1.0]);
+
+	let all = archive.score(&behaviours, 2, false);
+	assert_eq!(archive.score_from(&behaviours, 2, 2, false), all[2..]);
+	assert!(archive.score_from(&behaviours, 4, 2, false).is_empty());
+}
@@ -0,0 +1,26 @@
+#![cfg(feature = "parallel")]
+
+use soft_evolution::{genetic_algorithm::GeneticAlgorithm, ls_evolve::{LS, EvolveParams}, l_system::grid::Grid};
+
+fn run(parallel: bool) -> GeneticAlgorithm<LS, EvolveParams> {
+	let goal = Grid::from_string("..W..\n..W..\nWWWWW\n..W..\n..W..", [2, 2]).unwrap();
+	let mut gen_alg = GeneticAlgorithm::new_seeded(200, 100, 0.5, EvolveParams::new(goal), 42);
+	gen_alg.parallel = parallel;
+	gen_alg.perform_generations(15);
+	gen_alg
+}
+
+#[test]
+fn parallel_matches_serial() {
+	let serial = run(false);
+	let parallel = run(true);
+
+	assert_eq!(serial.agents().len(), parallel.agents().len());
+	for ((a, fa), (b, fb)) in serial.agents().iter().zip(parallel.agents()) {
+		assert_eq!(fa.to_bits(), fb.to_bits());
+		assert_eq!(a.0.rules().len(), b.0.rules().len());
+		for (ra, rb) in a.0.rules().iter().zip(b.0.rules()) {
+			assert_eq!(ra.serialize(), rb.serialize());
+		}
+	}
+}
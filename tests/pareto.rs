@@ -0,0 +1,26 @@
+use soft_evolution::genetic_algorithm::pareto::{dominates, non_dominated_sort, crowded_fitnesses};
+
+#[test]
+fn fronts_are_ordered_by_dominance() {
+	let objectives = vec![
+		vec![1.0, 1.0],
+		vec![3.0, 1.0],
+		vec![1.0, 3.0],
+		vec![2.0, 2.0],
+		vec![0.0, 0.0],
+	];
+
+	assert!(dominates(&objectives[3], &objectives[0]));
+	assert!(!dominates(&objectives[1], &objectives[2]));
+	assert!(!dominates(&objectives[0], &objectives[0]));
+
+	let mut fronts = non_dominated_sort(&objectives);
+	fronts.iter_mut().for_each(|f| f.sort());
+	assert_eq!(fronts, vec![vec![1, 2, 3], vec![0], vec![4]]);
+
+	let fitnesses = crowded_fitnesses(&objectives);
+	assert!(fitnesses[1] >= 0.0 && fitnesses[2] >= 0.0 && fitnesses[3] >= 0.0);
+	assert!(fitnesses[0] < 0.0 && fitnesses[4] < fitnesses[0]);
+	// the extremes of a front are preferred over its middle
+	assert!(fitnesses[1] > fitnesses[3]);
+}
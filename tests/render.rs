@@ -0,0 +1,80 @@
+use soft_evolution::{
+	l_system::{grid::Grid, cell::{Cell, Direction}},
+	render::{RenderSettings, Image, rasterize, grid_to_svg, cell_col, cell_border_col, ARROW_COL, GRID_COL},
+};
+
+fn bytes(col: [f32; 4]) -> [u8; 4] {
+	col.map(|c| (255.0 * c) as u8)
+}
+
+// a passive cell above an empty one, the stem at the origin pointing up
+fn sample() -> Grid {
+	Grid::from_rows(&["W.", "0^W"], [0, 0]).unwrap()
+}
+
+#[test]
+fn rasterized_cells_land_in_their_squares() {
+	let settings = RenderSettings { cell_size: 16, ..Default::default() };
+	let image = rasterize(&sample(), &settings).unwrap();
+	assert_eq!((image.width(), image.height()), (32, 32));
+
+	let stem = Cell::Stem(0, Direction::UP);
+	// borders and insides of the passive cell (top left) and the stem (bottom left)
+	assert_eq!(image.pixel(0, 0), bytes(cell_border_col(&Cell::Passive)));
+	assert_eq!(image.pixel(8, 8), bytes(cell_col(&Cell::Passive)));
+	assert_eq!(image.pixel(0, 16), bytes(cell_border_col(&stem)));
+	assert_eq!(image.pixel(8, 28), bytes(cell_col(&stem)));
+	// the empty cell keeps the background, the arrow points up inside the stem
+	assert_eq!(image.pixel(24, 8), bytes(settings.background));
+	assert_eq!(image.pixel(7, 18), bytes(ARROW_COL));
+	assert_eq!(image.pixel(7, 30), bytes(cell_border_col(&stem)));
+
+	let plain = rasterize(&sample(), &RenderSettings { cell_size: 16, stem_arrows: false, ..Default::default() }).unwrap();
+	assert_eq!(plain.pixel(7, 18), bytes(cell_col(&stem)));
+
+	let lined = rasterize(&sample(), &RenderSettings { cell_size: 16, grid_lines: true, ..Default::default() }).unwrap();
+	assert_eq!(lined.pixel(16, 8), bytes(GRID_COL));
+	assert_eq!(lined.pixel(31, 8), bytes(GRID_COL));
+}
+
+#[test]
+fn png_output_decodes_to_the_same_pixels() {
+	let image = rasterize(&sample(), &RenderSettings { cell_size: 3, ..Default::default() }).unwrap();
+
+	let mut data = Vec::new();
+	image.write_png(&mut data).unwrap();
+
+	let mut reader = png::Decoder::new(&data[..]).read_info().unwrap();
+	let mut pixels = vec![0; reader.output_buffer_size()];
+	let info = reader.next_frame(&mut pixels).unwrap();
+
+	assert_eq!((info.width, info.height), (6, 6));
+	assert_eq!(info.color_type, png::ColorType::Rgba);
+	assert_eq!(&pixels[..info.buffer_size()], image.pixels());
+	assert_eq!(Image::from_rgba(6, 6, pixels).pixels(), image.pixels());
+}
+
+#[test]
+fn oversized_images_are_errors() {
+	assert!(Image::new(u32::MAX, u32::MAX, [0.0, 0.0, 0.0, 1.0]).is_err());
+	// two cells across at this size overflow the width
+	assert!(rasterize(&sample(), &RenderSettings { cell_size: u32::MAX / 2 + 1, ..Default::default() }).is_err());
+}
+
+#[test]
+fn svg_has_a_shape_per_cell() {
+	let settings = RenderSettings { cell_size: 10, ..Default::default() };
+	let svg = grid_to_svg(&sample(), &settings);
+
+	assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="20" height="20" viewBox="0 0 2 2">"#));
+	assert!(svg.trim_end().ends_with("</svg>"));
+	// the background, then a border and an inside for each of the three cells
+	assert_eq!(svg.matches("<rect").count(), 1 + 2 * 3);
+	assert_eq!(svg.matches("<polygon").count(), 1);
+	assert_eq!(svg.matches("<line").count(), 0);
+	assert!(svg.contains(r#"<rect x="0" y="0" width="1" height="1""#));
+
+	let svg = grid_to_svg(&sample(), &RenderSettings { grid_lines: true, axes: true, stem_arrows: false, ..settings });
+	assert_eq!(svg.matches("<line").count(), 3 + 3 + 2);
+	assert_eq!(svg.matches("<polygon").count(), 0);
+}
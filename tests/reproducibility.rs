@@ -0,0 +1,44 @@
+use soft_evolution::{
+	genetic_algorithm::{GeneticAlgorithm, checkpoint::Persist, islands::Islands, selection::Selection},
+	l_system::grid::Grid,
+	ls_evolve::{LS, EvolveParams},
+};
+
+fn goal() -> Grid {
+	Grid::from_rows(&["..WW..", ".WWWW.", "WWWWWW", ".WWWW."], [3, 1]).unwrap()
+}
+
+fn population(gen_alg: &GeneticAlgorithm<LS, EvolveParams>) -> Vec<(Vec<u8>, f32)> {
+	gen_alg.agents().iter().map(|(agent, fitness)| (agent.persist(), *fitness)).collect()
+}
+
+fn run(seed: u64) -> GeneticAlgorithm<LS, EvolveParams> {
+	let mut gen_alg = GeneticAlgorithm::new_seeded(80, 40, 0.5, EvolveParams::new(goal()), seed);
+	gen_alg.crossover_rate = 0.3;
+	gen_alg.novelty_weight = 0.2;
+	gen_alg.selection = Selection::Rank;
+	gen_alg.mutations.adaptive = true;
+	gen_alg.perform_generations(15);
+	gen_alg
+}
+
+#[test]
+fn same_seed_evolves_the_same_population() {
+	let (a, b) = (run(7), run(7));
+	assert_eq!(population(&a), population(&b));
+	assert_eq!(a.ids(), b.ids());
+
+	assert_ne!(population(&a), population(&run(8)));
+}
+
+#[test]
+fn same_seed_evolves_the_same_islands() {
+	let run = |seed| {
+		let mut islands = Islands::<LS, EvolveParams>::new_seeded(3, 40, 20, 0.5, EvolveParams::new(goal()), seed);
+		islands.perform_generations(12);
+		islands.islands().iter().map(population).collect::<Vec<_>>()
+	};
+
+	assert_eq!(run(5), run(5));
+	assert_ne!(run(5), run(6));
+}
@@ -0,0 +1,322 @@
+use rand::{SeedableRng, Rng};
+use rand_chacha::ChaCha8Rng;
+
+use soft_evolution::{
+	genetic_algorithm::evolve::Evolve,
+	l_system::{LSystem, grid::Grid, cell::{Cell, Direction}, is_valid, ruleset::{Ruleset, equivalent, MAX_RULES}},
+	ls_evolve::{LS, EvolveParams, unique_genotypes, MUTATIONS},
+};
+
+fn random_rules(rng: &mut ChaCha8Rng) -> Vec<Grid> {
+	let len = rng.gen_range(1..=5u8);
+	(0..len).map(|_| Grid::random(rng, len)).collect()
+}
+
+#[test]
+fn crossover_keeps_stems_valid() {
+	let mut rng = ChaCha8Rng::seed_from_u64(0);
+
+	for _ in 0..500 {
+		let mut a = random_rules(&mut rng);
+		let b = random_rules(&mut rng);
+
+		if rng.gen_bool(0.5) {
+			a.crossover_rules(&b, &mut rng);
+		}
+		else {
+			a.crossover_cells(&b, &mut rng);
+		}
+		assert!(is_valid(&a));
+
+		a.clear_dead_rules();
+		assert!(is_valid(&a));
+	}
+}
+
+#[test]
+fn canonical_hash_ignores_empty_borders() {
+	let mut rng = ChaCha8Rng::seed_from_u64(2);
+
+	for _ in 0..500 {
+		let rules = random_rules(&mut rng);
+		let mut contracted = rules.clone();
+		contracted.contract_empty_borders();
+		assert_eq!(rules.canonical_hash(), contracted.canonical_hash());
+	}
+
+	let a = vec![Grid::from_rows(&["..W", ".W.", "..."], [1, 1]).unwrap()];
+	let b = vec![Grid::from_rows(&["W", "."], [0, 0]).unwrap()];
+	let c = vec![Grid::from_rows(&[".W", "W."], [0, 0]).unwrap()];
+	assert_ne!(a.canonical_hash(), b.canonical_hash());
+	assert_eq!(a.canonical_hash(), c.canonical_hash());
+}
+
+fn stem_targets(rules: &[Grid]) -> Vec<u8> {
+	rules.iter().flat_map(|rule| rule.contents()).filter_map(|cell| match cell {
+		Cell::Stem(n, _) => Some(*n),
+		_ => None,
+	}).collect()
+}
+
+fn count(rules: &[Grid], wanted: Cell) -> usize {
+	rules.iter().flat_map(|rule| rule.contents()).filter(|&&cell| cell == wanted).count()
+}
+
+#[test]
+fn structural_mutations_keep_stems_valid() {
+	let mut rng = ChaCha8Rng::seed_from_u64(3);
+
+	for _ in 0..2000 {
+		let mut rules = random_rules(&mut rng);
+		match rng.gen_range(0..7) {
+			0 => rules.rotate_rule(&mut rng),
+			1 => rules.mirror_rule(&mut rng),
+			2 => rules.shift_rule_origin(&mut rng),
+			3 => { rules.retarget_stem(&mut rng); },
+			4 => { rules.grow_stem(&mut rng); },
+			5 => { rules.merge_rules(&mut rng); },
+			_ => { rules.duplicate_rule(&mut rng, 0.3); },
+		}
+		assert!(!rules.is_empty());
+		assert!(is_valid(&rules));
+
+		rules.clear_dead_rules();
+		rules.contract_empty_borders();
+		assert!(is_valid(&rules));
+	}
+}
+
+#[test]
+fn rotating_and_mirroring_keep_the_cells() {
+	let mut rng = ChaCha8Rng::seed_from_u64(4);
+
+	for _ in 0..200 {
+		let rules = random_rules(&mut rng);
+		let mut changed = rules.clone();
+		if rng.gen_bool(0.5) { changed.rotate_rule(&mut rng); } else { changed.mirror_rule(&mut rng); }
+		for cell in [Cell::Passive, Cell::Empty] {
+			assert_eq!(count(&changed, cell), count(&rules, cell));
+		}
+		let mut targets = stem_targets(&changed);
+		targets.sort();
+		let mut expected = stem_targets(&rules);
+		expected.sort();
+		assert_eq!(targets, expected);
+	}
+
+	let mut grid = Grid::from_rows(&["W0>.", ".W."], [1, 0]).unwrap();
+	grid.mirror(true);
+	assert_eq!(grid.to_rows(), Grid::from_rows(&[".0<W", ".W."], [1, 0]).unwrap().to_rows());
+	assert_eq!(grid.shift(), [1, 0]);
+	grid.mirror(true);
+	grid.mirror(false);
+	grid.mirror(false);
+	assert_eq!(grid.to_rows(), Grid::from_rows(&["W0>.", ".W."], [1, 0]).unwrap().to_rows());
+}
+
+#[test]
+fn shifting_the_origin_moves_the_cells() {
+	let grid = Grid::from_rows(&["W0^.", ".W."], [1, 0]).unwrap();
+	let mut shifted = grid.clone();
+	shifted.shift_origin(Direction::RIGHT);
+	assert_eq!(shifted.at([-1, 0]), Cell::Passive);
+	assert_eq!(shifted.at([-1, 1]), Cell::Stem(0, Direction::UP));
+	assert_eq!(shifted.at([0, 0]), Cell::Empty);
+
+	shifted.shift_origin(Direction::LEFT);
+	assert_eq!(vec![shifted].canonical_hash(), vec![grid].canonical_hash());
+}
+
+#[test]
+fn retargeting_changes_one_stem() {
+	let mut rng = ChaCha8Rng::seed_from_u64(5);
+	let mut rules = vec![
+		Grid::from_rows(&["0^1>"], [0, 0]).unwrap(),
+		Grid::from_rows(&["W1<"], [0, 0]).unwrap(),
+	];
+	let before = stem_targets(&rules);
+	assert!(rules.retarget_stem(&mut rng));
+	let after = stem_targets(&rules);
+	assert_eq!(before.iter().zip(&after).filter(|(a, b)| a != b).count(), 1);
+
+	let mut single = vec![Grid::from_rows(&["0^W"], [0, 0]).unwrap()];
+	assert!(!single.retarget_stem(&mut rng));
+	let mut stemless = vec![Grid::from_rows(&["W"], [0, 0]).unwrap(), Grid::from_rows(&["W"], [0, 0]).unwrap()];
+	assert!(!stemless.retarget_stem(&mut rng));
+}
+
+#[test]
+fn growing_a_stem_adds_its_rule() {
+	let mut rng = ChaCha8Rng::seed_from_u64(6);
+	let mut rules = vec![Grid::from_rows(&["WW"], [0, 0]).unwrap()];
+	assert!(rules.grow_stem(&mut rng));
+	assert_eq!(rules.len(), 2);
+	assert_eq!(rules[0].contents().iter().filter(|&&cell| cell == Cell::Passive).count(), 1);
+	assert_eq!(stem_targets(&rules[..1]), vec![1]);
+
+	let mut empty = vec![Grid::from_rows(&["0^"], [0, 0]).unwrap()];
+	assert!(!empty.grow_stem(&mut rng));
+}
+
+#[test]
+fn merging_joins_nearly_identical_rules() {
+	let mut rng = ChaCha8Rng::seed_from_u64(7);
+	let mut rules = vec![
+		Grid::from_rows(&["1^2>"], [0, 0]).unwrap(),
+		Grid::from_rows(&["WWW", ".W."], [1, 0]).unwrap(),
+		Grid::from_rows(&["WW.", ".W."], [1, 0]).unwrap(),
+	];
+	assert!(rules.merge_rules(&mut rng));
+	assert_eq!(rules.len(), 2);
+	assert_eq!(stem_targets(&rules), vec![1, 1]);
+
+	let mut distinct = vec![
+		Grid::from_rows(&["1^"], [0, 0]).unwrap(),
+		Grid::from_rows(&["WWW", "WWW"], [1, 0]).unwrap(),
+	];
+	assert!(!distinct.merge_rules(&mut rng));
+}
+
+#[test]
+fn duplicating_mutates_only_the_copy() {
+	let mut rng = ChaCha8Rng::seed_from_u64(8);
+	let original = Grid::from_rows(&["WWWW", "W.WW", "WWWW"], [1, 1]).unwrap();
+	let mut rules = vec![Grid::from_rows(&["1^1>"], [0, 0]).unwrap(), original.clone()];
+	assert!(rules.duplicate_rule(&mut rng, 1.0));
+
+	assert_eq!(rules.len(), 3);
+	assert_eq!(rules[1].to_rows(), original.to_rows());
+	let mut targets = stem_targets(&rules[..1]);
+	targets.sort();
+	assert_eq!(targets, vec![1, 2]);
+	assert!(is_valid(&rules));
+}
+
+#[test]
+fn full_rulesets_get_no_more_rules() {
+	let mut rng = ChaCha8Rng::seed_from_u64(10);
+	// every rule has passive cells and a stem of the next one, the last one of rule 0
+	let full: Vec<Grid> = (0..MAX_RULES).map(|i| {
+		let mut rule = Grid::from_rows(&["W0^W"], [1, 0]).unwrap();
+		rule.contents_mut()[1] = Cell::Stem(((i + 1) % MAX_RULES) as u8, Direction::UP);
+		rule
+	}).collect();
+	assert!(is_valid(&full));
+
+	let mut rules = full.clone();
+	assert!(!rules.grow_stem(&mut rng));
+	assert!(!rules.duplicate_rule(&mut rng, 0.5));
+	rules.add_rule(&mut rng);
+	rules.separate_rule(&mut rng);
+	assert_eq!(rules.len(), MAX_RULES);
+
+	// one less still grows to the limit
+	let mut rules = full[..MAX_RULES - 1].to_vec();
+	rules[MAX_RULES - 2].contents_mut()[1] = Cell::Stem(0, Direction::UP);
+	assert!(rules.grow_stem(&mut rng));
+	assert_eq!(rules.len(), MAX_RULES);
+	assert!(is_valid(&rules));
+
+	// and every operator leaves a full genome valid
+	let agent = LS::new(full);
+	for operator in 0..MUTATIONS.len() {
+		let (mutant, _) = LS::new_mutated_by(&agent, operator, 0.5, &mut rng);
+		assert!(mutant.0.rules().len() <= MAX_RULES);
+		assert!(is_valid(mutant.0.rules()));
+	}
+}
+
+fn grown(rules: Vec<Grid>, steps: usize) -> Grid {
+	let mut system = LSystem::new(Grid::single(Cell::Stem(0, Direction::UP)), rules);
+	for _ in 0..steps {
+		if !system.try_step() { break; }
+	}
+	system.state().clone()
+}
+
+#[test]
+fn canonical_form_ignores_numbering_padding_and_unreachable_rules() {
+	let rules = vec![
+		Grid::from_rows(&["..", "2^W"], [0, 0]).unwrap(),
+		Grid::from_rows(&["W"], [0, 0]).unwrap(), // unreachable
+		Grid::from_rows(&["3>W"], [1, 0]).unwrap(),
+		Grid::from_rows(&["WW"], [0, 0]).unwrap(),
+	];
+	let mut canonical = rules.clone();
+	canonical.canonicalize();
+
+	assert_eq!(canonical.len(), 3);
+	assert_eq!(canonical[0].to_rows(), vec!["1^W"]);
+	assert_eq!(canonical[1].to_rows(), vec!["2>W"]);
+	assert_eq!(canonical[2].to_rows(), vec!["WW"]);
+	assert_eq!(rules.canonical_hash(), canonical.canonical_hash());
+	assert!(equivalent(&rules, &canonical, 0));
+
+	// the stems of the axiom are discovered first and renumbered with the rules
+	let mut rules = vec![Grid::from_rows(&["W"], [0, 0]).unwrap(), Grid::from_rows(&["0^"], [0, 0]).unwrap()];
+	let mut axiom = Grid::from_rows(&["1<"], [0, 0]).unwrap();
+	rules.canonicalize_with(&mut axiom);
+	assert_eq!(axiom.to_rows(), vec!["0<"]);
+	assert_eq!(rules[0].to_rows(), vec!["1^"]);
+}
+
+#[test]
+fn stemless_axioms_keep_the_first_rule() {
+	// nothing is reached from the axiom, rule 0 stays along with what it grows
+	let mut rules = vec![Grid::from_rows(&["W2^"], [0, 0]).unwrap(), Grid::from_rows(&["W"], [0, 0]).unwrap(), Grid::from_rows(&["0>"], [0, 0]).unwrap()];
+	let mut axiom = Grid::from_rows(&["WW"], [0, 0]).unwrap();
+	rules.canonicalize_with(&mut axiom);
+	assert_eq!(axiom.to_rows(), vec!["WW"]);
+	assert_eq!(rules.iter().map(Grid::to_rows).collect::<Vec<_>>(), vec![vec!["W1^"], vec!["0>"]]);
+	assert!(is_valid(&rules));
+}
+
+#[test]
+fn canonical_form_grows_the_same_body() {
+	let mut rng = ChaCha8Rng::seed_from_u64(9);
+
+	for _ in 0..300 {
+		let rules = random_rules(&mut rng);
+		let mut canonical = rules.clone();
+		canonical.canonicalize();
+		assert!(is_valid(&canonical));
+
+		// stem numbers differ while growing, the cells don't
+		let (a, b) = (grown(rules, 30), grown(canonical, 30));
+		for (pos, cell) in &a {
+			assert!(cell.same_type(&b.at(pos)));
+		}
+		for (pos, cell) in &b {
+			assert!(cell.same_type(&a.at(pos)));
+		}
+	}
+}
+
+#[test]
+fn rulesets_growing_the_same_body_are_equivalent() {
+	let direct = vec![Grid::from_rows(&["WW"], [0, 0]).unwrap()];
+	let detour = vec![Grid::from_rows(&["1^"], [0, 0]).unwrap(), Grid::from_rows(&["WW"], [0, 0]).unwrap()];
+	let other = vec![Grid::from_rows(&["W", "W"], [0, 0]).unwrap()];
+
+	assert_ne!(direct.canonical_hash(), detour.canonical_hash());
+	assert!(equivalent(&direct, &detour, 10));
+	assert!(!equivalent(&direct, &other, 10));
+	// not grown far enough to tell
+	assert!(!equivalent(&direct, &detour, 1));
+}
+
+#[test]
+fn unique_genotypes_counts_equivalent_agents_once() {
+	let params = EvolveParams::new(Grid::from_rows(&["W"], [0, 0]).unwrap());
+	let mut agents: Vec<(LS, f32)> = [
+		vec![Grid::from_rows(&["WW"], [0, 0]).unwrap()],
+		vec![Grid::from_rows(&["WW", ".."], [0, 1]).unwrap()],
+		vec![Grid::from_rows(&["1^"], [0, 0]).unwrap(), Grid::from_rows(&["WW"], [0, 0]).unwrap()],
+		vec![Grid::from_rows(&["W", "W"], [0, 0]).unwrap()],
+	].into_iter().map(|rules| (LS::new(rules), 0.0)).collect();
+	for (agent, fitness) in &mut agents {
+		*fitness = agent.fitness(&params);
+	}
+
+	assert_eq!(unique_genotypes(&agents), 2);
+}
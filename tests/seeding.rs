@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+
+use soft_evolution::{
+	genetic_algorithm::{GeneticAlgorithm, evolve::Evolve, islands::Islands},
+	l_system::{grid::Grid, is_valid},
+	ls_evolve::{LS, EvolveParams},
+};
+
+fn params() -> EvolveParams {
+	EvolveParams::new(Grid::from_string(".W.\nWWW\n.W.", [1, 1]).unwrap())
+}
+
+fn seed() -> LS {
+	LS::new(vec![Grid::from_rows(&["W0^W", ".W."], [1, 0]).unwrap()])
+}
+
+#[test]
+fn reset_from_seed_starts_a_generation_of_mutants() {
+	let seed = seed();
+	let mut gen_alg = GeneticAlgorithm::<LS, EvolveParams>::new_seeded(50, 25, 0.5, params(), 1);
+	gen_alg.perform_generations(3);
+	gen_alg.reset_from_seed(&seed);
+
+	assert_eq!(gen_alg.generation_number(), 0);
+	assert_eq!(gen_alg.history().len(), 1);
+	assert_eq!(gen_alg.agents().len(), 50);
+	assert!(gen_alg.agents().iter().any(|(agent, _)| agent.genome_hash() == seed.genome_hash()));
+	assert!(gen_alg.agents().iter().all(|(agent, _)| is_valid(agent.0.rules())));
+	assert!(gen_alg.agents().windows(2).all(|w| w[0].1 >= w[1].1));
+
+	gen_alg.perform_generations(3);
+	assert_eq!(gen_alg.generation_number(), 3);
+}
+
+#[test]
+fn injected_agents_replace_the_worst() {
+	let seed = seed();
+	let mut islands = Islands::<LS, EvolveParams>::new_seeded(2, 50, 25, 0.5, params(), 1);
+	islands.inject(&[seed.clone(), seed.clone()]);
+
+	for island in islands.islands() {
+		assert_eq!(island.agents().len(), 50);
+		assert_eq!(island.agents().iter().filter(|(agent, _)| agent.genome_hash() == seed.genome_hash()).count(), 2);
+	}
+}
+
+#[test]
+fn injection_only_scores_the_newcomers() {
+	let seed = seed();
+	for multi_objective in [false, true] {
+		let mut gen_alg = GeneticAlgorithm::<LS, EvolveParams>::new_seeded(50, 25, 0.5, params(), 2);
+		gen_alg.novelty_weight = 0.4;
+		gen_alg.multi_objective = multi_objective;
+		gen_alg.perform_generations(3);
+
+		let archived = gen_alg.novelty_archive.len();
+		let kept: HashMap<u64, (f32, Vec<f32>)> = gen_alg.ids().iter().enumerate()
+			.map(|(i, &id)| (id, (gen_alg.novelty()[i], gen_alg.objectives().get(i).cloned().unwrap_or_default())))
+			.collect();
+
+		gen_alg.inject(vec![seed.clone(), seed.clone()]);
+
+		// the archive only grows once per generation, the others keep their novelty and objectives
+		assert_eq!(gen_alg.novelty_archive.len(), archived);
+		assert_eq!(gen_alg.novelty().len(), 50);
+		assert_eq!(gen_alg.objectives().len(), if multi_objective { 50 } else { 0 });
+		let mut injected = 0;
+		for (i, id) in gen_alg.ids().iter().enumerate() {
+			match kept.get(id) {
+				Some((novelty, objectives)) => {
+					assert_eq!(gen_alg.novelty()[i], *novelty);
+					assert_eq!(gen_alg.objectives().get(i).cloned().unwrap_or_default(), *objectives);
+				},
+				None => injected += 1,
+			}
+		}
+		assert_eq!(injected, 2);
+		assert!(gen_alg.agents().windows(2).all(|w| w[0].1 >= w[1].1));
+
+		gen_alg.perform_generation();
+		assert!(gen_alg.novelty_archive.len() > archived);
+	}
+}
@@ -0,0 +1,103 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use soft_evolution::{
+	genetic_algorithm::{GeneticAlgorithm, checkpoint::Persist, selection::Selection},
+	l_system::grid::Grid,
+	ls_evolve::{LS, EvolveParams},
+};
+
+const FITNESSES: [f32; 8] = [3.0, -1.0, 7.0, 0.5, 7.0, -20.0, 2.0, 4.0];
+
+fn strategies() -> Vec<Selection> {
+	vec![
+		Selection::Tournament { size: 3, replacement: false },
+		Selection::Tournament { size: 3, replacement: true },
+		Selection::Roulette,
+		Selection::Rank,
+		Selection::Truncation,
+		Selection::Boltzmann { temperature: 2.0 },
+	]
+}
+
+fn unique(selected: &[usize]) -> bool {
+	selected.iter().enumerate().all(|(i, a)| !selected[..i].contains(a))
+}
+
+#[test]
+fn selected_indices_stay_in_bounds() {
+	let mut rng = ChaCha8Rng::seed_from_u64(1);
+	for selection in strategies() {
+		for count in [0, 1, 5, 8, 20] {
+			let selected = selection.select(&FITNESSES, count, &mut rng);
+			assert!(selected.iter().all(|&i| i < FITNESSES.len()), "{selection:?}");
+
+			// truncation and tournaments without replacement run out of agents
+			let exhausting = matches!(selection, Selection::Truncation | Selection::Tournament { replacement: false, .. });
+			if exhausting {
+				assert_eq!(selected.len(), count.min(FITNESSES.len()), "{selection:?}");
+				assert!(unique(&selected), "{selection:?}");
+			}
+			else {
+				assert_eq!(selected.len(), count, "{selection:?}");
+			}
+		}
+		assert!(selection.select(&[], 5, &mut rng).is_empty());
+	}
+}
+
+#[test]
+fn truncation_keeps_the_fittest() {
+	let mut rng = ChaCha8Rng::seed_from_u64(2);
+	let mut selected = Selection::Truncation.select(&FITNESSES, 4, &mut rng);
+	selected.sort();
+	assert_eq!(selected, vec![0, 2, 4, 7]);
+}
+
+#[test]
+fn tournaments_favour_the_fittest() {
+	let mut rng = ChaCha8Rng::seed_from_u64(3);
+
+	// the worst agent only wins a tournament it fights alone
+	let selected = Selection::Tournament { size: 2, replacement: true }.select(&FITNESSES, 1000, &mut rng);
+	let worst = selected.iter().filter(|&&i| i == 5).count();
+	assert!(worst < 1000 / 8 / 2, "{worst}");
+
+	// contenders are drawn with replacement, so huge tournaments are won by the best
+	let selected = Selection::Tournament { size: 200, replacement: true }.select(&FITNESSES, 50, &mut rng);
+	assert!(selected.iter().all(|&i| FITNESSES[i] == 7.0));
+
+	// without replacement, the last draws take whatever is left
+	let mut selected = Selection::Tournament { size: 200, replacement: false }.select(&FITNESSES, 8, &mut rng);
+	assert_eq!(FITNESSES[selected[0]], 7.0);
+	selected.sort();
+	assert_eq!(selected, (0..8).collect::<Vec<_>>());
+}
+
+#[test]
+fn elites_survive_unchanged() {
+	let goal = Grid::from_rows(&["..WW..", ".WWWW.", "WWWWWW", ".WWWW."], [3, 1]).unwrap();
+
+	for selection in [Selection::Roulette, Selection::Tournament { size: 2, replacement: false }, Selection::Boltzmann { temperature: 0.1 }] {
+		let mut gen_alg = GeneticAlgorithm::<LS, EvolveParams>::new_seeded(60, 10, 0.5, EvolveParams::new(goal.clone()), 4);
+		gen_alg.selection = selection;
+		gen_alg.elite_count = 5;
+
+		for _ in 0..8 {
+			let elites: Vec<(Vec<u8>, f32, u64)> = gen_alg.agents()[..5].iter().zip(gen_alg.ids())
+				.map(|((agent, fitness), &id)| (agent.persist(), *fitness, id))
+				.collect();
+			let best = gen_alg.best().1;
+
+			gen_alg.perform_generation();
+
+			for (genome, fitness, id) in &elites {
+				let i = gen_alg.ids().iter().position(|i| i == id).unwrap_or_else(|| panic!("{selection:?} lost elite {id}"));
+				assert_eq!(&gen_alg.agents()[i].0.persist(), genome);
+				assert_eq!(gen_alg.agents()[i].1, *fitness);
+			}
+			assert!(gen_alg.best().1 >= best);
+			assert_eq!(gen_alg.agents().len(), 60);
+		}
+	}
+}
@@ -0,0 +1,178 @@
+#![cfg(feature = "serde")]
+
+use std::time::Duration;
+
+use soft_evolution::{
+	l_system::{LSystem, grid::Grid, cell::{Cell, Direction}, ruleset::Symmetry},
+	gls_file::{GlsFile, EvolutionSettings},
+	genetic_algorithm::{GeneticAlgorithm, selection::Selection, stopping::StopCondition, islands::Topology},
+	ls_evolve::{LS, EvolveParams, descriptor_axis},
+};
+
+fn sample_rules() -> Vec<Grid> {
+	vec![
+		Grid::new(3, 2, vec![
+			Cell::Passive, Cell::Stem(1, Direction::LEFT), Cell::Empty,
+			Cell::Stem(0, Direction::UP), Cell::Passive, Cell::Stem(1, Direction::RIGHT),
+		], [1, 0]),
+		Grid::vertical(vec![Cell::Passive, Cell::Stem(0, Direction::DOWN), Cell::Empty], 0),
+	]
+}
+
+fn round_trip<T: serde::Serialize + serde::de::DeserializeOwned>(value: &T) -> T {
+	let json = serde_json::to_string(value).unwrap();
+	serde_json::from_str(&json).unwrap()
+}
+
+#[test]
+fn cell_round_trip() {
+	for cell in [Cell::Empty, Cell::Passive, Cell::Stem(17, Direction::LEFT), Cell::Stem(0, Direction::DOWN)] {
+		assert_eq!(round_trip(&cell), cell);
+	}
+	for dir in [Direction::UP, Direction::LEFT, Direction::DOWN, Direction::RIGHT] {
+		assert_eq!(round_trip(&dir), dir);
+	}
+}
+
+#[test]
+fn grid_round_trip_matches_binary() {
+	for grid in sample_rules() {
+		let json_grid = round_trip(&grid);
+		let (binary_grid, _) = Grid::deserialize(&grid.serialize()).unwrap();
+
+		assert_eq!(json_grid.serialize(), grid.serialize());
+		assert_eq!(json_grid.serialize(), binary_grid.serialize());
+	}
+}
+
+#[test]
+fn grid_uses_row_strings() {
+	let grid = &sample_rules()[0];
+	let json = serde_json::to_value(grid).unwrap();
+
+	assert_eq!(json["shift"], serde_json::json!([1, 0]));
+	assert_eq!(json["rows"], serde_json::json!(["0^W1>", "W1<."]));
+}
+
+#[test]
+fn grid_rejects_invalid_rows() {
+	assert!(serde_json::from_str::<Grid>(r#"{"shift":[0,0],"rows":["WW","W"]}"#).is_err());
+	assert!(serde_json::from_str::<Grid>(r#"{"shift":[0,0],"rows":["W3"]}"#).is_err());
+	assert!(serde_json::from_str::<Grid>(r#"{"shift":[2,0],"rows":["WW"]}"#).is_err());
+}
+
+#[test]
+fn lsystem_round_trip() {
+	let mut system = LSystem::new(Grid::single(Cell::Stem(0, Direction::UP)), sample_rules());
+	for _ in 0..5 {
+		system.try_step();
+	}
+
+	let mut copy = round_trip(&system);
+
+	assert_eq!(copy.state().serialize(), system.state().serialize());
+	assert_eq!(copy.queue(), system.queue());
+	assert_eq!(copy.rules().len(), system.rules().len());
+
+	// both continue growing identically
+	for _ in 0..5 {
+		assert_eq!(copy.try_step(), system.try_step());
+	}
+	assert_eq!(copy.state().serialize(), system.state().serialize());
+}
+
+#[test]
+fn gls_file_round_trip_matches_binary() {
+	let mut file = GlsFile::new(sample_rules());
+	file.goal = Some(Grid::from_string(".W.\nWWW\n.W.", [1, 1]).unwrap());
+	file.evolution = Some(EvolutionSettings {
+		generation_count: 1000,
+		survivors_count: 500,
+		tournament_size: 2,
+		mutation_factor: 0.5,
+		same_weight: 1.0,
+		different_weight: -10.0,
+		size_weight: -0.5,
+		size_pow: 1.5,
+	});
+	file.metadata.name = "cross".into();
+
+	let json_file = round_trip(&file);
+	let binary_file = GlsFile::deserialize(&file.serialize()).unwrap();
+
+	assert_eq!(json_file.serialize(), binary_file.serialize());
+	assert_eq!(json_file.evolution, file.evolution);
+	assert_eq!(json_file.metadata, file.metadata);
+}
+
+#[test]
+fn gls_file_rejects_rules_that_cant_grow() {
+	let file = GlsFile::new(sample_rules());
+	let json = serde_json::to_value(&file).unwrap();
+	assert!(serde_json::from_value::<GlsFile>(json.clone()).is_ok());
+
+	let mut empty = json.clone();
+	empty["rules"] = serde_json::json!([]);
+	assert!(serde_json::from_value::<GlsFile>(empty).is_err());
+
+	// the same stems the binary format turns away
+	let mut rule = json.clone();
+	rule["rules"][1]["rows"] = serde_json::json!(["W", "5v", "."]);
+	assert!(serde_json::from_value::<GlsFile>(rule).is_err());
+	let mut axiom = json;
+	axiom["axiom"]["rows"] = serde_json::json!(["2^"]);
+	assert!(serde_json::from_value::<GlsFile>(axiom).is_err());
+}
+
+#[test]
+fn lsystem_rejects_systems_that_cant_grow() {
+	let system = LSystem::new(Grid::single(Cell::Stem(1, Direction::UP)), sample_rules());
+	let mut json = serde_json::to_value(&system).unwrap();
+	assert!(serde_json::from_value::<LSystem>(json.clone()).is_ok());
+
+	json["rules"] = serde_json::json!([]);
+	assert!(serde_json::from_value::<LSystem>(json).is_err());
+
+	// stems of the state or the rules pointing past the last rule
+	let mut json = serde_json::to_value(&system).unwrap();
+	json["state"]["rows"] = serde_json::json!(["2^"]);
+	assert!(serde_json::from_value::<LSystem>(json).is_err());
+	let mut json = serde_json::to_value(&system).unwrap();
+	json["rules"][1]["rows"] = serde_json::json!(["W", "5v", "."]);
+	assert!(serde_json::from_value::<LSystem>(json).is_err());
+
+	// a queued position without a stem
+	let mut json = serde_json::to_value(&system).unwrap();
+	json["stem_queue"] = serde_json::json!([[3, 3]]);
+	assert!(serde_json::from_value::<LSystem>(json).is_err());
+}
+
+#[test]
+fn evolution_settings_round_trip() {
+	let mut params = EvolveParams::new(sample_rules()[0].clone());
+	params.max_steps = 40;
+	params.size_pow = 2.0;
+	params.symmetry = Symmetry::Rotational;
+	let copy = round_trip(&params);
+	assert_eq!(copy.goal.serialize(), params.goal.serialize());
+	assert_eq!((copy.max_steps, copy.size_pow, copy.symmetry), (40, 2.0, Symmetry::Rotational));
+
+	let mut gen_alg = GeneticAlgorithm::<LS, EvolveParams>::new_seeded(20, 10, 0.5, params, 0);
+	gen_alg.selection = Selection::Boltzmann { temperature: 0.5 };
+	gen_alg.novelty_weight = 0.3;
+	let settings = round_trip(&gen_alg.settings());
+	assert_eq!(settings, gen_alg.settings());
+
+	let mut other = GeneticAlgorithm::<LS, EvolveParams>::new_seeded(20, 10, 0.1, copy, 1);
+	other.apply_settings(settings);
+	assert_eq!(other.settings(), gen_alg.settings());
+
+	for selection in [Selection::default(), Selection::Rank, Selection::Truncation] {
+		assert_eq!(round_trip(&selection), selection);
+	}
+	for condition in [StopCondition::TimeLimit(Duration::from_millis(1500)), StopCondition::Stagnation(20)] {
+		assert_eq!(round_trip(&condition), condition);
+	}
+	assert_eq!(round_trip(&Topology::Random), Topology::Random);
+	assert_eq!(round_trip(&descriptor_axis(1, 16)), descriptor_axis(1, 16));
+}
@@ -0,0 +1,111 @@
+use rand::{SeedableRng, Rng};
+use rand_chacha::ChaCha8Rng;
+
+use soft_evolution::l_system::{LSystem, grid::Grid, cell::{Cell, Direction}, is_valid, simplify::{simplify, effective_cells, Simplification}};
+
+fn axiom() -> Grid {
+	Grid::single(Cell::Stem(0, Direction::UP))
+}
+
+fn grown(rules: &[Grid], steps: usize) -> Grid {
+	let mut system = LSystem::new(axiom(), rules.to_vec());
+	for _ in 0..steps {
+		if !system.try_step() { break; }
+	}
+	system.state().clone()
+}
+
+fn rows(rules: &[Grid]) -> Vec<Vec<String>> {
+	rules.iter().map(|rule| rule.to_rows()).collect()
+}
+
+#[test]
+fn overwritten_cells_are_removed() {
+	let mut rules = vec![
+		Grid::from_rows(&["1^W"], [0, 0]).unwrap(),
+		Grid::from_rows(&["WW"], [0, 0]).unwrap(),
+	];
+	assert_eq!(effective_cells(&rules, &axiom(), 10), vec![vec![true, false], vec![true, true]]);
+
+	let result = simplify(&mut rules, &mut axiom(), 10);
+	assert_eq!(result, Simplification { removed_cells: 1, removed_rules: 0, steps: 10, stopped: true });
+	assert_eq!(rows(&rules), vec![vec!["1^"], vec!["WW"]]);
+}
+
+#[test]
+fn rules_that_only_differ_in_overwritten_cells_are_merged() {
+	let mut rules = vec![
+		Grid::from_rows(&["1^..2^..3^"], [0, 0]).unwrap(),
+		Grid::from_rows(&["WW"], [0, 0]).unwrap(),
+		Grid::from_rows(&["WWW"], [0, 0]).unwrap(),
+		Grid::from_rows(&["WW"], [1, 0]).unwrap(),
+	];
+	let expected = grown(&rules, 10);
+
+	let result = simplify(&mut rules, &mut axiom(), 10);
+	assert_eq!(result, Simplification { removed_cells: 3, removed_rules: 1, steps: 10, stopped: true });
+	assert_eq!(rows(&rules), vec![vec!["1^..1^..2^"], vec!["WW"], vec!["WW"]]);
+	assert_eq!(rules[2].shift(), [1, 0]);
+	assert_eq!(grown(&rules, 10).to_rows(), expected.to_rows());
+}
+
+#[test]
+fn stems_killed_before_growing_drop_their_rule() {
+	let mut rules = vec![
+		Grid::from_rows(&["1^2^"], [0, 0]).unwrap(),
+		Grid::from_rows(&["WW"], [0, 0]).unwrap(),
+		Grid::from_rows(&["WWW"], [0, 0]).unwrap(),
+	];
+	let result = simplify(&mut rules, &mut axiom(), 10);
+	assert_eq!(result, Simplification { removed_cells: 4, removed_rules: 1, steps: 10, stopped: true });
+	assert_eq!(rows(&rules), vec![vec!["1^"], vec!["WW"]]);
+}
+
+#[test]
+fn results_are_checked_for_the_growth_steps() {
+	// a stem that keeps growing upwards never stops
+	let endless = vec![Grid::from_rows(&["W", "0^", "W"], [0, 1]).unwrap()];
+	let mut rules = endless.clone();
+	let result = simplify(&mut rules, &mut axiom(), 3);
+	assert_eq!((result.steps, result.stopped), (3, false));
+	assert_eq!(grown(&rules, 3).to_rows(), grown(&endless, 3).to_rows());
+
+	// rule 1 is only grown on the second step. Simplified for one, its cells are dropped
+	let finite = vec![
+		Grid::from_rows(&["1^W"], [0, 0]).unwrap(),
+		Grid::from_rows(&["WW"], [0, 0]).unwrap(),
+	];
+	let mut rules = finite.clone();
+	let result = simplify(&mut rules, &mut axiom(), 1);
+	assert_eq!((result.steps, result.stopped), (1, false));
+	assert_ne!(grown(&rules, 2).to_rows(), grown(&finite, 2).to_rows());
+
+	// once growth stops within the steps, the result grows the same at any step
+	let mut rules = finite.clone();
+	let result = simplify(&mut rules, &mut axiom(), 2);
+	assert_eq!((result.steps, result.stopped), (2, true));
+	assert_eq!(grown(&rules, 100).to_rows(), grown(&finite, 100).to_rows());
+}
+
+#[test]
+fn simplified_rulesets_grow_the_same_body() {
+	let mut rng = ChaCha8Rng::seed_from_u64(10);
+
+	for _ in 0..300 {
+		let len = rng.gen_range(1..=5u8);
+		let mut rules: Vec<Grid> = (0..len).map(|_| Grid::random(&mut rng, len)).collect();
+		let steps = rng.gen_range(1..40);
+		let expected = grown(&rules, steps);
+
+		simplify(&mut rules, &mut axiom(), steps);
+		assert!(is_valid(&rules));
+
+		let state = grown(&rules, steps);
+		for (pos, cell) in &expected {
+			assert!(cell.same_type(&state.at(pos)));
+		}
+		for (pos, cell) in &state {
+			assert!(cell.same_type(&expected.at(pos)));
+		}
+	}
+}
@@ -0,0 +1,30 @@
+use soft_evolution::{
+	genetic_algorithm::{GeneticAlgorithm, statistics::{write_csv, CSV_HEADER}},
+	l_system::grid::Grid,
+	ls_evolve::{LS, EvolveParams},
+};
+
+#[test]
+fn history_records_every_generation() {
+	let goal = Grid::from_string(".W.\nWWW\n.W.", [1, 1]).unwrap();
+	let mut gen_alg = GeneticAlgorithm::<LS, EvolveParams>::new_seeded(100, 50, 0.5, EvolveParams::new(goal), 5);
+	gen_alg.perform_generations(4);
+
+	let history = gen_alg.history();
+	assert_eq!(history.len(), 5);
+	for (i, stats) in history.iter().enumerate() {
+		assert_eq!(stats.generation, i as u32);
+		assert!(stats.best >= stats.median && stats.median >= stats.worst);
+		assert!(stats.best >= stats.mean && stats.mean >= stats.worst);
+		assert_eq!(stats.rule_counts.iter().sum::<u32>(), 100);
+	}
+
+	let mut csv = Vec::new();
+	write_csv(history, &mut csv).unwrap();
+	let csv = String::from_utf8(csv).unwrap();
+	assert_eq!(csv.lines().next(), Some(CSV_HEADER));
+	assert_eq!(csv.lines().count(), 6);
+
+	gen_alg.reset();
+	assert_eq!(gen_alg.history().len(), 1);
+}
@@ -0,0 +1,61 @@
+use std::time::Duration;
+
+use soft_evolution::{
+	genetic_algorithm::{GeneticAlgorithm, islands::Islands, stopping::StopCondition},
+	l_system::grid::Grid,
+	ls_evolve::{LS, EvolveParams},
+};
+
+fn params() -> EvolveParams {
+	EvolveParams::new(Grid::from_string(".W.\nWWW\n.W.", [1, 1]).unwrap())
+}
+
+#[test]
+fn run_until_stops_at_the_first_condition() {
+	let mut gen_alg = GeneticAlgorithm::<LS, EvolveParams>::new_seeded(100, 50, 0.5, params(), 3);
+
+	let summary = gen_alg.run_until(&[StopCondition::GenerationCap(6), StopCondition::TimeLimit(Duration::from_secs(600))]);
+	assert_eq!(summary.stopped_by, Some(StopCondition::GenerationCap(6)));
+	assert_eq!(summary.generations, 6);
+	assert_eq!(summary.generation_number, 6);
+	assert_eq!(summary.best_fitness, gen_alg.best().1);
+
+	// conditions already reached stop before any generation
+	let summary = gen_alg.run_until(&[StopCondition::GenerationCap(4)]);
+	assert_eq!(summary.generations, 0);
+
+	let summary = gen_alg.run_until(&[]);
+	assert_eq!(summary.stopped_by, None);
+	assert_eq!(summary.generations, 0);
+}
+
+#[test]
+fn stagnation_and_target_fitness() {
+	let mut gen_alg = GeneticAlgorithm::<LS, EvolveParams>::new_seeded(100, 50, 0.5, params(), 3);
+
+	let summary = gen_alg.run_until(&[StopCondition::Stagnation(3), StopCondition::GenerationCap(200)]);
+	if summary.stopped_by == Some(StopCondition::Stagnation(3)) {
+		let bests: Vec<f32> = gen_alg.history().iter().map(|s| s.best).collect();
+		let before = bests[..bests.len() - 3].iter().copied().fold(f32::NEG_INFINITY, f32::max);
+		assert!(bests[bests.len() - 3..].iter().all(|&b| b <= before));
+	}
+
+	let target = gen_alg.best().1;
+	gen_alg.reset();
+	let summary = gen_alg.run_until(&[StopCondition::TargetFitness(target), StopCondition::GenerationCap(200)]);
+	if summary.stopped_by == Some(StopCondition::TargetFitness(target)) {
+		assert!(gen_alg.best().1 >= target);
+	}
+}
+
+#[test]
+fn islands_stop_together() {
+	let mut islands = Islands::<LS, EvolveParams>::new_seeded(3, 60, 30, 0.5, params(), 8);
+	let summary = islands.run_until(&[StopCondition::GenerationCap(5)]);
+	assert_eq!(summary.generation_number, 5);
+	assert!(islands.islands().iter().all(|i| i.generation_number() == 5));
+
+	// every island has a diversity of at least zero
+	assert_eq!(islands.stop_reason(&[StopCondition::DiversityCollapse(-1.0)], Duration::ZERO), None);
+	assert!(islands.stop_reason(&[StopCondition::DiversityCollapse(f32::MAX)], Duration::ZERO).is_some());
+}
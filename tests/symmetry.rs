@@ -0,0 +1,113 @@
+use rand::{SeedableRng, Rng};
+use rand_chacha::ChaCha8Rng;
+
+use soft_evolution::{
+	genetic_algorithm::{GeneticAlgorithm, checkpoint::Persist, evolve::Evolve},
+	l_system::{grid::Grid, cell::{Cell, Direction}, ruleset::{Ruleset, Symmetry}},
+	ls_evolve::{LS, Growth, EvolveParams},
+};
+
+fn random_rules(rng: &mut ChaCha8Rng) -> Vec<Grid> {
+	let len = rng.gen_range(1..=5u8);
+	(0..len).map(|_| Grid::random(rng, len)).collect()
+}
+
+fn is_mirrored(rule: &Grid) -> bool {
+	rule.into_iter().all(|([x, y], cell)| {
+		let image = match cell {
+			Cell::Stem(n, dir) => Cell::Stem(n, dir.mirror(true)),
+			cell => cell,
+		};
+		rule.at([-x, y]) == image
+	})
+}
+
+fn is_rotated(rule: &Grid) -> bool {
+	rule.into_iter().filter(|&(pos, _)| pos != [0, 0]).all(|(pos, cell)| {
+		let turn = Direction::LEFT;
+		rule.at(turn.rotate_coords(pos)) == turn.rotate_cell(cell)
+	})
+}
+
+#[test]
+fn symmetrized_rules_are_symmetric() {
+	let mut rng = ChaCha8Rng::seed_from_u64(11);
+
+	for _ in 0..300 {
+		let rules = random_rules(&mut rng);
+
+		let mut mirrored = rules.clone();
+		mirrored.symmetrize(Symmetry::Mirror);
+		assert!(mirrored.iter().all(is_mirrored));
+
+		let mut rotated = rules.clone();
+		rotated.symmetrize(Symmetry::Rotational);
+		assert!(rotated.iter().all(is_rotated));
+
+		// symmetric rules stay as they are
+		let mut again = mirrored.clone();
+		again.symmetrize(Symmetry::Mirror);
+		assert_eq!(again.canonical_hash(), mirrored.canonical_hash());
+	}
+}
+
+#[test]
+fn the_fuller_half_is_kept() {
+	// one cell left of the axis, three right of it
+	let mut rules = vec![Grid::from_rows(&["W0>.W", "..WW"], [1, 0]).unwrap()];
+	rules.symmetrize(Symmetry::Mirror);
+	assert_eq!(rules[0].to_rows(), vec!["W.0^.W", "WW.WW"]);
+	assert_eq!(rules[0].shift(), [2, 0]);
+}
+
+#[test]
+fn symmetrized_axioms_keep_a_stem() {
+	// a passive origin, the stem down left of it and a passive cell right of it: either quarter fills as many cells
+	let mut axiom = Grid::single(Cell::Passive);
+	axiom.insert_cell(Cell::Stem(0, Direction::UP), [-1, -1]);
+	axiom.insert_cell(Cell::Passive, [1, 0]);
+
+	let mut rotated = vec![axiom.clone()];
+	rotated.symmetrize(Symmetry::Rotational);
+	assert!(rotated[0].contents().iter().any(|cell| matches!(cell, Cell::Stem(_, _))));
+
+	let mut params = EvolveParams::new(Grid::from_string(".W.\nWWW\n.W.", [1, 1]).unwrap());
+	params.symmetry = Symmetry::Rotational;
+	let growth = Growth { axiom, step_share: 1.0 };
+	let mut ls = LS::with_growth(vec![Grid::from_rows(&["W0^"], [0, 0]).unwrap()], growth.clone());
+	assert!(ls.fitness(&params).is_finite());
+
+	// and an axiom whose every quarter loses the stem grows unsymmetrized
+	let mut axiom = Grid::single(Cell::Passive);
+	axiom.insert_cell(Cell::Stem(0, Direction::UP), [-1, -1]);
+	axiom.insert_cell(Cell::Passive, [1, 0]);
+	axiom.insert_cell(Cell::Passive, [2, 0]);
+	let mut ls = LS::with_growth(vec![Grid::from_rows(&["W0^"], [0, 0]).unwrap()], Growth { axiom: axiom.clone(), ..growth });
+	assert!(ls.fitness(&params).is_finite());
+	assert_eq!(ls.1.axiom.to_rows(), axiom.to_rows());
+}
+
+#[test]
+fn evolved_rules_keep_the_symmetry() {
+	let mut params = EvolveParams::new(Grid::from_string(".W.\nWWW\n.W.", [1, 1]).unwrap());
+	params.symmetry = Symmetry::Mirror;
+	let mut gen_alg = GeneticAlgorithm::<LS, EvolveParams>::new_seeded(60, 30, 0.5, params, 12);
+	gen_alg.perform_generations(5);
+
+	for (agent, _) in gen_alg.agents() {
+		assert!(agent.0.rules().iter().all(is_mirrored));
+	}
+}
+
+#[test]
+fn symmetry_is_saved_with_the_params() {
+	let mut params = EvolveParams::new(Grid::from_string("W", [0, 0]).unwrap());
+	params.symmetry = Symmetry::Rotational;
+	let data = params.persist();
+	let (restored, length) = EvolveParams::restore(&data).unwrap();
+	assert_eq!((restored.symmetry, length), (Symmetry::Rotational, data.len()));
+
+	// params saved before symmetry existed
+	let (old, _) = EvolveParams::restore(&data[..data.len() - 1]).unwrap();
+	assert_eq!(old.symmetry, Symmetry::None);
+}